@@ -67,6 +67,9 @@
           pname = "datapass";
           version = "0.1.0";
 
+          # The binary needs the optional CLI, TUI and HTTP features
+          cargoExtraArgs = "--locked --all-features";
+
           buildInputs = with pkgs;
             (pkgs.lib.optionals (!pkgs.stdenv.isDarwin) [
               openssl
@@ -152,7 +155,7 @@
             cargo-test = {
               enable = true;
               name = "cargo test";
-              entry = "${rustToolchain}/bin/cargo test";
+              entry = "${rustToolchain}/bin/cargo test --all-features";
               files = "\\.(rs|toml)$";
               pass_filenames = false;
             };
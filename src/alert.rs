@@ -0,0 +1,465 @@
+use crate::config::state_dir;
+use crate::error::Result;
+use crate::types::DataUsage;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::PathBuf;
+
+const STATE_FILE: &str = "alerts.json";
+
+/// Alert rules (`[alerts]` config section), evaluated after every fresh reading
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfig {
+    /// Evaluate the rules; the section being present enables it
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Alert when the used share of the volume reaches these percentages
+    #[serde(default)]
+    pub usage_percent: Vec<f64>,
+
+    /// Alert when less than this many GB remain
+    #[serde(default)]
+    pub remaining_gb: Vec<f64>,
+
+    /// Alert when this many days or fewer are left in the cycle
+    #[serde(default)]
+    pub days_left: Vec<i64>,
+
+    /// Alert when the plan becomes throttled, unlimited or available again
+    #[serde(default)]
+    pub status_change: bool,
+
+    /// How far (in percent of the volume) usage has to fall back below a
+    /// threshold before its alert can fire again
+    #[serde(default = "default_hysteresis_percent")]
+    pub hysteresis_percent: f64,
+
+    /// Where fired alerts are remembered between runs
+    /// (default: `$XDG_STATE_HOME/datapass/alerts.json`)
+    pub state_file: Option<PathBuf>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_hysteresis_percent() -> f64 {
+    2.0
+}
+
+/// Whether data can be used at full speed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    /// Volume left at full speed
+    Available,
+    /// The volume is used up and the connection is throttled
+    Throttled,
+    /// No volume limit
+    Unlimited,
+}
+
+impl PlanStatus {
+    pub fn of(usage: &DataUsage) -> Self {
+        if usage.is_unlimited {
+            PlanStatus::Unlimited
+        } else if usage.remaining_gb <= 0.0 {
+            PlanStatus::Throttled
+        } else {
+            PlanStatus::Available
+        }
+    }
+}
+
+impl fmt::Display for PlanStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanStatus::Available => write!(f, "available"),
+            PlanStatus::Throttled => write!(f, "throttled"),
+            PlanStatus::Unlimited => write!(f, "unlimited"),
+        }
+    }
+}
+
+/// How urgent an alert is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// One alert rule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    UsagePercent(f64),
+    RemainingGb(f64),
+    DaysLeft(i64),
+    StatusChange,
+}
+
+impl Rule {
+    /// Stable name of the rule, e.g. "usage_percent:80", as kept in the state
+    pub fn id(&self) -> String {
+        match self {
+            Rule::UsagePercent(threshold) => format!("usage_percent:{}", threshold),
+            Rule::RemainingGb(threshold) => format!("remaining_gb:{}", threshold),
+            Rule::DaysLeft(threshold) => format!("days_left:{}", threshold),
+            Rule::StatusChange => "status_change".to_string(),
+        }
+    }
+
+    /// Severity of the rule's alerts: running out of volume is critical, the
+    /// cycle ending is a warning. Status changes depend on the new status.
+    pub fn severity(&self, status: PlanStatus) -> Severity {
+        match self {
+            Rule::UsagePercent(threshold) if *threshold >= 90.0 => Severity::Critical,
+            Rule::UsagePercent(_) | Rule::DaysLeft(_) => Severity::Warning,
+            Rule::RemainingGb(_) => Severity::Critical,
+            Rule::StatusChange if status == PlanStatus::Throttled => Severity::Critical,
+            Rule::StatusChange => Severity::Info,
+        }
+    }
+}
+
+impl AlertConfig {
+    /// The configured threshold rules; status changes are handled apart
+    pub fn rules(&self) -> Vec<Rule> {
+        let mut rules: Vec<Rule> = Vec::new();
+        rules.extend(self.usage_percent.iter().copied().map(Rule::UsagePercent));
+        rules.extend(self.remaining_gb.iter().copied().map(Rule::RemainingGb));
+        rules.extend(self.days_left.iter().copied().map(Rule::DaysLeft));
+        rules
+    }
+
+    /// The store for fired alerts this config points at
+    pub fn state_store(&self) -> AlertStateStore {
+        AlertStateStore::new(
+            self.state_file
+                .clone()
+                .unwrap_or_else(AlertStateStore::default_path),
+        )
+    }
+}
+
+/// An alert that fired for a reading
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    /// Rule name, e.g. "usage_percent:90" or "status_change"
+    pub rule: String,
+
+    /// What happened, e.g. "91.2% of the volume used (alert at 90%)"
+    pub message: String,
+
+    pub severity: Severity,
+
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Alert {
+    /// The alert sent by `datapass notify test`
+    pub fn test() -> Self {
+        Self {
+            rule: "test".to_string(),
+            message: "Test notification from datapass".to_string(),
+            severity: Severity::Info,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// What the alerts remember between runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertState {
+    /// Rules that fired and have not re-armed yet
+    #[serde(default)]
+    pub active: BTreeSet<String>,
+
+    /// Plan status at the last reading
+    pub status: Option<PlanStatus>,
+}
+
+/// Evaluate the rules for a reading. A rule fires once when its threshold is
+/// crossed and re-arms only when the reading is back past the threshold by
+/// the hysteresis margin (e.g. after a renewal or a booking).
+pub fn evaluate(
+    config: &AlertConfig,
+    usage: &DataUsage,
+    state: &mut AlertState,
+    now: DateTime<Utc>,
+    today: NaiveDate,
+) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+    let margin = config.hysteresis_percent.max(0.0);
+    let status = PlanStatus::of(usage);
+
+    for rule in config.rules() {
+        // (crossed, re-armed, message)
+        let (crossed, rearmed, message) = match rule {
+            Rule::UsagePercent(threshold) => (
+                !usage.is_unlimited && usage.percentage >= threshold,
+                usage.is_unlimited || usage.percentage < threshold - margin,
+                format!(
+                    "{:.1}% of the volume used (alert at {}%)",
+                    usage.percentage, threshold
+                ),
+            ),
+            Rule::RemainingGb(threshold) => (
+                !usage.is_unlimited && usage.remaining_gb < threshold,
+                usage.is_unlimited
+                    || usage.remaining_gb >= threshold + usage.total_gb * margin / 100.0,
+                format!(
+                    "{:.2} GB remaining (alert below {} GB)",
+                    usage.remaining_gb, threshold
+                ),
+            ),
+            Rule::DaysLeft(threshold) => match usage.days_left(today) {
+                Some(days) => (
+                    !usage.is_unlimited && days <= threshold,
+                    usage.is_unlimited || days > threshold,
+                    format!(
+                        "{} days left until {} (alert at {} days)",
+                        days,
+                        usage
+                            .valid_until
+                            .as_deref()
+                            .unwrap_or("the end of the cycle"),
+                        threshold
+                    ),
+                ),
+                None => (false, false, String::new()),
+            },
+            Rule::StatusChange => continue,
+        };
+
+        let id = rule.id();
+        if crossed && state.active.insert(id.clone()) {
+            alerts.push(Alert {
+                rule: id,
+                message,
+                severity: rule.severity(status),
+                timestamp: now,
+            });
+        } else if rearmed {
+            state.active.remove(&id);
+        }
+    }
+
+    if let Some(previous) = state.status.replace(status) {
+        if config.status_change && previous != status {
+            alerts.push(Alert {
+                rule: Rule::StatusChange.id(),
+                message: format!("Plan status changed from {} to {}", previous, status),
+                severity: Rule::StatusChange.severity(status),
+                timestamp: now,
+            });
+        }
+    }
+
+    alerts
+}
+
+/// Keeps the alert state on disk
+/// (default: `$XDG_STATE_HOME/datapass/alerts.json`)
+pub struct AlertStateStore {
+    path: PathBuf,
+}
+
+impl AlertStateStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Default state location
+    pub fn default_path() -> PathBuf {
+        state_dir().join(STATE_FILE)
+    }
+
+    /// The stored state; a missing or unreadable file starts afresh
+    pub fn load(&self) -> AlertState {
+        let Ok(text) = std::fs::read_to_string(&self.path) else {
+            return AlertState::default();
+        };
+
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!(
+                "Ignoring unreadable alert state {}: {}",
+                self.path.display(),
+                e
+            );
+            AlertState::default()
+        })
+    }
+
+    /// Replace the stored state
+    pub fn save(&self, state: &AlertState) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let temp_path = self
+            .path
+            .with_extension(format!("tmp.{}", std::process::id()));
+        std::fs::write(&temp_path, serde_json::to_string_pretty(state)?)?;
+        std::fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// Evaluates the alert rules against fresh readings, keeping the state on disk
+pub struct Alerts {
+    config: AlertConfig,
+    store: AlertStateStore,
+}
+
+impl Alerts {
+    pub fn new(config: AlertConfig) -> Self {
+        let store = config.state_store();
+        Self { config, store }
+    }
+
+    /// The alerts that fire for a reading
+    pub fn check(&mut self, usage: &DataUsage) -> Result<Vec<Alert>> {
+        // Read the state every time: a single run, the daemon and the TUI
+        // may take turns
+        let mut state = self.store.load();
+        let before = state.clone();
+
+        let alerts = evaluate(
+            &self.config,
+            usage,
+            &mut state,
+            Utc::now(),
+            Local::now().date_naive(),
+        );
+
+        if state != before {
+            self.store.save(&state)?;
+        }
+
+        for alert in &alerts {
+            log::warn!("Alert {}: {}", alert.rule, alert.message);
+        }
+
+        Ok(alerts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AlertConfig {
+        AlertConfig {
+            enabled: true,
+            usage_percent: vec![80.0, 90.0],
+            remaining_gb: vec![1.0],
+            days_left: vec![3],
+            status_change: true,
+            hysteresis_percent: 2.0,
+            state_file: None,
+        }
+    }
+
+    fn usage(remaining_gb: f64) -> DataUsage {
+        DataUsage::new(
+            remaining_gb,
+            50.0,
+            None,
+            Some("21. Februar 2026".to_string()),
+        )
+    }
+
+    fn rules(alerts: &[Alert]) -> Vec<&str> {
+        alerts.iter().map(|alert| alert.rule.as_str()).collect()
+    }
+
+    fn check(usage: &DataUsage, state: &mut AlertState) -> Vec<Alert> {
+        let today = NaiveDate::from_ymd_opt(2026, 2, 10).unwrap();
+        evaluate(&config(), usage, state, Utc::now(), today)
+    }
+
+    #[test]
+    fn test_thresholds_fire_once() {
+        let mut state = AlertState::default();
+
+        assert!(check(&usage(20.0), &mut state).is_empty());
+        // 81% used
+        let alerts = check(&usage(9.5), &mut state);
+        assert_eq!(rules(&alerts), ["usage_percent:80"]);
+        assert_eq!(alerts[0].severity, Severity::Warning);
+        assert!(alerts[0].message.starts_with("81.0% of the volume used"));
+        assert!(check(&usage(9.0), &mut state).is_empty());
+
+        // 98% used: the 90% and remaining alerts fire, the 80% one stays quiet
+        assert_eq!(
+            rules(&check(&usage(0.9), &mut state)),
+            ["usage_percent:90", "remaining_gb:1"]
+        );
+    }
+
+    #[test]
+    fn test_hysteresis_rearms_after_falling_back() {
+        let mut state = AlertState::default();
+        check(&usage(9.5), &mut state);
+
+        // 79% used is within the 2% margin: still armed off
+        assert!(check(&usage(10.5), &mut state).is_empty());
+        assert!(check(&usage(9.9), &mut state).is_empty());
+
+        // 70% used after a booking re-arms the rule
+        assert!(check(&usage(15.0), &mut state).is_empty());
+        assert_eq!(rules(&check(&usage(9.5), &mut state)), ["usage_percent:80"]);
+    }
+
+    #[test]
+    fn test_days_left_and_status_change() {
+        let mut state = AlertState::default();
+        let mut ending = usage(20.0);
+        ending.valid_until = Some("12. Februar 2026".to_string());
+
+        assert_eq!(rules(&check(&ending, &mut state)), ["days_left:3"]);
+
+        // Used up: throttled
+        let alerts = check(&usage(0.0), &mut state);
+        assert!(rules(&alerts).contains(&"status_change"));
+        assert_eq!(
+            alerts.last().unwrap().message,
+            "Plan status changed from available to throttled"
+        );
+        assert_eq!(alerts.last().unwrap().severity, Severity::Critical);
+        assert!(state.active.contains("usage_percent:90"));
+        assert!(!state.active.contains("days_left:3"));
+    }
+
+    #[test]
+    fn test_state_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = config();
+        config.days_left.clear();
+        config.state_file = Some(dir.path().join("alerts.json"));
+
+        let mut alerts = Alerts::new(config.clone());
+        assert_eq!(alerts.check(&usage(9.5)).unwrap().len(), 1);
+
+        // A new run remembers the fired alert
+        let mut alerts = Alerts::new(config);
+        assert!(alerts.check(&usage(9.4)).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+//! Blocking client, built on the async implementation
+
+use crate::client::{ClientBuilder, ClientCore};
+use crate::error::Result;
+use crate::parser;
+use crate::types::{DataReport, DataUsage, PassOffer, TariffTransparency};
+
+/// Blocking client for datapass.de.
+///
+/// Runs the async client on its own single-threaded runtime, which keeps the
+/// connection pool alive between calls. Must not be used from within an
+/// async runtime.
+///
+/// ```no_run
+/// let client = datapass::blocking::DatapassClient::builder()
+///     .retries(2)
+///     .build_blocking()?;
+///
+/// let report = client.fetch_report()?;
+/// println!("{:.2} GB left", report.usage.remaining_gb);
+/// # Ok::<(), datapass::DatapassError>(())
+/// ```
+#[derive(Debug)]
+pub struct DatapassClient {
+    core: ClientCore,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl DatapassClient {
+    /// Client for https://datapass.de with the default settings
+    pub fn new() -> Result<Self> {
+        Self::builder().build_blocking()
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    pub(crate) fn from_core(core: ClientCore) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+
+        Ok(Self { core, runtime })
+    }
+
+    /// Fetch the raw datapass page
+    pub fn fetch_html(&self) -> Result<String> {
+        Ok(self.runtime.block_on(self.core.get(self.core.url()))?.0)
+    }
+
+    /// Fetch and parse the data usage
+    pub fn fetch(&self) -> Result<DataUsage> {
+        parser::parse_html(&self.fetch_html()?)
+    }
+
+    /// Fetch and parse the data passes currently offered for booking
+    pub fn fetch_offers(&self) -> Result<Vec<PassOffer>> {
+        parser::parse_offers(&self.fetch_html()?)
+    }
+
+    /// Fetch the page once and parse usage, passes, offers and fetch details
+    pub fn fetch_report(&self) -> Result<DataReport> {
+        self.runtime.block_on(self.core.report())
+    }
+
+    /// Fetch and parse the tariff transparency information
+    pub fn fetch_tariff(&self) -> Result<TariffTransparency> {
+        let url = self.core.tariff_url()?;
+        let (html, _) = self.runtime.block_on(self.core.get(&url))?;
+        parser::parse_tariff_transparency(&html)
+    }
+}
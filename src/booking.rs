@@ -0,0 +1,486 @@
+use crate::error::{DatapassError, Result};
+use crate::fetcher;
+use crate::parser;
+use crate::types::PassOffer;
+use reqwest::blocking::Client;
+use reqwest::header::{ACCEPT, COOKIE, LOCATION, SET_COOKIE};
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+
+const MAX_REDIRECTS: usize = 10;
+
+/// Labels of submit buttons that confirm a purchase
+const CONFIRM_LABELS: &[&str] = &[
+    "kaufen",
+    "buchen",
+    "bestellen",
+    "bestätigen",
+    "buy",
+    "book",
+    "order",
+    "confirm",
+];
+
+/// Phrases on the result page that indicate a failed booking
+const FAILURE_PHRASES: &[&str] = &[
+    "fehler",
+    "fehlgeschlagen",
+    "nicht möglich",
+    "konnte nicht",
+    "abgelehnt",
+    "error",
+    "failed",
+    "not possible",
+    "could not",
+    "rejected",
+];
+
+/// Phrases on the result page that indicate a successful booking
+const SUCCESS_PHRASES: &[&str] = &[
+    "erfolgreich",
+    "vielen dank",
+    "wurde gebucht",
+    "successful",
+    "thank you",
+    "has been booked",
+];
+
+/// Outcome of a booking attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookingStatus {
+    /// Stopped before the final submission
+    DryRun,
+    /// The result page confirmed the booking
+    Booked,
+    /// The result page reported an error
+    Rejected,
+    /// The form was submitted but the result page could not be recognized
+    Unconfirmed,
+}
+
+/// Result of a booking attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct BookingResult {
+    /// The booked offer
+    pub offer: PassOffer,
+
+    /// Outcome of the attempt
+    pub status: BookingStatus,
+
+    /// Message shown on the result page (or what a dry run would submit)
+    pub message: Option<String>,
+}
+
+/// Confirmation form found on an offer's selection page
+#[derive(Debug, Clone)]
+pub struct BookingForm {
+    /// Absolute URL the form is submitted to
+    pub action: Url,
+
+    /// Form fields including the confirming submit button
+    pub fields: Vec<(String, String)>,
+}
+
+/// HTTP session that carries cookies through the booking flow.
+///
+/// The booking links carry a token that is bound to the session, so the
+/// offer catalog, the selection page and the final submission must all be
+/// requested with the same cookies.
+pub struct BookingSession {
+    client: Client,
+    base_url: Url,
+    cookies: Vec<(String, String)>,
+}
+
+impl BookingSession {
+    /// Create a session for the given (or default) datapass URL
+    pub fn new(url: Option<&str>, cookie: Option<&str>) -> Result<Self> {
+        // Redirects are followed manually so cookies set along the way are kept
+        let client = fetcher::build_client(reqwest::redirect::Policy::none())?;
+        let base_url = fetcher::base_url(url)?;
+
+        let mut session = Self {
+            client,
+            base_url,
+            cookies: Vec::new(),
+        };
+        session.add_cookies(cookie.unwrap_or(fetcher::DEFAULT_COOKIES));
+
+        Ok(session)
+    }
+
+    /// Fetch and parse the offers currently shown on the main page
+    pub fn offers(&mut self) -> Result<Vec<PassOffer>> {
+        let (_, html) = self.request(self.base_url.clone(), None)?;
+        parser::parse_offers(&html)
+    }
+
+    /// Find an offer by ID or name in the current catalog
+    pub fn find_offer(&mut self, id_or_name: &str) -> Result<PassOffer> {
+        let offers = self.offers()?;
+
+        let offer = offers
+            .into_iter()
+            .find(|offer| offer.matches(id_or_name))
+            .ok_or_else(|| {
+                DatapassError::BookingError(format!("No offer matching '{}' found", id_or_name))
+            })?;
+
+        if !offer.is_bookable() {
+            return Err(DatapassError::BookingError(format!(
+                "'{}' cannot be booked right now",
+                offer.name
+            )));
+        }
+
+        Ok(offer)
+    }
+
+    /// Open the offer's selection page and return its confirmation form
+    pub fn select(&mut self, offer: &PassOffer) -> Result<BookingForm> {
+        let path = offer.booking_path.as_deref().ok_or_else(|| {
+            DatapassError::BookingError(format!("'{}' has no booking link", offer.name))
+        })?;
+
+        let url = fetcher::join_url(&self.base_url, path)?;
+        log::info!("Opening booking page: {}", url);
+
+        let (page_url, html) = self.request(url, None)?;
+        parse_booking_form(&page_url, &html)
+    }
+
+    /// Submit the confirmation form and interpret the result page.
+    ///
+    /// Once the form is on its way the order may have gone through, so a
+    /// failed request (timeout, server error, broken redirect) is reported
+    /// as `Unconfirmed` rather than as an error.
+    pub fn submit(&mut self, offer: &PassOffer, form: &BookingForm) -> Result<BookingResult> {
+        log::info!("Submitting booking for '{}' to {}", offer.name, form.action);
+
+        let (status, message) = match self.request(form.action.clone(), Some(&form.fields)) {
+            Ok((_, html)) => parse_booking_result(&html),
+            Err(e) => {
+                log::warn!("No result page for the booking of '{}': {}", offer.name, e);
+                (
+                    BookingStatus::Unconfirmed,
+                    Some(format!("No result page after submitting: {}", e)),
+                )
+            }
+        };
+
+        log::info!("Booking result for '{}': {:?}", offer.name, status);
+
+        Ok(BookingResult {
+            offer: offer.clone(),
+            status,
+            message,
+        })
+    }
+
+    /// Run the booking flow for an offer, stopping before the final
+    /// submission when `dry_run` is set
+    pub fn book(&mut self, offer: &PassOffer, dry_run: bool) -> Result<BookingResult> {
+        let form = self.select(offer)?;
+
+        if dry_run {
+            return Ok(BookingResult {
+                offer: offer.clone(),
+                status: BookingStatus::DryRun,
+                message: Some(format!("Would submit booking form to {}", form.action)),
+            });
+        }
+
+        self.submit(offer, &form)
+    }
+
+    /// Send a GET (or form POST) request, following redirects and keeping cookies.
+    /// Returns the final URL and the response body. The session cookies only
+    /// ever go to the datapass site: a link or redirect elsewhere is an error.
+    fn request(&mut self, url: Url, form: Option<&[(String, String)]>) -> Result<(Url, String)> {
+        let mut url = url;
+        let mut form = form;
+
+        for _ in 0..=MAX_REDIRECTS {
+            if url.origin() != self.base_url.origin() {
+                return Err(DatapassError::BookingError(format!(
+                    "Refusing to send the session to {}, which is not on {}",
+                    url,
+                    self.base_url.origin().ascii_serialization()
+                )));
+            }
+
+            let request = match form {
+                Some(fields) => self.client.post(url.clone()).form(fields),
+                None => self.client.get(url.clone()),
+            };
+
+            let response = request
+                .header(ACCEPT, fetcher::ACCEPT_HTML)
+                .header(COOKIE, self.cookie_header())
+                .send()?;
+
+            for value in response.headers().get_all(SET_COOKIE) {
+                if let Ok(value) = value.to_str() {
+                    self.add_cookies(value.split(';').next().unwrap_or_default());
+                }
+            }
+
+            if response.status().is_redirection() {
+                let location = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .ok_or_else(|| {
+                        DatapassError::BookingError("Redirect without location".to_string())
+                    })?;
+
+                log::debug!("Following redirect to {}", location);
+                url = fetcher::join_url(&url, location)?;
+                // Post/Redirect/Get: the redirect target is always fetched with GET
+                form = None;
+                continue;
+            }
+
+            if !response.status().is_success() {
+                return Err(DatapassError::FetchError(
+                    response.error_for_status().unwrap_err(),
+                ));
+            }
+
+            return Ok((url, response.text()?));
+        }
+
+        Err(DatapassError::BookingError(
+            "Too many redirects during booking".to_string(),
+        ))
+    }
+
+    /// Merge "name=value; name2=value2" pairs into the session cookies
+    fn add_cookies(&mut self, cookies: &str) {
+        for pair in cookies.split(';') {
+            if let Some((name, value)) = pair.trim().split_once('=') {
+                let name = name.trim().to_string();
+                let value = value.trim().to_string();
+
+                match self
+                    .cookies
+                    .iter_mut()
+                    .find(|(existing, _)| *existing == name)
+                {
+                    Some(cookie) => cookie.1 = value,
+                    None => self.cookies.push((name, value)),
+                }
+            }
+        }
+    }
+
+    fn cookie_header(&self) -> String {
+        self.cookies
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Find the confirmation form on a selection page: the first form with a
+/// submit button that reads like a purchase ("Jetzt kaufen", "Book"). Other
+/// forms (search, cancel) are never submitted, as the flow spends money.
+fn parse_booking_form(page_url: &Url, html: &str) -> Result<BookingForm> {
+    let document = Html::parse_document(html);
+
+    let form_selector = Selector::parse("form")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let input_selector = Selector::parse("input[name]")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let submit_selector = Selector::parse("button, input[type=submit]")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let (form, submit) = document
+        .select(&form_selector)
+        .find_map(|form| {
+            form.select(&submit_selector)
+                .find(|elem| {
+                    let kind = elem.value().attr("type").unwrap_or("submit");
+                    kind.eq_ignore_ascii_case("submit")
+                        && CONFIRM_LABELS
+                            .iter()
+                            .any(|label| submit_label(elem).contains(label))
+                })
+                .map(|submit| (form, submit))
+        })
+        .ok_or_else(|| {
+            DatapassError::DataNotFound(
+                "no form with a confirming submit button on the offer page".to_string(),
+            )
+        })?;
+
+    let action = match form.value().attr("action") {
+        Some(action) if !action.is_empty() => fetcher::join_url(page_url, action)?,
+        _ => page_url.clone(),
+    };
+
+    let mut fields = Vec::new();
+
+    for input in form.select(&input_selector) {
+        let kind = input.value().attr("type").unwrap_or("text").to_lowercase();
+        let checked = input.value().attr("checked").is_some();
+
+        let include = match kind.as_str() {
+            "submit" | "button" | "image" | "reset" => false,
+            "checkbox" | "radio" => checked,
+            _ => true,
+        };
+
+        if include {
+            if let Some(name) = input.value().attr("name") {
+                let value = input.value().attr("value").unwrap_or_default();
+                fields.push((name.to_string(), value.to_string()));
+            }
+        }
+    }
+
+    // The pressed submit button is part of the submission (JSF relies on it)
+    if let Some(name) = submit.value().attr("name") {
+        let value = submit.value().attr("value").unwrap_or_default();
+        fields.push((name.to_string(), value.to_string()));
+    }
+
+    Ok(BookingForm { action, fields })
+}
+
+/// Lowercase label of a submit button (value attribute or text content)
+fn submit_label(elem: &ElementRef) -> String {
+    let text = elem.text().collect::<String>();
+    let label = elem.value().attr("value").unwrap_or(&text);
+    label.to_lowercase()
+}
+
+/// Interpret the page shown after submitting a booking
+fn parse_booking_result(html: &str) -> (BookingStatus, Option<String>) {
+    let document = Html::parse_document(html);
+
+    let message_selector =
+        match Selector::parse("div.message-box-container, div.message-box, h1, h2") {
+            Ok(s) => s,
+            Err(_) => return (BookingStatus::Unconfirmed, None),
+        };
+
+    let mut messages: Vec<String> = Vec::new();
+
+    for elem in document.select(&message_selector) {
+        let text = elem.text().collect::<Vec<_>>().join(" ");
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        // Nested matches (a heading inside a message box) would repeat text
+        if !text.is_empty() && !messages.iter().any(|m| m.contains(&text)) {
+            messages.push(text);
+        }
+    }
+
+    let message = if messages.is_empty() {
+        None
+    } else {
+        Some(messages.join(" "))
+    };
+
+    let lower = message.as_deref().unwrap_or_default().to_lowercase();
+
+    let status = if FAILURE_PHRASES.iter().any(|phrase| lower.contains(phrase)) {
+        BookingStatus::Rejected
+    } else if SUCCESS_PHRASES.iter().any(|phrase| lower.contains(phrase)) {
+        BookingStatus::Booked
+    } else {
+        BookingStatus::Unconfirmed
+    };
+
+    (status, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_booking_form_prefers_confirm_button() {
+        let html = r#"
+            <html><body>
+                <form id="search" action="/search"><button type="submit">Suchen</button></form>
+                <form id="confirm" action="/pass/confirm" method="post">
+                    <input type="hidden" name="confirm" value="confirm" />
+                    <input type="hidden" name="jakarta.faces.ViewState" value="-123:456" />
+                    <input type="checkbox" name="newsletter" value="yes" />
+                    <input type="checkbox" name="terms" value="accepted" checked="checked" />
+                    <button type="button" name="back">Zurück</button>
+                    <button type="submit" name="confirm:buy" value="buy">Jetzt kaufen</button>
+                </form>
+            </body></html>
+        "#;
+
+        let page_url = Url::parse("https://datapass.de/pass/select/GDPXDF13?crt=abc").unwrap();
+        let form = parse_booking_form(&page_url, html).unwrap();
+
+        assert_eq!(form.action.as_str(), "https://datapass.de/pass/confirm");
+        assert_eq!(
+            form.fields,
+            vec![
+                ("confirm".to_string(), "confirm".to_string()),
+                (
+                    "jakarta.faces.ViewState".to_string(),
+                    "-123:456".to_string()
+                ),
+                ("terms".to_string(), "accepted".to_string()),
+                ("confirm:buy".to_string(), "buy".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_booking_form_missing() {
+        let page_url = Url::parse("https://datapass.de/pass/select/X").unwrap();
+        let result = parse_booking_form(&page_url, "<html><body>Nothing</body></html>");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_booking_form_ignores_unrelated_forms() {
+        let html = r#"
+            <html><body>
+                <form action="/search"><button type="submit">Suchen</button></form>
+                <form action="/pass/cancel" method="post">
+                    <input type="submit" name="cancel" value="Abbrechen" />
+                </form>
+            </body></html>
+        "#;
+
+        let page_url = Url::parse("https://datapass.de/pass/select/X").unwrap();
+        let result = parse_booking_form(&page_url, html);
+        assert!(matches!(result, Err(DatapassError::DataNotFound(_))));
+    }
+
+    #[test]
+    fn test_parse_booking_result() {
+        let booked = r#"<html><body><h1>Vielen Dank!</h1>
+            <div class="message-box-container">Ihr Pass wurde erfolgreich gebucht.</div>
+            </body></html>"#;
+        let (status, message) = parse_booking_result(booked);
+        assert_eq!(status, BookingStatus::Booked);
+        assert_eq!(
+            message,
+            Some("Vielen Dank! Ihr Pass wurde erfolgreich gebucht.".to_string())
+        );
+
+        let rejected = r#"<html><body><div class="message-box">
+            Die Buchung ist leider nicht möglich.</div></body></html>"#;
+        let (status, _) = parse_booking_result(rejected);
+        assert_eq!(status, BookingStatus::Rejected);
+
+        let (status, message) = parse_booking_result("<html><body><p>?</p></body></html>");
+        assert_eq!(status, BookingStatus::Unconfirmed);
+        assert_eq!(message, None);
+    }
+}
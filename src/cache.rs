@@ -0,0 +1,140 @@
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A fetched page together with its validators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// URL the page was fetched from
+    pub url: String,
+
+    /// `ETag` response header, sent back as `If-None-Match`
+    pub etag: Option<String>,
+
+    /// `Last-Modified` response header, sent back as `If-Modified-Since`
+    pub last_modified: Option<String>,
+
+    /// When the page was last fetched or revalidated
+    pub fetched_at: DateTime<Utc>,
+
+    /// Page content
+    pub body: String,
+}
+
+impl CachedResponse {
+    /// Time since the page was last fetched or revalidated
+    pub fn age(&self) -> Duration {
+        (Utc::now() - self.fetched_at)
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Whether the page may be served without asking the server
+    pub fn is_fresh(&self, max_age: Duration) -> bool {
+        self.age() <= max_age
+    }
+}
+
+/// On-disk cache of fetched pages (default: `$XDG_CACHE_HOME/datapass`),
+/// one JSON file per URL and cookie combination. Writes go through a
+/// temporary file and a rename, so concurrent invocations never see a
+/// partially written entry.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Default cache directory
+    pub fn default_dir() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("datapass")
+    }
+
+    /// Look up a cached page; unreadable entries count as missing
+    pub fn get(&self, url: &str, cookie: &str) -> Option<CachedResponse> {
+        let text = std::fs::read_to_string(self.entry_path(url, cookie)).ok()?;
+
+        match serde_json::from_str::<CachedResponse>(&text) {
+            Ok(entry) if entry.url == url => Some(entry),
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("Ignoring unreadable cache entry for {}: {}", url, e);
+                None
+            }
+        }
+    }
+
+    /// Store a page
+    pub fn put(&self, cookie: &str, entry: &CachedResponse) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let path = self.entry_path(&entry.url, cookie);
+        let temp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+
+        std::fs::write(&temp_path, serde_json::to_string(entry)?)?;
+        std::fs::rename(&temp_path, &path)?;
+
+        Ok(())
+    }
+
+    /// Entries are keyed by URL and cookies, since the cookies select the
+    /// page language and view
+    fn entry_path(&self, url: &str, cookie: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(&[url, "\n", cookie])))
+    }
+}
+
+/// FNV-1a hash, stable across Rust versions (unlike `DefaultHasher`)
+fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for byte in parts.iter().flat_map(|part| part.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf());
+
+        let entry = CachedResponse {
+            url: "https://datapass.de".to_string(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+            fetched_at: Utc::now(),
+            body: "<html></html>".to_string(),
+        };
+        cache.put("lang=en", &entry).unwrap();
+
+        let cached = cache.get("https://datapass.de", "lang=en").unwrap();
+        assert_eq!(cached.etag, entry.etag);
+        assert_eq!(cached.body, entry.body);
+        assert!(cached.is_fresh(Duration::from_secs(60)));
+
+        // Different cookies select a different page
+        assert!(cache.get("https://datapass.de", "lang=de").is_none());
+    }
+
+    #[test]
+    fn test_fnv1a_is_stable() {
+        assert_eq!(fnv1a(&[""]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(&["a"]), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,358 @@
+use crate::alert::PlanStatus;
+use crate::types::DataUsage;
+use chrono::NaiveDate;
+use std::fmt;
+use std::str::FromStr;
+
+/// Service name at the start of the status line
+const SERVICE: &str = "DATAPASS";
+
+/// A `--warning` or `--critical` threshold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    /// This share of the volume is used (e.g. "80%")
+    UsedPercent(f64),
+
+    /// Less than this many GB are left (e.g. "5GB" or "500MB")
+    RemainingGb(f64),
+}
+
+impl Threshold {
+    /// Whether the reading is outside the perfdata range, as a monitoring
+    /// plugin would judge it: the threshold value itself is still fine
+    fn is_reached(&self, data: &DataUsage) -> bool {
+        match *self {
+            Threshold::UsedPercent(percent) => data.percentage > percent,
+            Threshold::RemainingGb(gb) => data.remaining_gb < gb,
+        }
+    }
+
+    /// The threshold as a perfdata range for the matching value
+    fn range(&self) -> String {
+        match *self {
+            Threshold::UsedPercent(percent) => number(percent),
+            Threshold::RemainingGb(gb) => format!("{}:", number(gb)),
+        }
+    }
+}
+
+impl FromStr for Threshold {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let lower = text.to_lowercase();
+        let (digits, threshold): (&str, fn(f64) -> Threshold) =
+            if let Some(digits) = lower.strip_suffix('%') {
+                (digits, Threshold::UsedPercent)
+            } else if let Some(digits) = lower.strip_suffix("gb") {
+                (digits, Threshold::RemainingGb)
+            } else if let Some(digits) = lower.strip_suffix("mb") {
+                (digits, |mb| Threshold::RemainingGb(mb / 1000.0))
+            } else {
+                return Err(format!(
+                    "expected a used share (e.g. 80%) or a volume left (e.g. 5GB, 500MB), got '{}'",
+                    text
+                ));
+            };
+
+        let value: f64 = digits
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid number in '{}'", text))?;
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!("threshold must not be negative, got '{}'", text));
+        }
+
+        Ok(threshold(value))
+    }
+}
+
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Threshold::UsedPercent(percent) => write!(f, "above {}% used", number(*percent)),
+            Threshold::RemainingGb(gb) => write!(f, "below {} GB left", number(*gb)),
+        }
+    }
+}
+
+/// Service states of the monitoring plugin API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckState {
+    Ok,
+    Warning,
+    Critical,
+    Unknown,
+}
+
+impl CheckState {
+    /// The plugin's exit code: 0, 1, 2 or 3
+    pub fn exit_code(self) -> i32 {
+        match self {
+            CheckState::Ok => 0,
+            CheckState::Warning => 1,
+            CheckState::Critical => 2,
+            CheckState::Unknown => 3,
+        }
+    }
+}
+
+impl fmt::Display for CheckState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckState::Ok => write!(f, "OK"),
+            CheckState::Warning => write!(f, "WARNING"),
+            CheckState::Critical => write!(f, "CRITICAL"),
+            CheckState::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+/// What `datapass check` alerts on
+#[derive(Debug, Clone, Default)]
+pub struct CheckOptions {
+    pub warning: Option<Threshold>,
+    pub critical: Option<Threshold>,
+
+    /// WARNING when fewer days of validity are left
+    pub min_days: Option<i64>,
+}
+
+/// The state and the status line (with perfdata) of a check
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub state: CheckState,
+    pub line: String,
+}
+
+impl CheckResult {
+    /// The result when there is nothing to check, e.g. a failed fetch
+    pub fn unknown(error: &dyn fmt::Display) -> Self {
+        let state = CheckState::Unknown;
+        // The plugin output is a single line
+        let message = error
+            .to_string()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Self {
+            state,
+            line: format!("{} {} - {}", SERVICE, state, message),
+        }
+    }
+}
+
+/// Check a reading against the thresholds. The thresholds do not apply to
+/// unlimited plans; a throttled plan is always CRITICAL.
+pub fn check(data: &DataUsage, options: &CheckOptions, today: NaiveDate) -> CheckResult {
+    let mut state = CheckState::Ok;
+    let mut reasons = Vec::new();
+    let status = PlanStatus::of(data);
+
+    if status == PlanStatus::Throttled {
+        state = CheckState::Critical;
+        reasons.push("throttled".to_string());
+    } else if status == PlanStatus::Available {
+        if let Some(critical) = options.critical.filter(|t| t.is_reached(data)) {
+            state = CheckState::Critical;
+            reasons.push(format!("critical {}", critical));
+        } else if let Some(warning) = options.warning.filter(|t| t.is_reached(data)) {
+            state = CheckState::Warning;
+            reasons.push(format!("warning {}", warning));
+        }
+    }
+
+    let days_left = data.days_left(today).map(|days| days.max(0));
+    if let (Some(days_left), Some(min_days)) = (days_left, options.min_days) {
+        if days_left < min_days {
+            state = state.max(CheckState::Warning);
+            reasons.push(format!("fewer than {} days left", min_days));
+        }
+    }
+
+    let mut summary = match &data.plan_name {
+        Some(plan) => format!("{}: ", plan),
+        None => String::new(),
+    };
+    if data.is_unlimited {
+        summary.push_str("unlimited data");
+    } else {
+        summary.push_str(&format!(
+            "{:.2} GB of {:.2} GB left ({:.2}% used)",
+            data.remaining_gb, data.total_gb, data.percentage
+        ));
+    }
+    match days_left {
+        Some(1) => summary.push_str(", 1 day left"),
+        Some(days) => summary.push_str(&format!(", {} days left", days)),
+        None => {}
+    }
+    if let Some(stale) = &data.stale {
+        summary.push_str(&format!(", {}", stale.notice()));
+    }
+    for reason in &reasons {
+        summary.push_str(&format!("; {}", reason));
+    }
+
+    let mut line = format!("{} {} - {}", SERVICE, state, summary);
+    let perfdata = perfdata(data, options, days_left);
+    if !perfdata.is_empty() {
+        line.push_str(" | ");
+        line.push_str(&perfdata);
+    }
+
+    CheckResult { state, line }
+}
+
+/// Performance data: `label=value[unit];[warn];[crit];[min];[max]` each
+fn perfdata(data: &DataUsage, options: &CheckOptions, days_left: Option<i64>) -> String {
+    let mut values = Vec::new();
+
+    if !data.is_unlimited {
+        let range = |threshold: Option<Threshold>, percent: bool| match threshold {
+            Some(t @ Threshold::UsedPercent(_)) if percent => t.range(),
+            Some(t @ Threshold::RemainingGb(_)) if !percent => t.range(),
+            _ => String::new(),
+        };
+        let total = number(data.total_gb);
+
+        values.push(format!(
+            "remaining={}GB;{};{};0;{}",
+            number(data.remaining_gb),
+            range(options.warning, false),
+            range(options.critical, false),
+            total
+        ));
+        values.push(format!("used={}GB;;;0;{}", number(data.used_gb), total));
+        values.push(format!(
+            "used_percent={}%;{};{};0;100",
+            number(data.percentage),
+            range(options.warning, true),
+            range(options.critical, true)
+        ));
+    }
+
+    if let Some(days_left) = days_left {
+        let warning = options
+            .min_days
+            .map(|days| format!("{}:", days))
+            .unwrap_or_default();
+        values.push(format!("days_left={};{};;0", days_left, warning));
+    }
+
+    values.join(" ")
+}
+
+/// A number with up to two decimals and no trailing zeros
+fn number(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    fn options(warning: &str, critical: &str, min_days: Option<i64>) -> CheckOptions {
+        CheckOptions {
+            warning: Some(warning.parse().unwrap()),
+            critical: Some(critical.parse().unwrap()),
+            min_days,
+        }
+    }
+
+    #[test]
+    fn test_parse_threshold() {
+        assert_eq!("80%".parse(), Ok(Threshold::UsedPercent(80.0)));
+        assert_eq!("5GB".parse(), Ok(Threshold::RemainingGb(5.0)));
+        assert_eq!("500 MB".parse(), Ok(Threshold::RemainingGb(0.5)));
+        assert!("80".parse::<Threshold>().is_err());
+        assert!("-5GB".parse::<Threshold>().is_err());
+    }
+
+    #[test]
+    fn test_ok_with_perfdata() {
+        let data = DataUsage::new(
+            38.36,
+            51.0,
+            Some("MagentaMobil Prepaid XL".to_string()),
+            Some("30.10.2026".to_string()),
+        );
+
+        let result = check(&data, &options("80%", "95%", Some(2)), today());
+        assert_eq!(result.state, CheckState::Ok);
+        assert_eq!(
+            result.line,
+            "DATAPASS OK - MagentaMobil Prepaid XL: 38.36 GB of 51.00 GB left (24.78% used), \
+             12 days left | remaining=38.36GB;;;0;51 used=12.64GB;;;0;51 \
+             used_percent=24.78%;80;95;0;100 days_left=12;2:;;0"
+        );
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let data = DataUsage::new(4.0, 51.0, None, None);
+
+        let result = check(&data, &options("80%", "95%", None), today());
+        assert_eq!(result.state, CheckState::Warning);
+        assert!(result.line.contains("; warning above 80% used |"));
+
+        let result = check(&data, &options("10GB", "5GB", None), today());
+        assert_eq!(result.state, CheckState::Critical);
+        assert!(result.line.contains("; critical below 5 GB left |"));
+        assert!(result.line.contains(" remaining=4GB;10:;5:;0;51 "));
+        assert!(result.line.ends_with(" used_percent=92.16%;;;0;100"));
+    }
+
+    #[test]
+    fn test_threshold_boundaries_match_perfdata_ranges() {
+        // Exactly 80% used and exactly 5 GB left are inside "80" and "5:"
+        let data = DataUsage::new(10.0, 50.0, None, None);
+        assert_eq!(data.percentage, 80.0);
+
+        let result = check(&data, &options("80%", "95%", None), today());
+        assert_eq!(result.state, CheckState::Ok);
+        assert!(result.line.ends_with(" used_percent=80%;80;95;0;100"));
+
+        let result = check(&data, &options("10GB", "10GB", None), today());
+        assert_eq!(result.state, CheckState::Ok);
+        assert!(result.line.contains(" remaining=10GB;10:;10:;0;50 "));
+    }
+
+    #[test]
+    fn test_throttled_unlimited_and_days() {
+        let throttled = DataUsage::new(0.0, 51.0, None, None);
+        let result = check(&throttled, &options("80%", "100%", None), today());
+        assert_eq!(result.state, CheckState::Critical);
+        assert!(result.line.contains("; throttled |"));
+
+        let unlimited = DataUsage::new_unlimited(None, Some("19.10.2026".to_string()));
+        let result = check(&unlimited, &options("80%", "95%", Some(2)), today());
+        assert_eq!(result.state, CheckState::Warning);
+        assert_eq!(
+            result.line,
+            "DATAPASS WARNING - unlimited data, 1 day left; fewer than 2 days left | \
+             days_left=1;2:;;0"
+        );
+    }
+
+    #[test]
+    fn test_no_perfdata() {
+        let unlimited = DataUsage::new_unlimited(None, None);
+        let result = check(&unlimited, &CheckOptions::default(), today());
+        assert_eq!(result.line, "DATAPASS OK - unlimited data");
+    }
+
+    #[test]
+    fn test_unknown_is_one_line() {
+        let result = CheckResult::unknown(&"Access denied.\n  \nTry again");
+        assert_eq!(result.state.exit_code(), 3);
+        assert_eq!(result.line, "DATAPASS UNKNOWN - Access denied. Try again");
+    }
+}
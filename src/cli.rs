@@ -1,4 +1,6 @@
-use clap::{Parser, ValueEnum};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::time::Duration;
 
 #[allow(unused_imports)]
 use clap::CommandFactory;
@@ -8,12 +10,15 @@ use clap::CommandFactory;
 #[command(name = "datapass")]
 #[command(about = "CLI tool to fetch and display mobile data usage from datapass.de")]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Output format
-    #[arg(short, long, value_enum, default_value = "human")]
+    #[arg(short, long, value_enum, default_value = "human", global = true)]
     pub format: Format,
 
     /// Enable colored output
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, global = true)]
     pub color: bool,
 
     /// Print only the used data in GB
@@ -36,30 +41,50 @@ pub struct Cli {
     #[arg(long, conflicts_with = "format")]
     pub plan: bool,
 
+    /// Write the output to FILE instead of stdout, replacing it atomically
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<String>,
+
     /// Watch mode: refresh every N seconds
     #[arg(short, long, value_name = "SECONDS")]
     pub watch: Option<u64>,
 
     /// Custom URL to fetch from (default: https://datapass.de)
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub url: Option<String>,
 
-    /// Read from local HTML file instead of fetching
-    #[arg(short = 'F', long, value_name = "FILE")]
+    /// Read from local HTML file instead of fetching ("-" reads stdin)
+    #[arg(short = 'F', long, value_name = "FILE", global = true)]
     pub file: Option<String>,
 
     /// Enable verbose logging
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub verbose: bool,
 
     /// Log to file
-    #[arg(long, value_name = "FILE")]
+    #[arg(long, value_name = "FILE", global = true)]
     pub log: Option<String>,
 
     /// Custom cookie string to send with HTTP request (advanced usage)
-    #[arg(short = 'C', long, value_name = "COOKIES")]
+    #[arg(short = 'C', long, value_name = "COOKIES", global = true)]
     pub cookie: Option<String>,
 
+    /// Serve the cached page without asking the server if it is younger than this (e.g. 90s, 5m, 1h)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, global = true)]
+    pub max_age: Option<Duration>,
+
+    /// Always download the full page, bypassing the response cache
+    #[arg(long, conflicts_with = "max_age", global = true)]
+    pub no_cache: bool,
+
+    /// Show the last successful reading, marked stale, when fetching fails and it is younger than this (e.g. 6h)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, global = true)]
+    pub allow_stale: Option<Duration>,
+
+    /// Config file (default: ~/.config/datapass/config.toml)
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<String>,
+
     /// Generate shell completions for the specified shell
     #[arg(long, value_name = "SHELL", value_enum)]
     pub generate_completions: Option<Shell>,
@@ -69,6 +94,148 @@ pub struct Cli {
     pub generate_man: bool,
 }
 
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Show tariff transparency information (advertised speeds and pricing)
+    Tariff,
+
+    /// List the data passes currently offered for booking
+    Offers,
+
+    /// Book a data pass (asks for confirmation unless --yes is given)
+    Book {
+        /// Offer ID (e.g. GDPXDF13) or offer name (e.g. "SpeedOn M")
+        offer: String,
+
+        /// Book without asking for confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+
+        /// Open the offer's booking page but stop before the final submission
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Run headless, fetching every N seconds and applying the configured policies
+    Daemon {
+        /// Seconds between fetches
+        #[arg(short, long, value_name = "SECONDS", default_value_t = 300)]
+        interval: u64,
+    },
+
+    /// Break the recorded usage down by hour, day or week
+    Report {
+        /// Period length
+        #[arg(long, value_enum, default_value = "day")]
+        by: crate::report::Bucket,
+
+        /// Only periods from this time on (e.g. 7d, 2026-10-01, 2026-10-01T08:00:00Z)
+        #[arg(long, value_name = "TIME", value_parser = parse_time_bound)]
+        since: Option<DateTime<Utc>>,
+
+        /// Only periods before this time
+        #[arg(long, value_name = "TIME", value_parser = parse_time_bound)]
+        until: Option<DateTime<Utc>>,
+
+        /// Only one billing cycle: current, previous or its number from `datapass cycles`
+        #[arg(long, value_name = "CYCLE", conflicts_with_all = ["since", "until"])]
+        cycle: Option<crate::cycle::CycleRef>,
+    },
+
+    /// Summarise the usage of each billing cycle in the history
+    Cycles,
+
+    /// Show or prune the recorded usage history
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+
+    /// Export the recorded readings as CSV (default), JSON Lines or JSON
+    Export {
+        /// Only readings from this time on (e.g. 7d, 2026-10-01, 2026-10-01T08:00:00Z)
+        #[arg(long, value_name = "TIME", value_parser = parse_time_bound)]
+        since: Option<DateTime<Utc>>,
+
+        /// Only readings before this time
+        #[arg(long, value_name = "TIME", value_parser = parse_time_bound)]
+        until: Option<DateTime<Utc>>,
+    },
+
+    /// Add readings from earlier `--format json` outputs, CSV or JSONL files
+    /// (or directories of them) to the history; known timestamps are skipped
+    Import {
+        /// Files or directories to import
+        #[arg(required = true, value_name = "PATH")]
+        paths: Vec<std::path::PathBuf>,
+    },
+
+    /// Check the data usage as a Nagios/Icinga plugin: prints one status
+    /// line with perfdata and exits with 0 (OK), 1 (WARNING), 2 (CRITICAL)
+    /// or 3 (UNKNOWN)
+    Check {
+        /// WARNING when this share is used (e.g. 80%) or less than this is left (e.g. 5GB, 500MB)
+        #[arg(long, value_name = "THRESHOLD", default_value = "80%")]
+        warning: crate::check::Threshold,
+
+        /// CRITICAL when this share is used (e.g. 95%) or less than this is left (e.g. 1GB)
+        #[arg(long, value_name = "THRESHOLD", default_value = "95%")]
+        critical: crate::check::Threshold,
+
+        /// WARNING when fewer than this many days of validity are left
+        #[arg(long, value_name = "DAYS")]
+        min_days: Option<i64>,
+    },
+
+    /// Serve the data usage as Prometheus metrics over HTTP
+    Serve {
+        /// Address to serve `/metrics` on (e.g. :9877 for all interfaces, 127.0.0.1:9877)
+        #[arg(long, value_name = "ADDRESS", default_value = ":9877")]
+        metrics: String,
+
+        /// Fetch the page at most this often; scrapes in between get the last reading
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "5m")]
+        min_interval: Duration,
+    },
+
+    /// Check the configured notifiers (`[notify]` section)
+    Notify {
+        #[command(subcommand)]
+        command: NotifyCommand,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum NotifyCommand {
+    /// Send a test alert about the last reading to every configured notifier
+    Test,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum HistoryCommand {
+    /// List recorded readings, oldest first
+    List {
+        /// Only readings from this time on (e.g. 7d, 2026-10-01, 2026-10-01T08:00:00Z)
+        #[arg(long, value_name = "TIME", value_parser = parse_time_bound)]
+        since: Option<DateTime<Utc>>,
+
+        /// Only readings before this time
+        #[arg(long, value_name = "TIME", value_parser = parse_time_bound)]
+        until: Option<DateTime<Utc>>,
+
+        /// Show only the N most recent readings
+        #[arg(short = 'n', long, value_name = "N")]
+        limit: Option<usize>,
+    },
+
+    /// Remove old readings (default: those past the configured retention)
+    Prune {
+        /// Remove readings older than this (e.g. 30d)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        older_than: Option<Duration>,
+    },
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 #[allow(clippy::enum_variant_names)]
 pub enum Shell {
@@ -85,6 +252,16 @@ pub enum Format {
     Human,
     /// JSON format
     Json,
+    /// CSV with a header line
+    Csv,
+    /// JSON Lines: one compact JSON object per line
+    #[value(alias = "ndjson", alias = "parquet-free-ndjson")]
+    Jsonl,
+    /// Prometheus text, e.g. for node_exporter's textfile collector
+    Prometheus,
+    /// InfluxDB line protocol, e.g. for Telegraf's exec input
+    #[value(alias = "line-protocol")]
+    Influx,
 }
 
 impl Cli {
@@ -95,6 +272,42 @@ impl Cli {
             return Err("Watch mode is not compatible with single value output flags".to_string());
         }
 
+        if self.command.is_some()
+            && (self.watch.is_some()
+                || self.used
+                || self.total
+                || self.remaining
+                || self.percentage
+                || self.plan)
+        {
+            return Err(
+                "Subcommands are not compatible with watch mode or single value output flags"
+                    .to_string(),
+            );
+        }
+
+        if self.output.is_some() && (self.watch.is_some() || self.command.is_some()) {
+            return Err("--output only applies to the data usage reading".to_string());
+        }
+
+        if matches!(self.format, Format::Prometheus | Format::Influx) && self.command.is_some() {
+            return Err(
+                "--format prometheus and influx only apply to the data usage reading".to_string(),
+            );
+        }
+
+        if matches!(self.command, Some(Command::Book { .. })) && self.file.is_some() {
+            return Err("Booking requires a network connection and cannot use --file".to_string());
+        }
+
+        if self.file.is_some() && self.max_age.is_some() {
+            return Err("--max-age only applies to fetched pages, not --file".to_string());
+        }
+
+        if self.file.is_some() && self.allow_stale.is_some() {
+            return Err("--allow-stale only applies to fetched pages, not --file".to_string());
+        }
+
         if self.file.is_some() && self.url.is_some() {
             return Err("Cannot specify both --file and --url".to_string());
         }
@@ -119,7 +332,127 @@ impl Cli {
             match self.format {
                 Format::Human => OutputFormat::Human,
                 Format::Json => OutputFormat::Json,
+                Format::Csv => OutputFormat::Csv,
+                Format::Jsonl => OutputFormat::Jsonl,
+                Format::Prometheus => OutputFormat::Prometheus,
+                Format::Influx => OutputFormat::Influx,
             }
         }
     }
 }
+
+/// Parse a duration such as `90`, `90s`, `5m`, `2h`, `1d` or `1h30m`;
+/// a bare number is taken as seconds
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("empty duration".to_string());
+    }
+
+    if let Ok(seconds) = text.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(format!("invalid duration unit '{}' in '{}'", c, text)),
+        };
+        let value: u64 = digits
+            .parse()
+            .map_err(|_| format!("missing number before '{}' in '{}'", c, text))?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or("duration too large")?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() {
+        return Err(format!("missing unit after '{}' in '{}'", digits, text));
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+/// Parse a point in time: a duration meaning that long ago (`7d`), a local
+/// date (`2026-10-01`, midnight) or an RFC 3339 timestamp
+pub fn parse_time_bound(text: &str) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        return Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|time| time.with_timezone(&Utc))
+            .ok_or_else(|| format!("no local midnight on {}", text));
+    }
+
+    let ago = parse_duration(text)
+        .map_err(|_| format!("expected a duration, date or timestamp, got '{}'", text))?;
+    chrono::Duration::from_std(ago)
+        .ok()
+        .and_then(|ago| Utc::now().checked_sub_signed(ago))
+        .ok_or_else(|| format!("'{}' is too long ago", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(172_800)));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_garbage() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("1h30").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_overflow() {
+        let too_large = Err("duration too large".to_string());
+        assert_eq!(parse_duration("999999999999999d"), too_large);
+        assert_eq!(parse_duration("18446744073709551615s1s"), too_large);
+        assert!(parse_time_bound("99999999999d").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_bound() {
+        assert_eq!(
+            parse_time_bound("2026-10-01T08:00:00+02:00").unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 1, 6, 0, 0).unwrap()
+        );
+
+        let week_ago = parse_time_bound("7d").unwrap();
+        let expected = Utc::now() - chrono::Duration::days(7);
+        assert!((week_ago - expected).num_seconds().abs() < 5);
+
+        assert!(parse_time_bound("2026-10-01").is_ok());
+        assert!(parse_time_bound("yesterday").is_err());
+    }
+}
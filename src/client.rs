@@ -0,0 +1,352 @@
+use crate::cache::ResponseCache;
+use crate::error::{DatapassError, Result};
+use crate::fetcher::{self, PageRequest};
+use crate::parser;
+use crate::types::{DataReport, FetchMetadata};
+#[cfg(feature = "async")]
+use crate::types::{DataUsage, PassOffer, TariffTransparency};
+use chrono::Utc;
+use std::time::Duration;
+
+/// Language of the requested pages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+}
+
+impl Locale {
+    /// Value of the `Apollo-Lang` cookie that selects the page language
+    fn apollo_lang(self) -> &'static str {
+        match self {
+            Locale::English => "en_DE_TMDE",
+            Locale::German => "de_DE_TMDE",
+        }
+    }
+
+    fn accept_language(self) -> &'static str {
+        match self {
+            Locale::English => fetcher::DEFAULT_ACCEPT_LANGUAGE,
+            Locale::German => "de-DE,de;q=0.9",
+        }
+    }
+}
+
+/// Data pass portal to talk to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Provider {
+    /// Telekom Deutschland (https://datapass.de)
+    #[default]
+    Telekom,
+    /// Telekom Deutschland's alternative portal (https://pass.telekom.de)
+    TelekomPass,
+}
+
+impl Provider {
+    /// Portal URL
+    pub fn url(self) -> &'static str {
+        match self {
+            Provider::Telekom => fetcher::DEFAULT_URL,
+            Provider::TelekomPass => "https://pass.telekom.de",
+        }
+    }
+}
+
+/// Builder for [`DatapassClient`] and [`crate::blocking::DatapassClient`]
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    url: Option<String>,
+    cookie: Option<String>,
+    locale: Locale,
+    provider: Provider,
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+    proxy: Option<String>,
+    retries: u32,
+    retry_delay: Duration,
+    cache: Option<ResponseCache>,
+    max_age: Option<Duration>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self {
+            url: None,
+            cookie: None,
+            locale: Locale::default(),
+            provider: Provider::default(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: None,
+            proxy: None,
+            retries: 0,
+            retry_delay: Duration::from_secs(1),
+            cache: None,
+            max_age: None,
+        }
+    }
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetch from this URL instead of the provider's portal
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    /// Send this cookie string instead of the default Apollo cookies
+    /// (overrides the locale)
+    pub fn cookie(mut self, cookie: &str) -> Self {
+        self.cookie = Some(cookie.to_string());
+        self
+    }
+
+    /// Page language (default: English)
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Portal to talk to (default: datapass.de)
+    pub fn provider(mut self, provider: Provider) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// Timeout for a whole request (default: 30s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Timeout for establishing a connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Send all requests through this proxy (e.g. "http://proxy:3128")
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_string());
+        self
+    }
+
+    /// Retry failed requests this many times on connection errors, timeouts
+    /// and server errors (default: 0)
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled for every further retry (default: 1s)
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Revalidate pages through this response cache
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Serve cached pages younger than this without a request
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Build the async client
+    #[cfg(feature = "async")]
+    pub fn build(self) -> Result<DatapassClient> {
+        Ok(DatapassClient {
+            core: self.build_core()?,
+        })
+    }
+
+    /// Build the blocking client
+    pub fn build_blocking(self) -> Result<crate::blocking::DatapassClient> {
+        crate::blocking::DatapassClient::from_core(self.build_core()?)
+    }
+
+    fn build_core(self) -> Result<ClientCore> {
+        let mut http = fetcher::async_client_builder().timeout(self.timeout);
+
+        if let Some(timeout) = self.connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| DatapassError::InvalidUrl(format!("{}: {}", proxy, e)))?;
+            http = http.proxy(proxy);
+        }
+
+        let cookie = self.cookie.unwrap_or_else(|| {
+            format!(
+                "Apollo-Summation-Disabled=true; Apollo-Lang={}",
+                self.locale.apollo_lang()
+            )
+        });
+
+        Ok(ClientCore {
+            http: http.build()?,
+            url: self.url.unwrap_or_else(|| self.provider.url().to_string()),
+            cookie,
+            accept_language: self.locale.accept_language(),
+            retries: self.retries,
+            retry_delay: self.retry_delay,
+            cache: self.cache,
+            max_age: self.max_age,
+        })
+    }
+}
+
+/// Configuration and connection pool shared by the async and blocking clients
+#[derive(Debug, Clone)]
+pub(crate) struct ClientCore {
+    http: reqwest::Client,
+    url: String,
+    cookie: String,
+    accept_language: &'static str,
+    retries: u32,
+    retry_delay: Duration,
+    cache: Option<ResponseCache>,
+    max_age: Option<Duration>,
+}
+
+impl ClientCore {
+    /// The portal's main page URL
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The portal's tariff transparency page URL
+    pub(crate) fn tariff_url(&self) -> Result<String> {
+        fetcher::tariff_transparency_url(Some(&self.url))
+    }
+
+    /// Fetch a page, retrying transient failures. Returns the page and the
+    /// number of attempts made.
+    pub(crate) async fn get(&self, url: &str) -> Result<(String, u32)> {
+        let request = PageRequest {
+            url,
+            cookie: &self.cookie,
+            accept_language: self.accept_language,
+        };
+
+        let mut attempt = 1;
+        loop {
+            match fetcher::fetch_page(&self.http, request, self.cache.as_ref(), self.max_age).await
+            {
+                Ok(html) => return Ok((html, attempt)),
+                Err(e) if attempt <= self.retries && is_transient(&e) => {
+                    let delay = self.retry_delay * 2u32.saturating_pow(attempt - 1);
+                    log::warn!(
+                        "Fetch attempt {} failed ({}), retrying in {:?}",
+                        attempt,
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Fetch the main page and parse everything on it
+    pub(crate) async fn report(&self) -> Result<DataReport> {
+        let (html, attempts) = self.get(&self.url).await?;
+
+        Ok(DataReport {
+            usage: parser::parse_html(&html)?,
+            passes: parser::parse_passes(&html)?,
+            offers: parser::parse_offers(&html)?,
+            metadata: FetchMetadata {
+                url: self.url.clone(),
+                fetched_at: Utc::now(),
+                attempts,
+                last_update: parser::parse_last_update(&html),
+            },
+        })
+    }
+}
+
+/// Whether a failed fetch may succeed when tried again
+fn is_transient(error: &DatapassError) -> bool {
+    match error {
+        DatapassError::FetchError(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status().is_some_and(|status| status.is_server_error())
+        }
+        _ => false,
+    }
+}
+
+/// Asynchronous client for datapass.de.
+///
+/// Shares the parser and request logic with the blocking client, and keeps
+/// one HTTP connection pool for all requests made through it.
+///
+/// ```no_run
+/// # async fn run() -> datapass::Result<()> {
+/// let client = datapass::DatapassClient::builder()
+///     .locale(datapass::Locale::German)
+///     .retries(2)
+///     .build()?;
+///
+/// let usage = client.fetch().await?;
+/// println!("{:.2} GB left", usage.remaining_gb);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async")]
+#[derive(Debug, Clone)]
+pub struct DatapassClient {
+    core: ClientCore,
+}
+
+#[cfg(feature = "async")]
+impl DatapassClient {
+    /// Client for https://datapass.de with the default settings
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Fetch the raw datapass page
+    pub async fn fetch_html(&self) -> Result<String> {
+        Ok(self.core.get(self.core.url()).await?.0)
+    }
+
+    /// Fetch and parse the data usage
+    pub async fn fetch(&self) -> Result<DataUsage> {
+        parser::parse_html(&self.fetch_html().await?)
+    }
+
+    /// Fetch and parse the data passes currently offered for booking
+    pub async fn fetch_offers(&self) -> Result<Vec<PassOffer>> {
+        parser::parse_offers(&self.fetch_html().await?)
+    }
+
+    /// Fetch the page once and parse usage, passes, offers and fetch details
+    pub async fn fetch_report(&self) -> Result<DataReport> {
+        self.core.report().await
+    }
+
+    /// Fetch and parse the tariff transparency information
+    pub async fn fetch_tariff(&self) -> Result<TariffTransparency> {
+        let (html, _) = self.core.get(&self.core.tariff_url()?).await?;
+        parser::parse_tariff_transparency(&html)
+    }
+}
@@ -0,0 +1,308 @@
+use crate::alert::AlertConfig;
+use crate::error::{DatapassError, Result};
+use crate::forecast::ForecastConfig;
+use crate::history::{HistoryConfig, HistoryStore};
+use crate::hooks::HooksConfig;
+use crate::mqtt::MqttConfig;
+use crate::notify::NotifyConfig;
+use crate::statsd::StatsdConfig;
+use crate::topup::TopUpPolicy;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Settings read from the configuration file
+/// (default: `$XDG_CONFIG_HOME/datapass/config.toml`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Automatic top-up policy (`[topup]` section)
+    pub topup: Option<TopUpPolicy>,
+
+    /// Usage history recording (`[history]` section)
+    pub history: Option<HistoryConfig>,
+
+    /// Depletion forecast from the history (`[forecast]` section)
+    pub forecast: Option<ForecastConfig>,
+
+    /// Threshold and status alerts (`[alerts]` section)
+    pub alerts: Option<AlertConfig>,
+
+    /// External commands run on events (`[hooks]` section)
+    pub hooks: Option<HooksConfig>,
+
+    /// Where alerts are sent (`[notify]` section)
+    pub notify: NotifyConfig,
+
+    /// Publishing readings over MQTT (`[mqtt]` section)
+    pub mqtt: Option<MqttConfig>,
+
+    /// Sending readings as StatsD gauges (`[statsd]` section)
+    pub statsd: Option<StatsdConfig>,
+}
+
+impl Config {
+    /// Default config file location
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("datapass").join(CONFIG_FILE))
+    }
+
+    /// Load the config from the given file, or from the default location.
+    /// A missing default file yields the default (empty) config; a missing
+    /// explicitly requested file is an error.
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        match path {
+            Some(path) => Self::from_file(Path::new(path)),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => Self::from_file(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// Load the config from a TOML file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        log::info!("Loading config from: {}", path.display());
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            DatapassError::ConfigError(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        Self::from_toml(&text)
+    }
+
+    /// Parse the config from TOML text
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| DatapassError::ConfigError(e.to_string()))
+    }
+
+    /// The configured history store; reading it works even while recording
+    /// is off
+    pub fn history_store(&self) -> HistoryStore {
+        match &self.history {
+            Some(history) => history.store(),
+            None => HistoryStore::new(HistoryStore::default_path()),
+        }
+    }
+}
+
+/// Directory for state kept between runs (`$XDG_STATE_HOME/datapass`,
+/// falling back to the local data directory on platforms without one)
+pub fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("datapass")
+}
+
+/// Directory for data worth keeping (`$XDG_DATA_HOME/datapass`)
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("datapass")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_topup_section() {
+        let config = Config::from_toml(
+            r#"
+            [topup]
+            enabled = true
+            offer = "SpeedOn M"
+            below_mb = 500
+            min_days_left = 3
+            max_per_cycle = 2
+            max_spend_eur = 30.0
+            "#,
+        )
+        .unwrap();
+
+        let topup = config.topup.expect("topup section missing");
+        assert!(topup.enabled);
+        assert_eq!(topup.offer, "SpeedOn M");
+        assert_eq!(topup.below_mb, 500.0);
+        assert_eq!(topup.min_days_left, 3);
+        assert_eq!(topup.max_per_cycle, 2);
+        assert_eq!(topup.max_spend_eur, 30.0);
+        assert_eq!(topup.cooldown_minutes, 60);
+        assert!(!topup.dry_run);
+    }
+
+    #[test]
+    fn test_parse_history_section() {
+        let config = Config::from_toml(
+            r#"
+            [history]
+            retention_days = 90
+            "#,
+        )
+        .unwrap();
+
+        let history = config.history.expect("history section missing");
+        assert!(history.enabled);
+        assert!(history.path.is_none());
+        assert_eq!(history.retention_days, Some(90));
+    }
+
+    #[test]
+    fn test_parse_forecast_section() {
+        let config = Config::from_toml(
+            r#"
+            [forecast]
+            method = "ewma"
+            window_hours = 24
+            "#,
+        )
+        .unwrap();
+
+        let forecast = config.forecast.expect("forecast section missing");
+        assert!(forecast.enabled);
+        assert_eq!(forecast.method, crate::types::ForecastMethod::Ewma);
+        assert_eq!(forecast.window_hours, 24);
+        assert_eq!(forecast.ewma_alpha, 0.3);
+    }
+
+    #[test]
+    fn test_parse_alerts_section() {
+        let config = Config::from_toml(
+            r#"
+            [alerts]
+            usage_percent = [80, 90, 95]
+            days_left = [3]
+            status_change = true
+            "#,
+        )
+        .unwrap();
+
+        let alerts = config.alerts.expect("alerts section missing");
+        assert!(alerts.enabled);
+        assert_eq!(alerts.usage_percent, [80.0, 90.0, 95.0]);
+        assert!(alerts.remaining_gb.is_empty());
+        assert_eq!(alerts.rules().len(), 4);
+        assert_eq!(alerts.hysteresis_percent, 2.0);
+    }
+
+    #[test]
+    fn test_parse_hooks_section() {
+        let config = Config::from_toml(
+            r#"
+            [hooks]
+            on_alert = "notify-send datapass \"$DATAPASS_ALERT_MESSAGE\""
+            on_error = "logger -t datapass"
+            "#,
+        )
+        .unwrap();
+
+        let hooks = config.hooks.expect("hooks section missing");
+        assert!(hooks.on_fetch.is_none());
+        assert_eq!(hooks.on_error.as_deref(), Some("logger -t datapass"));
+        assert_eq!(hooks.timeout_seconds, 10);
+    }
+
+    #[test]
+    fn test_parse_notify_section() {
+        let config = Config::from_toml(
+            r#"
+            [notify.desktop]
+            icon = "network-cellular"
+            "#,
+        )
+        .unwrap();
+
+        let desktop = config.notify.desktop.expect("desktop section missing");
+        assert!(desktop.enabled);
+        assert_eq!(desktop.icon.as_deref(), Some("network-cellular"));
+        assert!(desktop.expire_timeout_ms.is_none());
+        assert!(Config::from_toml("[notify]\n")
+            .unwrap()
+            .notify
+            .desktop
+            .is_none());
+    }
+
+    #[test]
+    fn test_parse_notify_email() {
+        let config = Config::from_toml(
+            r#"
+            [notify.email]
+            host = "smtp.example.com"
+            tls = "tls"
+            from = "datapass@example.com"
+            to = ["ops@example.com", "Alice <alice@example.com>"]
+            weekly_digest = true
+            digest_weekday = "Fri"
+            "#,
+        )
+        .unwrap();
+
+        let email = config.notify.email.expect("email section missing");
+        assert_eq!(email.tls, crate::notify::EmailTls::Tls);
+        assert_eq!(email.port(), 465);
+        assert_eq!(email.to.len(), 2);
+        assert_eq!(email.subject_prefix, "[datapass]");
+        assert_eq!(email.digest_weekday, chrono::Weekday::Fri);
+    }
+
+    #[test]
+    fn test_parse_mqtt_section() {
+        let config = Config::from_toml(
+            r#"
+            [mqtt]
+            host = "homeassistant.local"
+            tls = true
+            username = "datapass"
+            password = "secret"
+            "#,
+        )
+        .unwrap();
+
+        let mqtt = config.mqtt.expect("mqtt section missing");
+        assert!(mqtt.enabled);
+        assert_eq!(mqtt.port(), 8883);
+        assert_eq!(mqtt.qos, 1);
+        assert!(mqtt.retain);
+        assert!(mqtt.discovery);
+        assert_eq!(mqtt.topic("plan"), "datapass/plan");
+    }
+
+    #[test]
+    fn test_parse_statsd_section() {
+        let config = Config::from_toml(
+            r#"
+            [statsd]
+            prefix = "sim1.datapass"
+            "#,
+        )
+        .unwrap();
+
+        let statsd = config.statsd.expect("statsd section missing");
+        assert!(statsd.enabled);
+        assert_eq!(statsd.host, "127.0.0.1");
+        assert_eq!(statsd.port, 8125);
+        assert_eq!(statsd.metric("used_ratio"), "sim1.datapass.used_ratio");
+    }
+
+    #[test]
+    fn test_empty_config() {
+        let config = Config::from_toml("").unwrap();
+        assert!(config.topup.is_none());
+        assert!(config.history.is_none());
+    }
+
+    #[test]
+    fn test_reject_unknown_keys() {
+        // A typo in a spending policy must not be silently ignored
+        let result = Config::from_toml(
+            r#"
+            [topup]
+            offer = "SpeedOn M"
+            below_mb = 500
+            max_spend = 30.0
+            "#,
+        );
+        assert!(result.is_err());
+    }
+}
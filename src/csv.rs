@@ -0,0 +1,90 @@
+//! Minimal CSV (RFC 4180) writing and reading
+
+/// Join fields into a CSV line, quoting those that need it
+pub fn row<S: AsRef<str>>(fields: &[S]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Split CSV text into records of fields. Quoted fields may contain commas,
+/// doubled quotes and line breaks; empty lines are skipped.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|field| !field.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+
+    record.push(field);
+    if record.iter().any(|field| !field.is_empty()) {
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let fields = ["plain", "with, comma", "with \"quotes\"", "two\nlines", ""];
+        let line = row(&fields);
+        assert_eq!(
+            line,
+            "plain,\"with, comma\",\"with \"\"quotes\"\"\",\"two\nlines\","
+        );
+
+        let records = parse(&format!("a,b,c,d,e\r\n{}\n\n", line)).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1], fields);
+    }
+
+    #[test]
+    fn test_unterminated_quote() {
+        assert!(parse("a,\"b\n").is_err());
+    }
+}
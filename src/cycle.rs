@@ -0,0 +1,295 @@
+use crate::history::HistoryEntry;
+use crate::types::DataUsage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Something that happened to the plan between two readings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum HistoryEvent {
+    /// A new billing cycle began: the plan renewed (validity date changed,
+    /// or the used volume started over) or the plan changed
+    CycleStarted {
+        valid_until: Option<String>,
+        total_gb: f64,
+    },
+    /// Volume was booked on top of the current cycle
+    PassBooked { added_gb: f64 },
+}
+
+impl HistoryEvent {
+    /// Short description, e.g. "cycle started" or "+5.00 GB booked"
+    pub fn describe(&self) -> String {
+        match self {
+            HistoryEvent::CycleStarted { .. } => "cycle started".to_string(),
+            HistoryEvent::PassBooked { added_gb } => format!("+{:.2} GB booked", added_gb),
+        }
+    }
+}
+
+/// The event between two consecutive readings, if any
+pub fn detect(before: &DataUsage, after: &DataUsage) -> Option<HistoryEvent> {
+    let cycle_started = || {
+        Some(HistoryEvent::CycleStarted {
+            valid_until: after.valid_until.clone(),
+            total_gb: after.total_gb,
+        })
+    };
+
+    let validity_changed = matches!(
+        (&before.valid_until, &after.valid_until),
+        (Some(before), Some(after)) if before != after
+    );
+
+    if before.is_unlimited != after.is_unlimited || validity_changed {
+        return cycle_started();
+    }
+
+    if before.is_unlimited {
+        return None;
+    }
+
+    // A booking adds to both total and remaining, so the used volume
+    // carries on; a renewal starts it over
+    if after.used_gb < before.used_gb {
+        cycle_started()
+    } else if after.total_gb > before.total_gb {
+        Some(HistoryEvent::PassBooked {
+            added_gb: after.total_gb - before.total_gb,
+        })
+    } else if after.total_gb != before.total_gb {
+        cycle_started()
+    } else {
+        None
+    }
+}
+
+/// Data used between two consecutive readings in GB; never negative
+pub fn consumption(before: &DataUsage, after: &DataUsage) -> f64 {
+    if before.is_unlimited || after.is_unlimited {
+        return 0.0;
+    }
+
+    if after.used_gb < before.used_gb {
+        // Started over: everything used so far was used since the renewal
+        after.used_gb.max(0.0)
+    } else {
+        after.used_gb - before.used_gb
+    }
+}
+
+/// One billing cycle in the history
+#[derive(Debug, Clone, Serialize)]
+pub struct Cycle {
+    /// Position in the history, starting at 1 with the oldest cycle
+    pub number: usize,
+
+    /// First reading of the cycle
+    pub started_at: DateTime<Utc>,
+
+    /// Last reading of the cycle
+    pub last_reading_at: DateTime<Utc>,
+
+    pub plan_name: Option<String>,
+    pub valid_until: Option<String>,
+
+    /// Volume of the cycle including booked passes, as of the last reading
+    pub total_gb: f64,
+
+    /// Volume used in the cycle, as of the last reading
+    pub used_gb: f64,
+
+    /// Volume added by bookings during the cycle
+    pub booked_gb: f64,
+
+    /// Number of bookings during the cycle
+    pub bookings: usize,
+
+    pub readings: usize,
+
+    /// Index of the cycle's first reading in the history
+    #[serde(skip)]
+    pub first: usize,
+
+    /// Index of the cycle's last reading in the history
+    #[serde(skip)]
+    pub last: usize,
+}
+
+/// Split readings in chronological order into billing cycles
+pub fn cycles(entries: &[HistoryEntry]) -> Vec<Cycle> {
+    let mut cycles: Vec<Cycle> = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let event = index
+            .checked_sub(1)
+            .and_then(|previous| detect(&entries[previous].usage, &entry.usage));
+
+        match (cycles.last_mut(), &event) {
+            (Some(cycle), None | Some(HistoryEvent::PassBooked { .. })) => {
+                if let Some(HistoryEvent::PassBooked { added_gb }) = event {
+                    cycle.booked_gb += added_gb;
+                    cycle.bookings += 1;
+                }
+                cycle.last_reading_at = entry.timestamp;
+                cycle.valid_until = entry.usage.valid_until.clone();
+                cycle.total_gb = entry.usage.total_gb;
+                cycle.used_gb = entry.usage.used_gb;
+                cycle.readings += 1;
+                cycle.last = index;
+            }
+            _ => cycles.push(Cycle {
+                number: cycles.len() + 1,
+                started_at: entry.timestamp,
+                last_reading_at: entry.timestamp,
+                plan_name: entry.usage.plan_name.clone(),
+                valid_until: entry.usage.valid_until.clone(),
+                total_gb: entry.usage.total_gb,
+                used_gb: entry.usage.used_gb,
+                booked_gb: 0.0,
+                bookings: 0,
+                readings: 1,
+                first: index,
+                last: index,
+            }),
+        }
+    }
+
+    cycles
+}
+
+/// A cycle picked on the command line: `current`, `previous` or its number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleRef {
+    Current,
+    Previous,
+    Number(usize),
+}
+
+impl CycleRef {
+    /// The referenced cycle among `cycles`
+    pub fn find<'a>(&self, cycles: &'a [Cycle]) -> Option<&'a Cycle> {
+        match self {
+            CycleRef::Current => cycles.last(),
+            CycleRef::Previous => cycles.len().checked_sub(2).map(|index| &cycles[index]),
+            CycleRef::Number(number) => cycles.iter().find(|cycle| cycle.number == *number),
+        }
+    }
+}
+
+impl fmt::Display for CycleRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CycleRef::Current => write!(f, "current"),
+            CycleRef::Previous => write!(f, "previous"),
+            CycleRef::Number(number) => write!(f, "{}", number),
+        }
+    }
+}
+
+impl FromStr for CycleRef {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "current" => Ok(CycleRef::Current),
+            "previous" => Ok(CycleRef::Previous),
+            _ => text.parse().map(CycleRef::Number).map_err(|_| {
+                format!(
+                    "expected current, previous or a cycle number, got '{}'",
+                    text
+                )
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn usage(remaining_gb: f64, total_gb: f64, valid_until: &str) -> DataUsage {
+        DataUsage::new(remaining_gb, total_gb, None, Some(valid_until.to_string()))
+    }
+
+    #[test]
+    fn test_detect_events() {
+        let october = usage(10.0, 51.0, "1. November 2026");
+
+        // Renewal with a new validity date
+        assert!(matches!(
+            detect(&october, &usage(51.0, 51.0, "1. December 2026")),
+            Some(HistoryEvent::CycleStarted { .. })
+        ));
+        // Remaining went up at the same total and date
+        assert!(matches!(
+            detect(&october, &usage(50.0, 51.0, "1. November 2026")),
+            Some(HistoryEvent::CycleStarted { .. })
+        ));
+        // Booking: total and remaining up by 5 GB, 1 GB used meanwhile
+        assert_eq!(
+            detect(&october, &usage(14.0, 56.0, "1. November 2026")),
+            Some(HistoryEvent::PassBooked { added_gb: 5.0 })
+        );
+        // Plain consumption
+        assert_eq!(
+            detect(&october, &usage(9.0, 51.0, "1. November 2026")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_consumption_is_never_negative() {
+        let before = usage(1.0, 51.0, "1. November 2026");
+
+        assert_eq!(
+            consumption(&before, &usage(50.5, 51.0, "1. December 2026")),
+            0.5
+        );
+        assert_eq!(
+            consumption(&before, &usage(4.0, 56.0, "1. November 2026")),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_split_into_cycles() {
+        let start = chrono::Utc::now();
+        let entries: Vec<HistoryEntry> = [
+            usage(3.0, 51.0, "2. October 2026"),
+            usage(1.0, 51.0, "2. October 2026"),
+            usage(50.0, 51.0, "1. November 2026"),
+            usage(54.0, 56.0, "1. November 2026"),
+            usage(53.0, 56.0, "1. November 2026"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(hour, usage)| HistoryEntry {
+            timestamp: start + Duration::hours(hour as i64),
+            usage,
+            passes: Vec::new(),
+            events: Vec::new(),
+        })
+        .collect();
+
+        let cycles = cycles(&entries);
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[0].used_gb, 50.0);
+        assert_eq!(cycles[0].readings, 2);
+        assert_eq!(cycles[1].number, 2);
+        assert_eq!(cycles[1].used_gb, 3.0);
+        assert_eq!(cycles[1].booked_gb, 5.0);
+        assert_eq!(cycles[1].bookings, 1);
+        assert_eq!((cycles[1].first, cycles[1].last), (2, 4));
+
+        assert_eq!(CycleRef::Current.find(&cycles).unwrap().number, 2);
+        assert_eq!(CycleRef::Previous.find(&cycles).unwrap().number, 1);
+        assert!(CycleRef::Number(3).find(&cycles).is_none());
+        assert_eq!("previous".parse(), Ok(CycleRef::Previous));
+        assert_eq!("2".parse(), Ok(CycleRef::Number(2)));
+        assert!("last".parse::<CycleRef>().is_err());
+    }
+}
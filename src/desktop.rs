@@ -0,0 +1,148 @@
+use crate::alert::{Alert, Severity};
+use crate::error::{DatapassError, Result};
+use crate::notify::{self, DesktopConfig, Notifier};
+use crate::types::DataUsage;
+use std::collections::HashMap;
+use zbus::blocking::Connection;
+use zbus::zvariant::Value;
+
+const DESTINATION: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+const APP_NAME: &str = "datapass";
+
+/// Urgency hint of the notification specification: 0 low, 1 normal, 2 critical
+fn urgency(severity: Severity) -> u8 {
+    match severity {
+        Severity::Info => 0,
+        Severity::Warning => 1,
+        Severity::Critical => 2,
+    }
+}
+
+/// Sends alerts to the desktop through `org.freedesktop.Notifications` on the
+/// session bus. Without a reachable bus it logs once and stays quiet.
+pub struct DesktopNotifier {
+    config: DesktopConfig,
+
+    /// Bus address; the session bus when not set
+    address: Option<String>,
+
+    connection: Option<Connection>,
+
+    /// Connecting failed before; don't try again
+    unavailable: bool,
+}
+
+impl DesktopNotifier {
+    pub fn new(config: DesktopConfig) -> Self {
+        Self {
+            config,
+            address: None,
+            connection: None,
+            unavailable: false,
+        }
+    }
+
+    /// Use the bus at `address` (e.g. "unix:path=/run/user/1000/bus")
+    /// instead of the session bus
+    pub fn with_address(mut self, address: &str) -> Self {
+        self.address = Some(address.to_string());
+        self
+    }
+
+    fn connect(&self) -> zbus::Result<Connection> {
+        match &self.address {
+            Some(address) => {
+                zbus::blocking::connection::Builder::address(address.as_str())?.build()
+            }
+            None => Connection::session(),
+        }
+    }
+
+    /// The bus connection, opened on first use; `None` once it failed
+    fn connection(&mut self) -> Option<&Connection> {
+        if self.connection.is_none() && !self.unavailable {
+            match self.connect() {
+                Ok(connection) => self.connection = Some(connection),
+                Err(e) => {
+                    log::warn!("Desktop notifications disabled, no session bus: {}", e);
+                    self.unavailable = true;
+                }
+            }
+        }
+
+        self.connection.as_ref()
+    }
+}
+
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn notify(&mut self, alert: &Alert, data: &DataUsage) -> Result<()> {
+        let icon = self.config.icon.clone().unwrap_or_default();
+        let expire_timeout = self.config.expire_timeout_ms.unwrap_or(-1);
+        let Some(connection) = self.connection() else {
+            return Ok(());
+        };
+
+        let mut hints: HashMap<&str, Value> = HashMap::new();
+        hints.insert("urgency", Value::U8(urgency(alert.severity)));
+
+        let reply = connection
+            .call_method(
+                Some(DESTINATION),
+                PATH,
+                Some(DESTINATION),
+                "Notify",
+                &(
+                    APP_NAME,
+                    0u32,
+                    icon.as_str(),
+                    notify::summary(alert).as_str(),
+                    notify::body(alert, data).as_str(),
+                    Vec::<&str>::new(),
+                    hints,
+                    expire_timeout,
+                ),
+            )
+            .map_err(|e| DatapassError::NotifyError(e.to_string()))?;
+
+        let id: u32 = reply
+            .body()
+            .deserialize()
+            .map_err(|e| DatapassError::NotifyError(e.to_string()))?;
+        log::debug!("Desktop notification {} sent", id);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_bus_disables_quietly() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = format!("unix:path={}", dir.path().join("no-bus").display());
+        let config = DesktopConfig {
+            enabled: true,
+            icon: None,
+            expire_timeout_ms: None,
+        };
+        let mut notifier = DesktopNotifier::new(config).with_address(&address);
+        let alert = Alert {
+            rule: "remaining_gb:5".to_string(),
+            message: "2.50 GB remaining (alert below 5 GB)".to_string(),
+            severity: Severity::Critical,
+            timestamp: chrono::Utc::now(),
+        };
+        let data = DataUsage::new(2.5, 51.0, None, None);
+
+        assert!(notifier.notify(&alert, &data).is_ok());
+        assert!(notifier.unavailable);
+        assert!(notifier.notify(&alert, &data).is_ok());
+    }
+}
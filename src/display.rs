@@ -1,11 +1,71 @@
-use crate::types::DataUsage;
+use crate::alert::Alert;
+use crate::booking::{BookingResult, BookingStatus};
+use crate::csv;
+use crate::cycle::Cycle;
+use crate::export;
+use crate::history::HistoryEntry;
+use crate::influx;
+use crate::metrics;
+use crate::report::UsagePeriod;
+use crate::types::{DataUsage, PassOffer, TariffTransparency};
+#[cfg(feature = "color")]
 use owo_colors::OwoColorize;
+#[cfg(not(feature = "color"))]
+use plain::OwoColorize;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Stand-in for `owo_colors` when built without the `color` feature:
+/// every style leaves the text unchanged
+#[cfg(not(feature = "color"))]
+mod plain {
+    use std::fmt::Display;
+
+    pub trait OwoColorize: Display {
+        fn bold(&self) -> String {
+            self.to_string()
+        }
+        fn dimmed(&self) -> String {
+            self.to_string()
+        }
+        fn red(&self) -> String {
+            self.to_string()
+        }
+        fn green(&self) -> String {
+            self.to_string()
+        }
+        fn yellow(&self) -> String {
+            self.to_string()
+        }
+        fn cyan(&self) -> String {
+            self.to_string()
+        }
+        fn bright_blue(&self) -> String {
+            self.to_string()
+        }
+        fn bright_green(&self) -> String {
+            self.to_string()
+        }
+        fn bright_white(&self) -> String {
+            self.to_string()
+        }
+    }
+
+    impl<T: Display + ?Sized> OwoColorize for T {}
+}
 
 /// Display format options
 #[derive(Debug, Clone, Copy)]
 pub enum OutputFormat {
     Human,
     Json,
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+    /// Prometheus text with the exporter's metrics
+    Prometheus,
+    /// InfluxDB line protocol
+    Influx,
     Used,
     Total,
     Remaining,
@@ -15,69 +75,98 @@ pub enum OutputFormat {
 
 /// Display data usage in the specified format
 pub fn display(data: &DataUsage, format: OutputFormat, use_color: bool) {
-    match format {
-        OutputFormat::Human => display_human(data, use_color),
-        OutputFormat::Json => display_json(data),
-        OutputFormat::Used => {
-            if data.is_unlimited {
-                println!("unlimited");
-            } else {
-                println!("{:.2}", data.used_gb);
-            }
-        }
-        OutputFormat::Total => {
-            if data.is_unlimited {
-                println!("unlimited");
-            } else {
-                println!("{:.2}", data.total_gb);
-            }
-        }
-        OutputFormat::Remaining => {
-            if data.is_unlimited {
-                println!("unlimited");
-            } else {
-                println!("{:.2}", data.remaining_gb);
-            }
+    // Single values carry no room for a marker, so warn on stderr instead
+    if let (
+        Some(stale),
+        OutputFormat::Used
+        | OutputFormat::Total
+        | OutputFormat::Remaining
+        | OutputFormat::Percentage
+        | OutputFormat::Plan,
+    ) = (&data.stale, format)
+    {
+        eprintln!("Warning: {}", stale.notice());
+    }
+
+    print!("{}", usage_text(data, format, use_color));
+}
+
+/// Data usage as text in the specified format, as printed by `display`
+pub fn usage_text(data: &DataUsage, format: OutputFormat, use_color: bool) -> String {
+    let single = |value: f64| {
+        if data.is_unlimited {
+            "unlimited\n".to_string()
+        } else {
+            format!("{:.2}\n", value)
         }
-        OutputFormat::Percentage => {
-            if data.is_unlimited {
-                println!("unlimited");
-            } else {
-                println!("{:.2}", data.percentage);
+    };
+
+    match format {
+        OutputFormat::Human => human_text(data, use_color),
+        OutputFormat::Json => match to_json(data) {
+            Ok(json) => format!("{}\n", json),
+            Err(e) => {
+                eprintln!("Error serializing to JSON: {}", e);
+                String::new()
             }
-        }
-        OutputFormat::Plan => {
-            if let Some(plan) = &data.plan_name {
-                println!("{}", plan);
+        },
+        OutputFormat::Jsonl => match serde_json::to_string(data) {
+            Ok(json) => format!("{}\n", json),
+            Err(e) => {
+                eprintln!("Error serializing to JSON: {}", e);
+                String::new()
             }
-        }
+        },
+        OutputFormat::Csv => format!(
+            "{}\n{}\n",
+            csv::row(USAGE_CSV_COLUMNS),
+            csv::row(&usage_csv_fields(data))
+        ),
+        OutputFormat::Prometheus => metrics::render(
+            Some(data),
+            &metrics::FetchStats::of_reading(data, chrono::Utc::now()),
+        ),
+        OutputFormat::Influx => format!("{}\n", influx::line(data, chrono::Utc::now())),
+        OutputFormat::Used => single(data.used_gb),
+        OutputFormat::Total => single(data.total_gb),
+        OutputFormat::Remaining => single(data.remaining_gb),
+        OutputFormat::Percentage => single(data.percentage),
+        OutputFormat::Plan => match &data.plan_name {
+            Some(plan) => format!("{}\n", plan),
+            None => String::new(),
+        },
     }
 }
 
-/// Display data usage in human-readable format with optional colors
-fn display_human(data: &DataUsage, use_color: bool) {
-    let plan_line = if let Some(plan) = &data.plan_name {
+/// The human-readable output as text, as printed by `datapass` and sent in
+/// emails
+pub fn human_text(data: &DataUsage, use_color: bool) -> String {
+    let mut out = String::new();
+
+    if let Some(plan) = &data.plan_name {
         if use_color {
-            format!("Plan: {}\n", plan.bold())
+            let _ = writeln!(out, "Plan: {}", plan.bold());
         } else {
-            format!("Plan: {}\n", plan)
+            let _ = writeln!(out, "Plan: {}", plan);
         }
-    } else {
-        String::new()
-    };
+    }
 
-    let valid_until_line = if let Some(valid_until) = &data.valid_until {
+    if let Some(valid_until) = &data.valid_until {
         if use_color {
-            format!("Valid until: {}\n", valid_until.cyan())
+            let _ = writeln!(out, "Valid until: {}", valid_until.cyan());
         } else {
-            format!("Valid until: {}\n", valid_until)
+            let _ = writeln!(out, "Valid until: {}", valid_until);
         }
-    } else {
-        String::new()
-    };
+    }
 
-    print!("{}", plan_line);
-    print!("{}", valid_until_line);
+    if let Some(stale) = &data.stale {
+        let notice = stale.notice();
+        if use_color {
+            let _ = writeln!(out, "Stale:     {}", notice.yellow());
+        } else {
+            let _ = writeln!(out, "Stale:     {}", notice);
+        }
+    }
 
     if data.is_unlimited {
         // Display unlimited plan info
@@ -88,10 +177,10 @@ fn display_human(data: &DataUsage, use_color: bool) {
             unlimited_text.to_string()
         };
 
-        println!("Data:      {}", unlimited_display);
+        let _ = writeln!(out, "Data:      {}", unlimited_display);
 
         // Display progress bar showing 100%
-        display_progress_bar(data, use_color);
+        write_progress_bar(&mut out, data, use_color);
     } else {
         // Display standard metered plan info
         let used_str = format!("{:.2} GB", data.used_gb);
@@ -108,31 +197,42 @@ fn display_human(data: &DataUsage, use_color: bool) {
             (used_str, total_str, remaining_str)
         };
 
-        println!("Used:      {} ({:.2}%)", used_display, data.percentage);
-        println!("Total:     {} (100%)", total_display);
-        println!(
+        let _ = writeln!(out, "Used:      {} ({:.2}%)", used_display, data.percentage);
+        let _ = writeln!(out, "Total:     {} (100%)", total_display);
+        let _ = writeln!(
+            out,
             "Remaining: {} ({:.2}%)",
             remaining_display,
             data.remaining_percentage()
         );
 
+        if let Some(forecast) = &data.forecast {
+            let summary = forecast.summary(chrono::Utc::now());
+            if use_color && forecast.runs_out_early() {
+                let _ = writeln!(out, "Forecast:  {}", summary.red());
+            } else {
+                let _ = writeln!(out, "Forecast:  {}", summary);
+            }
+        }
+
         // Display progress bar
-        display_progress_bar(data, use_color);
+        write_progress_bar(&mut out, data, use_color);
     }
+
+    out
 }
 
-/// Display a progress bar showing usage
-fn display_progress_bar(data: &DataUsage, use_color: bool) {
+/// Write a progress bar showing usage
+fn write_progress_bar(out: &mut String, data: &DataUsage, use_color: bool) {
     let bar_width = 40;
 
     if data.is_unlimited {
         // For unlimited plans, show a full green bar
         if use_color {
-            use owo_colors::OwoColorize;
             let filled_str = "█".repeat(bar_width).bright_green().to_string();
-            println!("{} unlimited", filled_str);
+            let _ = writeln!(out, "{} unlimited", filled_str);
         } else {
-            println!("{} unlimited", "█".repeat(bar_width));
+            let _ = writeln!(out, "{} unlimited", "█".repeat(bar_width));
         }
     } else {
         // Standard metered plan progress bar
@@ -141,8 +241,6 @@ fn display_progress_bar(data: &DataUsage, use_color: bool) {
         let empty = bar_width - filled;
 
         if use_color {
-            use owo_colors::OwoColorize;
-
             // Determine color based on remaining percentage
             let filled_str = if data.remaining_percentage() > 50.0 {
                 "█".repeat(filled).green().to_string()
@@ -154,9 +252,10 @@ fn display_progress_bar(data: &DataUsage, use_color: bool) {
 
             let empty_str = "░".repeat(empty).dimmed().to_string();
 
-            println!("{}{} {:.2}%", filled_str, empty_str, data.percentage);
+            let _ = writeln!(out, "{}{} {:.2}%", filled_str, empty_str, data.percentage);
         } else {
-            println!(
+            let _ = writeln!(
+                out,
                 "{}{} {:.2}%",
                 "█".repeat(filled),
                 "░".repeat(empty),
@@ -165,12 +264,450 @@ fn display_progress_bar(data: &DataUsage, use_color: bool) {
         }
     }
 
-    println!(); // Add newline after progress bar
+    out.push('\n'); // Add newline after progress bar
+}
+
+/// One-line summary with a local timestamp, used by daemon mode
+pub fn status_line(data: &DataUsage) -> String {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    let plan = data.plan_name.as_deref().unwrap_or("Unknown Plan");
+
+    let line = if data.is_unlimited {
+        format!("[{}] {}: unlimited", timestamp, plan)
+    } else {
+        format!(
+            "[{}] {}: {:.2} GB of {:.2} GB remaining ({:.2}% used)",
+            timestamp, plan, data.remaining_gb, data.total_gb, data.percentage
+        )
+    };
+
+    match &data.stale {
+        Some(stale) => format!("{} [stale: {}]", line, stale.notice()),
+        None => line,
+    }
+}
+
+/// Print fired alerts on stderr, keeping stdout for the reading itself
+pub fn display_alerts(alerts: &[Alert], use_color: bool) {
+    for alert in alerts {
+        if use_color {
+            eprintln!("{} {}", "Alert:".yellow().bold(), alert.message);
+        } else {
+            eprintln!("Alert: {}", alert.message);
+        }
+    }
+}
+
+/// Display tariff transparency information in the specified format
+pub fn display_tariff(tariff: &TariffTransparency, format: OutputFormat, use_color: bool) {
+    match format {
+        OutputFormat::Json => display_json(tariff),
+        _ => display_tariff_human(tariff, use_color),
+    }
+}
+
+/// Display tariff transparency information in human-readable format
+fn display_tariff_human(tariff: &TariffTransparency, use_color: bool) {
+    if let Some(plan) = &tariff.plan_name {
+        if use_color {
+            println!("Plan:      {}", plan.bold());
+        } else {
+            println!("Plan:      {}", plan);
+        }
+    }
+
+    let download = format_speeds(tariff.max_download_mbps, tariff.estimated_download_mbps);
+    let upload = format_speeds(tariff.max_upload_mbps, tariff.estimated_upload_mbps);
+
+    if use_color {
+        println!("Download:  {}", download.bright_blue());
+        println!("Upload:    {}", upload.bright_green());
+    } else {
+        println!("Download:  {}", download);
+        println!("Upload:    {}", upload);
+    }
+
+    if let Some(price) = &tariff.price {
+        if use_color {
+            println!("Price:     {}", price.cyan());
+        } else {
+            println!("Price:     {}", price);
+        }
+    }
+}
+
+/// Format advertised and estimated maximum speeds, e.g. "max 300.00 Mbit/s, estimated 150.00 Mbit/s"
+fn format_speeds(max: Option<f64>, estimated: Option<f64>) -> String {
+    match (max, estimated) {
+        (Some(max), Some(estimated)) => {
+            format!("max {:.2} Mbit/s, estimated {:.2} Mbit/s", max, estimated)
+        }
+        (Some(max), None) => format!("max {:.2} Mbit/s", max),
+        (None, Some(estimated)) => format!("estimated {:.2} Mbit/s", estimated),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+/// Display the offered data passes in the specified format
+pub fn display_offers(offers: &[PassOffer], format: OutputFormat, use_color: bool) {
+    match format {
+        OutputFormat::Json => display_json(&offers),
+        _ => {
+            if offers.is_empty() {
+                println!("No offers available");
+                return;
+            }
+
+            for offer in offers {
+                let id = offer.id.as_deref().unwrap_or("-");
+                let line = format!(
+                    "{:<10} {:<20} {:>10} {:>9}  {}",
+                    id,
+                    offer.name,
+                    format_offer_volume(offer),
+                    format_offer_price(offer),
+                    offer.description.as_deref().unwrap_or_default()
+                );
+
+                if use_color && !offer.is_bookable() {
+                    println!("{}", line.dimmed());
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
+    }
+}
+
+/// Display a short summary of a single offer (used before booking)
+pub fn display_offer_summary(offer: &PassOffer, use_color: bool) {
+    let name = match &offer.id {
+        Some(id) => format!("{} ({})", offer.name, id),
+        None => offer.name.clone(),
+    };
+
+    if use_color {
+        println!("Offer:     {}", name.bold());
+    } else {
+        println!("Offer:     {}", name);
+    }
+
+    println!("Volume:    {}", format_offer_volume(offer));
+    println!("Price:     {}", format_offer_price(offer));
+
+    if let Some(description) = &offer.description {
+        println!("Validity:  {}", description);
+    }
+}
+
+/// Display the result of a booking attempt in the specified format
+pub fn display_booking(result: &BookingResult, format: OutputFormat, use_color: bool) {
+    match format {
+        OutputFormat::Json => display_json(result),
+        _ => {
+            display_offer_summary(&result.offer, use_color);
+
+            let status = match result.status {
+                BookingStatus::DryRun => "dry run, nothing was booked",
+                BookingStatus::Booked => "booked",
+                BookingStatus::Rejected => "rejected",
+                BookingStatus::Unconfirmed => "submitted, but the result could not be confirmed",
+            };
+
+            if use_color {
+                let status = match result.status {
+                    BookingStatus::Booked => status.bright_green().to_string(),
+                    BookingStatus::Rejected => status.red().to_string(),
+                    _ => status.yellow().to_string(),
+                };
+                println!("Status:    {}", status);
+            } else {
+                println!("Status:    {}", status);
+            }
+
+            if let Some(message) = &result.message {
+                println!("Message:   {}", message);
+            }
+        }
+    }
+}
+
+/// Display recorded readings in the specified format
+pub fn display_history(entries: &[HistoryEntry], format: OutputFormat, use_color: bool) {
+    match format {
+        OutputFormat::Json => display_json(&entries),
+        OutputFormat::Csv | OutputFormat::Jsonl => {
+            let mut out = std::io::stdout().lock();
+            let written = match format {
+                OutputFormat::Csv => export::write_csv(entries, &mut out),
+                _ => export::write_jsonl(entries, &mut out),
+            };
+            if let Err(e) = written {
+                eprintln!("Error writing readings: {}", e);
+            }
+        }
+        _ => {
+            if entries.is_empty() {
+                println!("No readings recorded");
+                return;
+            }
+
+            for entry in entries {
+                let time = entry
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string();
+                let usage = &entry.usage;
+                let volume = if usage.is_unlimited {
+                    format!("{:>30}", "unlimited")
+                } else {
+                    format!(
+                        "{:>8.2} GB of {:>6.2} GB ({:>6.2}%)",
+                        usage.remaining_gb,
+                        usage.total_gb,
+                        usage.remaining_percentage()
+                    )
+                };
+
+                let time = if use_color {
+                    time.dimmed().to_string()
+                } else {
+                    time
+                };
+
+                let mut line = format!(
+                    "{}  {}  {}",
+                    time,
+                    volume,
+                    usage.plan_name.as_deref().unwrap_or_default()
+                );
+                for event in &entry.events {
+                    line.push_str(&format!("  [{}]", event.describe()));
+                }
+
+                println!("{}", line.trim_end());
+            }
+        }
+    }
+}
+
+/// Display a usage breakdown by period in the specified format
+pub fn display_report(periods: &[UsagePeriod], format: OutputFormat, use_color: bool) {
+    match format {
+        OutputFormat::Json => display_json(&periods),
+        OutputFormat::Jsonl => periods.iter().for_each(display_json_line),
+        OutputFormat::Csv => {
+            println!(
+                "{}",
+                csv::row(&[
+                    "period", "start", "end", "used_gb", "readings", "renewals", "bookings"
+                ])
+            );
+            for period in periods {
+                println!(
+                    "{}",
+                    csv::row(&[
+                        period.period.clone(),
+                        period.start.to_rfc3339(),
+                        period.end.to_rfc3339(),
+                        format!("{:.3}", period.used_gb),
+                        period.readings.to_string(),
+                        period.renewals.to_string(),
+                        period.bookings.to_string(),
+                    ])
+                );
+            }
+        }
+        _ => print!("{}", report_text(periods, use_color)),
+    }
+}
+
+/// A usage report as a human-readable table
+pub fn report_text(periods: &[UsagePeriod], use_color: bool) -> String {
+    let mut out = String::new();
+
+    if periods.is_empty() {
+        out.push_str("No readings recorded\n");
+        return out;
+    }
+
+    let header = format!("{:<16} {:>10} {:>9}  Notes", "Period", "Used", "Readings");
+    if use_color {
+        let _ = writeln!(out, "{}", header.bold());
+    } else {
+        let _ = writeln!(out, "{}", header);
+    }
+
+    for period in periods {
+        let mut notes = Vec::new();
+        if period.renewals > 0 {
+            notes.push("renewed");
+        }
+        if period.bookings > 0 {
+            notes.push("pass booked");
+        }
+
+        let _ = writeln!(
+            out,
+            "{:<16} {:>10} {:>9}  {}",
+            period.period,
+            format!("{:.2} GB", period.used_gb),
+            period.readings,
+            notes.join(", ")
+        );
+    }
+
+    let total: f64 = periods.iter().map(|period| period.used_gb).sum();
+    let total = format!("{:<16} {:>10}", "Total", format!("{:.2} GB", total));
+    if use_color {
+        let _ = writeln!(out, "{}", total.bold());
+    } else {
+        let _ = writeln!(out, "{}", total);
+    }
+
+    out
+}
+
+/// Display the billing cycles found in the history in the specified format
+pub fn display_cycles(cycles: &[Cycle], format: OutputFormat, use_color: bool) {
+    let local = |time: chrono::DateTime<chrono::Utc>| {
+        time.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+
+    match format {
+        OutputFormat::Json => display_json(&cycles),
+        OutputFormat::Jsonl => cycles.iter().for_each(display_json_line),
+        OutputFormat::Csv => {
+            println!(
+                "{}",
+                csv::row(&[
+                    "number",
+                    "started_at",
+                    "last_reading_at",
+                    "plan_name",
+                    "valid_until",
+                    "total_gb",
+                    "used_gb",
+                    "booked_gb",
+                    "bookings",
+                    "readings",
+                ])
+            );
+            for cycle in cycles {
+                println!(
+                    "{}",
+                    csv::row(&[
+                        cycle.number.to_string(),
+                        cycle.started_at.to_rfc3339(),
+                        cycle.last_reading_at.to_rfc3339(),
+                        cycle.plan_name.clone().unwrap_or_default(),
+                        cycle.valid_until.clone().unwrap_or_default(),
+                        cycle.total_gb.to_string(),
+                        cycle.used_gb.to_string(),
+                        cycle.booked_gb.to_string(),
+                        cycle.bookings.to_string(),
+                        cycle.readings.to_string(),
+                    ])
+                );
+            }
+        }
+        _ => {
+            if cycles.is_empty() {
+                println!("No readings recorded");
+                return;
+            }
+
+            let header = format!(
+                "{:>3}  {:<16}  {:<16}  {:<18} {:>9} {:>9}  Booked",
+                "#", "First reading", "Last reading", "Valid until", "Used", "Total"
+            );
+            if use_color {
+                println!("{}", header.bold());
+            } else {
+                println!("{}", header);
+            }
+
+            for cycle in cycles {
+                let booked = if cycle.bookings > 0 {
+                    format!("{:.2} GB ({}x)", cycle.booked_gb, cycle.bookings)
+                } else {
+                    "-".to_string()
+                };
+
+                println!(
+                    "{:>3}  {:<16}  {:<16}  {:<18} {:>9} {:>9}  {}",
+                    cycle.number,
+                    local(cycle.started_at),
+                    local(cycle.last_reading_at),
+                    cycle.valid_until.as_deref().unwrap_or("-"),
+                    format!("{:.2} GB", cycle.used_gb),
+                    format!("{:.2} GB", cycle.total_gb),
+                    booked
+                );
+            }
+        }
+    }
+}
+
+/// CSV columns of a reading, named like the `DataUsage` JSON fields
+const USAGE_CSV_COLUMNS: &[&str] = &[
+    "remaining_gb",
+    "total_gb",
+    "used_gb",
+    "percentage",
+    "plan_name",
+    "valid_until",
+    "is_unlimited",
+];
+
+fn usage_csv_fields(data: &DataUsage) -> Vec<String> {
+    vec![
+        data.remaining_gb.to_string(),
+        data.total_gb.to_string(),
+        data.used_gb.to_string(),
+        data.percentage.to_string(),
+        data.plan_name.clone().unwrap_or_default(),
+        data.valid_until.clone().unwrap_or_default(),
+        data.is_unlimited.to_string(),
+    ]
+}
+
+fn format_offer_volume(offer: &PassOffer) -> String {
+    if offer.is_unlimited {
+        "unlimited".to_string()
+    } else {
+        match offer.volume_gb {
+            Some(volume) => format!("{:.2} GB", volume),
+            None => "-".to_string(),
+        }
+    }
+}
+
+fn format_offer_price(offer: &PassOffer) -> String {
+    match offer.price_eur {
+        Some(price) => format!("{:.2} €", price),
+        None => "-".to_string(),
+    }
+}
+
+/// Display any serializable value in JSON format
+/// The JSON shown by `--format json`, also handed to hooks and notifiers
+pub fn to_json<T: Serialize + ?Sized>(data: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(data)
+}
+
+fn display_json<T: Serialize>(data: &T) {
+    match to_json(data) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Error serializing to JSON: {}", e),
+    }
 }
 
-/// Display data usage in JSON format
-fn display_json(data: &DataUsage) {
-    match serde_json::to_string_pretty(data) {
+fn display_json_line<T: Serialize>(data: &T) {
+    match serde_json::to_string(data) {
         Ok(json) => println!("{}", json),
         Err(e) => eprintln!("Error serializing to JSON: {}", e),
     }
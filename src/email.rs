@@ -0,0 +1,271 @@
+use crate::alert::Alert;
+use crate::config::state_dir;
+use crate::display;
+use crate::error::{DatapassError, Result};
+use crate::history::HistoryStore;
+use crate::notify::{EmailConfig, EmailTls, Notifier};
+use crate::report::{self, Bucket};
+use crate::types::DataUsage;
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Utc, Weekday};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const DIGEST_STATE_FILE: &str = "email_digest.json";
+
+/// Sends alerts, and optionally a weekly digest, by email
+pub struct EmailNotifier {
+    config: EmailConfig,
+    transport: SmtpTransport,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+
+    /// Readings for the digest's usage breakdown
+    history: HistoryStore,
+
+    /// When the last digest went out
+    digest_state: PathBuf,
+}
+
+/// When the last weekly digest was sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DigestState {
+    last_sent: DateTime<Utc>,
+}
+
+impl EmailNotifier {
+    pub fn new(config: EmailConfig, history: HistoryStore) -> Result<Self> {
+        let from = mailbox(&config.from)?;
+        let to = config
+            .to
+            .iter()
+            .map(|address| mailbox(address))
+            .collect::<Result<Vec<_>>>()?;
+        if to.is_empty() {
+            return Err(DatapassError::ConfigError(
+                "notify.email.to needs at least one recipient".to_string(),
+            ));
+        }
+
+        let builder = match config.tls {
+            EmailTls::Starttls => SmtpTransport::starttls_relay(&config.host),
+            EmailTls::Tls => SmtpTransport::relay(&config.host),
+            EmailTls::None => Ok(SmtpTransport::builder_dangerous(&config.host)),
+        }
+        .map_err(|e| DatapassError::NotifyError(e.to_string()))?;
+
+        let mut builder = builder
+            .port(config.port())
+            .timeout(Some(std::time::Duration::from_secs(config.timeout_seconds)));
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        let digest_state = config
+            .digest_state
+            .clone()
+            .unwrap_or_else(|| state_dir().join(DIGEST_STATE_FILE));
+
+        Ok(Self {
+            transport: builder.build(),
+            config,
+            from,
+            to,
+            history,
+            digest_state,
+        })
+    }
+
+    /// Send one plain text email to all recipients
+    pub fn send(&self, subject: &str, body: String) -> Result<()> {
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(format!("{} {}", self.config.subject_prefix, subject).trim_start())
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+        let message = message
+            .body(body)
+            .map_err(|e| DatapassError::NotifyError(e.to_string()))?;
+
+        self.transport
+            .send(&message)
+            .map_err(|e| DatapassError::NotifyError(format!("{}: {}", self.config.host, e)))?;
+        log::info!("Email sent to {}", self.config.to.join(", "));
+
+        Ok(())
+    }
+
+    /// The weekly summary: the current reading and the usage per day
+    fn digest(&self, data: &DataUsage, now: DateTime<Utc>) -> String {
+        let mut body = display::human_text(data, false);
+
+        body.push_str("Last 7 days\n\n");
+        match self.history.read() {
+            Ok(entries) => {
+                let periods =
+                    report::usage_by(&entries, Bucket::Day, Some(now - Duration::days(7)), None);
+                body.push_str(&display::report_text(&periods, false));
+            }
+            Err(e) => body.push_str(&format!("The usage history could not be read: {}\n", e)),
+        }
+
+        body.push_str(&footer(now));
+        body
+    }
+
+    fn last_digest(&self) -> Option<DateTime<Utc>> {
+        let text = std::fs::read_to_string(&self.digest_state).ok()?;
+        serde_json::from_str::<DigestState>(&text)
+            .map(|state| state.last_sent)
+            .ok()
+    }
+
+    fn save_last_digest(&self, last_sent: DateTime<Utc>) -> Result<()> {
+        if let Some(dir) = self.digest_state.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let temp_path = self
+            .digest_state
+            .with_extension(format!("tmp.{}", std::process::id()));
+        std::fs::write(
+            &temp_path,
+            serde_json::to_string_pretty(&DigestState { last_sent })?,
+        )?;
+        std::fs::rename(&temp_path, &self.digest_state)?;
+
+        Ok(())
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn notify(&mut self, alert: &Alert, data: &DataUsage) -> Result<()> {
+        let subject = format!(
+            "{}: {}",
+            capitalize(&alert.severity.to_string()),
+            alert.message
+        );
+        let body = format!(
+            "{}\n\n{}{}",
+            alert.message,
+            display::human_text(data, false),
+            footer(alert.timestamp)
+        );
+
+        self.send(&subject, body)
+    }
+
+    fn on_reading(&mut self, data: &DataUsage) -> Result<()> {
+        let now = Utc::now();
+        if !self.config.weekly_digest
+            || !digest_due(self.last_digest(), now, self.config.digest_weekday)
+        {
+            return Ok(());
+        }
+
+        let subject = if data.is_unlimited {
+            "Weekly summary: unlimited".to_string()
+        } else {
+            format!(
+                "Weekly summary: {:.2} GB of {:.2} GB left",
+                data.remaining_gb, data.total_gb
+            )
+        };
+        self.send(&subject, self.digest(data, now))?;
+        self.save_last_digest(now)
+    }
+}
+
+/// Whether a digest is due: none was sent since the start (local midnight)
+/// of the latest `weekday`
+pub fn digest_due(last_sent: Option<DateTime<Utc>>, now: DateTime<Utc>, weekday: Weekday) -> bool {
+    let Some(last_sent) = last_sent else {
+        return true;
+    };
+
+    let today = now.with_timezone(&Local).date_naive();
+    let days_since =
+        (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    let due_day = today - Duration::days(days_since as i64);
+
+    match Local
+        .from_local_datetime(&due_day.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+    {
+        Some(due) => last_sent < due.with_timezone(&Utc),
+        None => last_sent < now - Duration::days(7),
+    }
+}
+
+fn mailbox(address: &str) -> Result<Mailbox> {
+    address.parse().map_err(|e| {
+        DatapassError::ConfigError(format!("Invalid email address '{}': {}", address, e))
+    })
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn footer(time: DateTime<Utc>) -> String {
+    format!(
+        "--\nSent by datapass at {}\n",
+        time.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_due_once_a_week() {
+        let at = |day: u32, hour: u32| {
+            Local
+                .with_ymd_and_hms(2026, 10, day, hour, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+
+        // 2026-10-19 is a Monday
+        assert!(digest_due(None, at(14, 12), Weekday::Mon));
+        assert!(!digest_due(Some(at(19, 8)), at(19, 20), Weekday::Mon));
+        assert!(!digest_due(Some(at(19, 8)), at(25, 23), Weekday::Mon));
+        assert!(digest_due(Some(at(19, 8)), at(26, 0), Weekday::Mon));
+
+        // A digest missed on its day goes out with the next reading
+        assert!(digest_due(Some(at(12, 8)), at(21, 9), Weekday::Mon));
+        assert!(!digest_due(Some(at(21, 9)), at(23, 9), Weekday::Mon));
+        assert!(digest_due(Some(at(21, 9)), at(23, 9), Weekday::Fri));
+    }
+
+    #[test]
+    fn test_invalid_recipient() {
+        let config: EmailConfig = toml::from_str(
+            r#"
+            host = "localhost"
+            from = "datapass@example.com"
+            to = ["not an address"]
+            "#,
+        )
+        .unwrap();
+
+        let error = EmailNotifier::new(config, HistoryStore::new(PathBuf::from("x")))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("'not an address'"));
+    }
+}
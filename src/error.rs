@@ -2,6 +2,7 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum DatapassError {
+    #[cfg(feature = "http")]
     #[error("Failed to fetch data: {0}")]
     FetchError(#[from] reqwest::Error),
 
@@ -11,6 +12,27 @@ pub enum DatapassError {
     #[error("Data not found in HTML: {0}")]
     DataNotFound(String),
 
+    #[error("Booking failed: {0}")]
+    BookingError(String),
+
+    #[error("Configuration error: {0}")]
+    ConfigError(String),
+
+    #[error("Notification failed: {0}")]
+    NotifyError(String),
+
+    #[error("Invalid import data: {0}")]
+    ImportError(String),
+
+    #[error("The page source has no more pages")]
+    SourceExhausted,
+
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
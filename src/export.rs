@@ -0,0 +1,331 @@
+use crate::csv;
+use crate::error::{DatapassError, Result};
+use crate::history::HistoryEntry;
+use crate::types::DataUsage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Exported columns, in order: the `DataUsage` fields plus the time of the
+/// reading, the names of all its passes and the stale flag
+pub const COLUMNS: &[&str] = &[
+    "timestamp",
+    "remaining_gb",
+    "total_gb",
+    "used_gb",
+    "percentage",
+    "plan_name",
+    "valid_until",
+    "is_unlimited",
+    "pass_name",
+    "zone",
+    "pass_names",
+    "stale",
+];
+
+/// Separator between pass names in the `pass_names` column
+const PASS_NAME_SEPARATOR: &str = "; ";
+
+/// One exported reading, flat so that it fits a table row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportRow {
+    pub timestamp: DateTime<Utc>,
+    pub remaining_gb: f64,
+    pub total_gb: f64,
+    pub used_gb: f64,
+    pub percentage: f64,
+    pub plan_name: Option<String>,
+    pub valid_until: Option<String>,
+    pub is_unlimited: bool,
+
+    /// Name of the active pass
+    #[serde(default)]
+    pub pass_name: Option<String>,
+
+    /// Where the active pass is valid
+    #[serde(default)]
+    pub zone: Option<String>,
+
+    /// Names of all passes on the page, separated by "; "
+    #[serde(default)]
+    pub pass_names: Option<String>,
+
+    /// Whether the reading was a stored one shown in place of a failed
+    /// fetch; recorded readings are always fresh
+    #[serde(default)]
+    pub stale: bool,
+}
+
+impl From<&HistoryEntry> for ExportRow {
+    fn from(entry: &HistoryEntry) -> Self {
+        let usage = &entry.usage;
+        let pass_names: Vec<&str> = entry
+            .passes
+            .iter()
+            .filter_map(|pass| pass.name.as_deref())
+            .collect();
+
+        Self {
+            timestamp: entry.timestamp,
+            remaining_gb: usage.remaining_gb,
+            total_gb: usage.total_gb,
+            used_gb: usage.used_gb,
+            percentage: usage.percentage,
+            plan_name: usage.plan_name.clone(),
+            valid_until: usage.valid_until.clone(),
+            is_unlimited: usage.is_unlimited,
+            pass_name: usage.pass_name.clone(),
+            zone: usage.zone.clone(),
+            pass_names: (!pass_names.is_empty()).then(|| pass_names.join(PASS_NAME_SEPARATOR)),
+            stale: usage.is_stale(),
+        }
+    }
+}
+
+impl ExportRow {
+    /// The reading as a history entry. Pass details other than the names
+    /// are not exported, so the entry has no passes.
+    pub fn into_entry(self) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: self.timestamp,
+            usage: DataUsage {
+                used_gb: self.used_gb,
+                percentage: self.percentage,
+                is_unlimited: self.is_unlimited,
+                pass_name: self.pass_name,
+                zone: self.zone,
+                ..DataUsage::new(
+                    self.remaining_gb,
+                    self.total_gb,
+                    self.plan_name,
+                    self.valid_until,
+                )
+            },
+            passes: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.timestamp.to_rfc3339(),
+            self.remaining_gb.to_string(),
+            self.total_gb.to_string(),
+            self.used_gb.to_string(),
+            self.percentage.to_string(),
+            self.plan_name.clone().unwrap_or_default(),
+            self.valid_until.clone().unwrap_or_default(),
+            self.is_unlimited.to_string(),
+            self.pass_name.clone().unwrap_or_default(),
+            self.zone.clone().unwrap_or_default(),
+            self.pass_names.clone().unwrap_or_default(),
+            self.stale.to_string(),
+        ]
+    }
+
+    /// Build a row from CSV fields keyed by column name. Only `timestamp`,
+    /// `remaining_gb` and `total_gb` are required; `used_gb` and
+    /// `percentage` are derived when missing.
+    fn from_csv(fields: &HashMap<&str, &str>) -> std::result::Result<Self, String> {
+        let text = |name: &str| {
+            fields
+                .get(name)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let number = |name: &str| -> std::result::Result<Option<f64>, String> {
+            text(name)
+                .map(|value| {
+                    value
+                        .parse::<f64>()
+                        .map_err(|e| format!("{} '{}': {}", name, value, e))
+                })
+                .transpose()
+        };
+        let flag = |name: &str| -> std::result::Result<bool, String> {
+            match text(name) {
+                None | Some("false") | Some("0") => Ok(false),
+                Some("true") | Some("1") => Ok(true),
+                Some(value) => Err(format!("{} '{}' is not a boolean", name, value)),
+            }
+        };
+
+        let timestamp = text("timestamp").ok_or("missing timestamp")?;
+        let timestamp = DateTime::parse_from_rfc3339(timestamp)
+            .map_err(|e| format!("timestamp '{}': {}", timestamp, e))?
+            .with_timezone(&Utc);
+        let remaining_gb = number("remaining_gb")?.ok_or("missing remaining_gb")?;
+        let total_gb = number("total_gb")?.ok_or("missing total_gb")?;
+        let derived = DataUsage::new(remaining_gb, total_gb, None, None);
+
+        Ok(Self {
+            timestamp,
+            remaining_gb,
+            total_gb,
+            used_gb: number("used_gb")?.unwrap_or(derived.used_gb),
+            percentage: number("percentage")?.unwrap_or(derived.percentage),
+            plan_name: text("plan_name").map(str::to_string),
+            valid_until: text("valid_until").map(str::to_string),
+            is_unlimited: flag("is_unlimited")?,
+            pass_name: text("pass_name").map(str::to_string),
+            zone: text("zone").map(str::to_string),
+            pass_names: text("pass_names").map(str::to_string),
+            stale: flag("stale")?,
+        })
+    }
+}
+
+/// Write readings as CSV with a header line
+pub fn write_csv(entries: &[HistoryEntry], out: &mut dyn Write) -> Result<()> {
+    writeln!(out, "{}", csv::row(COLUMNS))?;
+    for entry in entries {
+        writeln!(out, "{}", csv::row(&ExportRow::from(entry).csv_fields()))?;
+    }
+    Ok(())
+}
+
+/// Write readings as JSON Lines (newline-delimited JSON), one flat object
+/// with the CSV columns per line
+pub fn write_jsonl(entries: &[HistoryEntry], out: &mut dyn Write) -> Result<()> {
+    for entry in entries {
+        writeln!(out, "{}", serde_json::to_string(&ExportRow::from(entry))?)?;
+    }
+    Ok(())
+}
+
+/// Write readings as a JSON array of the flat rows
+pub fn write_json(entries: &[HistoryEntry], out: &mut dyn Write) -> Result<()> {
+    let rows: Vec<ExportRow> = entries.iter().map(ExportRow::from).collect();
+    writeln!(out, "{}", serde_json::to_string_pretty(&rows)?)?;
+    Ok(())
+}
+
+/// Read CSV written by [`write_csv`], or any CSV with a header line naming
+/// at least the `timestamp`, `remaining_gb` and `total_gb` columns
+pub fn read_csv(text: &str) -> Result<Vec<ExportRow>> {
+    let records = csv::parse(text).map_err(DatapassError::ImportError)?;
+    let Some((header, records)) = records.split_first() else {
+        return Ok(Vec::new());
+    };
+
+    records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let fields: HashMap<&str, &str> = header
+                .iter()
+                .map(|name| name.trim())
+                .zip(record.iter().map(String::as_str))
+                .collect();
+
+            // Line numbers count the header as line 1
+            ExportRow::from_csv(&fields)
+                .map_err(|e| DatapassError::ImportError(format!("row {}: {}", index + 2, e)))
+        })
+        .collect()
+}
+
+/// Read JSON Lines written by [`write_jsonl`]
+pub fn read_jsonl(text: &str) -> Result<Vec<ExportRow>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| DatapassError::ImportError(format!("line {}: {}", index + 1, e)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PassUsage;
+    use chrono::TimeZone;
+
+    fn entries() -> Vec<HistoryEntry> {
+        let pass = |name: &str| PassUsage {
+            id: None,
+            name: Some(name.to_string()),
+            remaining_gb: None,
+            total_gb: None,
+            is_unlimited: false,
+            is_exhausted: false,
+            valid_until: None,
+            zone: None,
+        };
+
+        vec![
+            HistoryEntry {
+                timestamp: Utc.with_ymd_and_hms(2026, 10, 1, 8, 0, 0).unwrap(),
+                usage: DataUsage {
+                    pass_name: Some("Prepaid XL".to_string()),
+                    zone: Some("Germany, Country group 1".to_string()),
+                    ..DataUsage::new(
+                        38.36,
+                        51.0,
+                        Some("MagentaMobil Prepaid XL, \"EU\"".to_string()),
+                        Some("12. November 2026".to_string()),
+                    )
+                },
+                passes: vec![pass("Prepaid XL"), pass("Datenbonus")],
+                events: Vec::new(),
+            },
+            HistoryEntry {
+                timestamp: Utc.with_ymd_and_hms(2026, 10, 1, 9, 0, 0).unwrap(),
+                usage: DataUsage::new_unlimited(None, None),
+                passes: Vec::new(),
+                events: Vec::new(),
+            },
+        ]
+    }
+
+    fn rows(entries: &[HistoryEntry]) -> Vec<ExportRow> {
+        entries.iter().map(ExportRow::from).collect()
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let mut out = Vec::new();
+        write_csv(&entries(), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(text.starts_with(
+            "timestamp,remaining_gb,total_gb,used_gb,percentage,plan_name,valid_until,is_unlimited,pass_name,zone,pass_names,stale\n"
+        ));
+        assert!(text
+            .contains(",Prepaid XL,\"Germany, Country group 1\",Prepaid XL; Datenbonus,false\n"));
+
+        let imported = read_csv(&text).unwrap();
+        assert_eq!(imported, rows(&entries()));
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let mut out = Vec::new();
+        write_jsonl(&entries(), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), 2);
+
+        let imported = read_jsonl(&text).unwrap();
+        assert_eq!(imported, rows(&entries()));
+
+        let entry = imported[0].clone().into_entry();
+        assert_eq!(entry.usage.remaining_gb, 38.36);
+        assert_eq!(entry.usage.used_gb, entries()[0].usage.used_gb);
+        assert_eq!(entry.usage.pass_name.as_deref(), Some("Prepaid XL"));
+    }
+
+    #[test]
+    fn test_read_minimal_csv() {
+        let rows =
+            read_csv("timestamp,total_gb,remaining_gb\n2026-10-01T08:00:00Z,50,40\n").unwrap();
+        assert_eq!(rows[0].used_gb, 10.0);
+        assert_eq!(rows[0].percentage, 20.0);
+        assert!(!rows[0].is_unlimited);
+
+        let error = read_csv("timestamp,total_gb\n2026-10-01T08:00:00Z,50\n").unwrap_err();
+        assert!(error.to_string().contains("row 2: missing remaining_gb"));
+    }
+}
@@ -1,45 +1,209 @@
+use crate::cache::{CachedResponse, ResponseCache};
 use crate::error::{DatapassError, Result};
+use chrono::Utc;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use std::future::Future;
+use std::time::Duration;
 
-const DEFAULT_URL: &str = "https://datapass.de";
+pub(crate) const DEFAULT_URL: &str = "https://datapass.de";
+const TARIFF_TRANSPARENCY_PATH: &str = "/tariffTransparency/domestic";
 // Use a real browser user agent to avoid being blocked
 const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/144.0.0.0 Safari/537.36";
 
-/// Fetch HTML content from the specified URL or default datapass.de
-pub fn fetch_html(url: Option<&str>, cookie: Option<&str>) -> Result<String> {
-    let target_url = url.unwrap_or(DEFAULT_URL);
-
-    log::info!("Fetching data from: {}", target_url);
+pub(crate) const DEFAULT_COOKIES: &str = "Apollo-Summation-Disabled=true; Apollo-Lang=en_DE_TMDE";
+pub(crate) const DEFAULT_ACCEPT_LANGUAGE: &str = "en-US,en;q=0.5";
+pub(crate) const ACCEPT_HTML: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8";
 
+/// Build a blocking HTTP client with the browser user agent and the given redirect policy
+pub(crate) fn build_client(
+    redirect: reqwest::redirect::Policy,
+) -> Result<reqwest::blocking::Client> {
     let client = reqwest::blocking::Client::builder()
         .user_agent(USER_AGENT)
-        .timeout(std::time::Duration::from_secs(30))
+        .timeout(Duration::from_secs(30))
+        .redirect(redirect)
+        .build()?;
+
+    Ok(client)
+}
+
+/// Builder for the asynchronous HTTP client used for page fetches
+pub(crate) fn async_client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(30))
         .redirect(reqwest::redirect::Policy::limited(10))
+}
+
+/// A page request: where to go and which page variant to ask for
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PageRequest<'a> {
+    pub url: &'a str,
+    pub cookie: &'a str,
+    pub accept_language: &'a str,
+}
+
+impl<'a> PageRequest<'a> {
+    /// Request with the default URL and cookies filled in
+    pub(crate) fn new(url: Option<&'a str>, cookie: Option<&'a str>) -> Self {
+        Self {
+            url: url.unwrap_or(DEFAULT_URL),
+            cookie: cookie.unwrap_or(DEFAULT_COOKIES),
+            accept_language: DEFAULT_ACCEPT_LANGUAGE,
+        }
+    }
+}
+
+/// Run a future to completion on a single-threaded runtime. The blocking
+/// fetch functions are thin wrappers over the async ones, so this must not
+/// be called from within an async runtime.
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
         .build()?;
 
+    Ok(runtime.block_on(future))
+}
+
+/// Fetch HTML content from the specified URL or default datapass.de
+pub fn fetch_html(url: Option<&str>, cookie: Option<&str>) -> Result<String> {
+    let client = async_client_builder().build()?;
+    block_on(fetch_page(
+        &client,
+        PageRequest::new(url, cookie),
+        None,
+        None,
+    ))?
+}
+
+/// Fetch HTML content through the response cache.
+///
+/// A cached page younger than `max_age` is served without contacting the
+/// server. Otherwise the request carries `If-None-Match`/`If-Modified-Since`
+/// for the cached page, and a `304 Not Modified` answer serves the cached body.
+pub fn fetch_html_cached(
+    url: Option<&str>,
+    cookie: Option<&str>,
+    cache: &ResponseCache,
+    max_age: Option<Duration>,
+) -> Result<String> {
+    let client = async_client_builder().build()?;
+    block_on(fetch_page(
+        &client,
+        PageRequest::new(url, cookie),
+        Some(cache),
+        max_age,
+    ))?
+}
+
+/// Fetch a page with the given client, through `cache` if there is one
+pub(crate) async fn fetch_page(
+    client: &reqwest::Client,
+    request: PageRequest<'_>,
+    cache: Option<&ResponseCache>,
+    max_age: Option<Duration>,
+) -> Result<String> {
+    let target_url = request.url;
+    let cookie_str = request.cookie;
+
+    let Some(cache) = cache else {
+        let response = send_request(client, request, None).await?;
+        let html = response.text().await?;
+        log::debug!("Successfully fetched {} bytes", html.len());
+        return Ok(html);
+    };
+
+    let cached = cache.get(target_url, cookie_str);
+
+    if let (Some(entry), Some(max_age)) = (&cached, max_age) {
+        if entry.is_fresh(max_age) {
+            log::info!(
+                "Serving cached page for {} (age {}s)",
+                target_url,
+                entry.age().as_secs()
+            );
+            return Ok(entry.body.clone());
+        }
+    }
+
+    let response = send_request(client, request, cached.as_ref()).await?;
+
+    if let Some(mut entry) = cached.filter(|_| response.status() == StatusCode::NOT_MODIFIED) {
+        log::info!("Page not modified, using cached copy");
+        entry.fetched_at = Utc::now();
+        store(cache, cookie_str, &entry);
+        return Ok(entry.body);
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+            .map(str::to_string)
+    };
+
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+
+    let html = response.text().await?;
+    log::debug!("Successfully fetched {} bytes", html.len());
+
+    let entry = CachedResponse {
+        url: target_url.to_string(),
+        etag,
+        last_modified,
+        fetched_at: Utc::now(),
+        body: html,
+    };
+    store(cache, cookie_str, &entry);
+
+    Ok(entry.body)
+}
+
+/// Write a cache entry; a failing cache must not fail the fetch
+fn store(cache: &ResponseCache, cookie: &str, entry: &CachedResponse) {
+    if let Err(e) = cache.put(cookie, entry) {
+        log::warn!("Failed to update response cache: {}", e);
+    }
+}
+
+/// Send the GET request, conditional on the cached page's validators.
+/// Returns successful and `304 Not Modified` responses.
+async fn send_request(
+    client: &reqwest::Client,
+    page: PageRequest<'_>,
+    cached: Option<&CachedResponse>,
+) -> Result<reqwest::Response> {
+    log::info!("Fetching data from: {}", page.url);
+
     let mut request = client
-        .get(target_url)
-        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8")
-        .header("Accept-Language", "en-US,en;q=0.5")
+        .get(page.url)
+        .header("Accept", ACCEPT_HTML)
+        .header("Accept-Language", page.accept_language)
         .header("Cache-Control", "no-cache")
         .header("Pragma", "no-cache");
 
-    // Add cookies if provided, otherwise use default Apollo cookies
-    let cookie_str = cookie.unwrap_or("Apollo-Summation-Disabled=true; Apollo-Lang=en_DE_TMDE");
-    log::debug!("Using cookies: {}", cookie_str);
-    request = request.header("Cookie", cookie_str);
+    log::debug!("Using cookies: {}", page.cookie);
+    request = request.header("Cookie", page.cookie);
 
-    let response = request.send()?;
-
-    if !response.status().is_success() {
-        return Err(DatapassError::FetchError(
-            response.error_for_status().unwrap_err(),
-        ));
+    if let Some(entry) = cached {
+        if let Some(etag) = &entry.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
     }
 
-    let html = response.text()?;
-    log::debug!("Successfully fetched {} bytes", html.len());
+    let response = request.send().await?;
 
-    Ok(html)
+    if response.status() == StatusCode::NOT_MODIFIED && cached.is_some() {
+        return Ok(response);
+    }
+
+    Ok(response.error_for_status()?)
 }
 
 /// Read HTML from a local file (useful for testing)
@@ -48,3 +212,21 @@ pub fn read_local_file(path: &str) -> Result<String> {
     let html = std::fs::read_to_string(path)?;
     Ok(html)
 }
+
+/// Parse the given URL or default datapass.de
+pub(crate) fn base_url(url: Option<&str>) -> Result<reqwest::Url> {
+    let base = url.unwrap_or(DEFAULT_URL);
+    reqwest::Url::parse(base).map_err(|e| DatapassError::InvalidUrl(format!("{}: {}", base, e)))
+}
+
+/// Resolve a (possibly relative) link against a page URL
+pub(crate) fn join_url(page: &reqwest::Url, link: &str) -> Result<reqwest::Url> {
+    page.join(link)
+        .map_err(|e| DatapassError::InvalidUrl(format!("{}: {}", link, e)))
+}
+
+/// Build the tariff transparency URL on the same host as the given (or default) URL
+pub fn tariff_transparency_url(base: Option<&str>) -> Result<String> {
+    let url = join_url(&base_url(base)?, TARIFF_TRANSPARENCY_PATH)?;
+    Ok(url.to_string())
+}
@@ -0,0 +1,299 @@
+use crate::cycle;
+use crate::error::Result;
+use crate::history::{HistoryEntry, HistoryStore};
+use crate::types::{DataUsage, Forecast, ForecastMethod};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+/// Burn rates below this (about 1 KB a day) are rounding noise from idle
+/// readings, not consumption
+const MIN_BURN_RATE_GB_PER_DAY: f64 = 1e-6;
+
+/// Forecast settings (`[forecast]` config section); forecasts are made
+/// whenever the history is recorded
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ForecastConfig {
+    pub enabled: bool,
+
+    /// Burn rate estimator: "linear" or "ewma"
+    pub method: ForecastMethod,
+
+    /// Only readings from this many hours back are considered
+    pub window_hours: u64,
+
+    /// Weight of the newest rate for the EWMA estimator (0..1)
+    pub ewma_alpha: f64,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            method: ForecastMethod::default(),
+            window_hours: 72,
+            ewma_alpha: 0.3,
+        }
+    }
+}
+
+/// Forecasts readings from the recorded history
+pub struct Forecaster {
+    store: HistoryStore,
+    config: ForecastConfig,
+}
+
+impl Forecaster {
+    pub fn new(store: HistoryStore, config: ForecastConfig) -> Self {
+        Self { store, config }
+    }
+
+    /// Forecast for `usage` from the readings in the configured window
+    pub fn forecast(&self, usage: &DataUsage, now: DateTime<Utc>) -> Result<Option<Forecast>> {
+        if usage.is_unlimited {
+            return Ok(None);
+        }
+
+        let since = now - Duration::hours(self.config.window_hours as i64);
+        let entries = self.store.range(Some(since), None)?;
+
+        Ok(forecast(&entries, usage, &self.config))
+    }
+}
+
+/// Project when the volume runs out from readings in chronological order.
+/// Only the readings of the current cycle are used; at least two of them,
+/// some time apart, are needed.
+pub fn forecast(
+    entries: &[HistoryEntry],
+    usage: &DataUsage,
+    config: &ForecastConfig,
+) -> Option<Forecast> {
+    let samples = current_cycle(entries);
+    let (first, last) = (samples.first()?, samples.last()?);
+    if last.timestamp <= first.timestamp || last.usage.is_unlimited {
+        return None;
+    }
+
+    let burn_rate = match config.method {
+        ForecastMethod::Linear => linear_rate(samples),
+        ForecastMethod::Ewma => ewma_rate(samples, config.ewma_alpha),
+    }?;
+    let burn_rate = if burn_rate < MIN_BURN_RATE_GB_PER_DAY {
+        0.0
+    } else {
+        burn_rate
+    };
+
+    let remaining = last.usage.remaining_gb;
+    // Too far off to be represented is as good as never
+    let exhausts_at = (burn_rate > 0.0)
+        .then(|| days_to_duration(remaining / burn_rate))
+        .flatten()
+        .and_then(|duration| last.timestamp.checked_add_signed(duration));
+
+    let valid_end = usage.valid_until_end();
+
+    let remaining_at_valid_until = valid_end.map(|end| {
+        let days_left = days_between(last.timestamp, end).max(0.0);
+        (remaining - burn_rate * days_left).max(0.0)
+    });
+
+    Some(Forecast {
+        method: config.method,
+        burn_rate_gb_per_day: burn_rate,
+        exhausts_at,
+        remaining_at_valid_until_gb: remaining_at_valid_until,
+        lasts_until_valid_until: valid_end
+            .map(|end| exhausts_at.map_or(true, |exhausted| exhausted >= end)),
+        samples: samples.len(),
+    })
+}
+
+/// The readings of the current cycle
+fn current_cycle(entries: &[HistoryEntry]) -> &[HistoryEntry] {
+    match cycle::cycles(entries).last() {
+        Some(current) => &entries[current.first..=current.last],
+        None => &[],
+    }
+}
+
+/// Least-squares slope of the used volume over time, in GB per day. The used
+/// volume, unlike the remaining one, carries on across bookings.
+fn linear_rate(samples: &[HistoryEntry]) -> Option<f64> {
+    let origin = samples.first()?.timestamp;
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|entry| (days_between(origin, entry.timestamp), entry.usage.used_gb))
+        .collect();
+
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_u = points.iter().map(|(_, u)| u).sum::<f64>() / n;
+
+    let covariance: f64 = points
+        .iter()
+        .map(|(t, u)| (t - mean_t) * (u - mean_u))
+        .sum();
+    let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+/// Exponentially weighted average of the rates between consecutive readings
+fn ewma_rate(samples: &[HistoryEntry], alpha: f64) -> Option<f64> {
+    let alpha = alpha.clamp(0.0, 1.0);
+
+    samples
+        .windows(2)
+        .filter_map(|pair| {
+            let days = days_between(pair[0].timestamp, pair[1].timestamp);
+            (days > 0.0).then(|| cycle::consumption(&pair[0].usage, &pair[1].usage) / days)
+        })
+        .fold(None, |average, rate| match average {
+            None => Some(rate),
+            Some(average) => Some(alpha * rate + (1.0 - alpha) * average),
+        })
+}
+
+fn days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_seconds() as f64 / 86_400.0
+}
+
+fn days_to_duration(days: f64) -> Option<Duration> {
+    // The cast saturates; out of range values are left to `try_seconds`
+    Duration::try_seconds((days * 86_400.0) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(hours: i64, remaining_gb: f64, total_gb: f64) -> HistoryEntry {
+        let start = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        HistoryEntry {
+            timestamp: start + Duration::hours(hours),
+            usage: DataUsage::new(remaining_gb, total_gb, None, None),
+            passes: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_linear_forecast() {
+        // 2 GB per day, 40 GB left at the last reading
+        let entries = [
+            entry(0, 44.0, 51.0),
+            entry(24, 42.0, 51.0),
+            entry(48, 40.0, 51.0),
+        ];
+        let usage = DataUsage::new(40.0, 51.0, None, Some("30. October 2026".to_string()));
+
+        let forecast = forecast(&entries, &usage, &ForecastConfig::default()).unwrap();
+        assert!((forecast.burn_rate_gb_per_day - 2.0).abs() < 1e-9);
+        assert_eq!(forecast.samples, 3);
+        assert_eq!(
+            forecast.exhausts_at,
+            Some(Utc.with_ymd_and_hms(2026, 10, 23, 0, 0, 0).unwrap())
+        );
+        assert_eq!(forecast.lasts_until_valid_until, Some(false));
+        assert_eq!(forecast.remaining_at_valid_until_gb, Some(0.0));
+    }
+
+    #[test]
+    fn test_ewma_favours_recent_rate() {
+        let entries = [
+            entry(0, 50.0, 51.0),
+            entry(24, 49.0, 51.0),
+            entry(48, 45.0, 51.0),
+        ];
+        let usage = DataUsage::new(45.0, 51.0, None, None);
+        let config = ForecastConfig {
+            method: ForecastMethod::Ewma,
+            ewma_alpha: 0.5,
+            ..ForecastConfig::default()
+        };
+
+        let forecast = forecast(&entries, &usage, &config).unwrap();
+        assert!((forecast.burn_rate_gb_per_day - 2.5).abs() < 1e-9);
+        assert_eq!(forecast.lasts_until_valid_until, None);
+    }
+
+    #[test]
+    fn test_forecast_starts_after_renewal() {
+        // The pass renewed between the second and third reading
+        let entries = [
+            entry(0, 10.0, 51.0),
+            entry(24, 2.0, 51.0),
+            entry(48, 51.0, 51.0),
+            entry(72, 50.0, 51.0),
+        ];
+        let usage = DataUsage::new(50.0, 51.0, None, None);
+
+        let forecast = forecast(&entries, &usage, &ForecastConfig::default()).unwrap();
+        assert_eq!(forecast.samples, 2);
+        assert!((forecast.burn_rate_gb_per_day - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_forecast_continues_across_booking() {
+        // 1 GB per day, with a 5 GB pass booked on the second day
+        let entries = [
+            entry(0, 10.0, 51.0),
+            entry(24, 14.0, 56.0),
+            entry(48, 13.0, 56.0),
+        ];
+        let usage = DataUsage::new(13.0, 56.0, None, None);
+
+        let forecast = forecast(&entries, &usage, &ForecastConfig::default()).unwrap();
+        assert_eq!(forecast.samples, 3);
+        assert!((forecast.burn_rate_gb_per_day - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_forecast_without_consumption_data() {
+        let usage = DataUsage::new(40.0, 51.0, None, None);
+        let config = ForecastConfig::default();
+
+        assert!(forecast(&[], &usage, &config).is_none());
+        assert!(forecast(&[entry(0, 40.0, 51.0)], &usage, &config).is_none());
+
+        let idle = forecast(
+            &[entry(0, 40.0, 51.0), entry(24, 40.0, 51.0)],
+            &usage,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(idle.burn_rate_gb_per_day, 0.0);
+        assert!(idle.exhausts_at.is_none());
+    }
+
+    #[test]
+    fn test_identical_readings_do_not_run_out() {
+        // Float noise in the slope must not become a date far out of range
+        for remaining in [0.3, 10.55] {
+            let entries: Vec<HistoryEntry> =
+                (0..6).map(|i| entry(i * 7, remaining, 51.0)).collect();
+            let usage = DataUsage::new(remaining, 51.0, None, Some("30. October 2026".to_string()));
+
+            for method in [ForecastMethod::Linear, ForecastMethod::Ewma] {
+                let config = ForecastConfig {
+                    method,
+                    ..ForecastConfig::default()
+                };
+                let forecast = forecast(&entries, &usage, &config).unwrap();
+                assert_eq!(forecast.burn_rate_gb_per_day, 0.0);
+                assert!(forecast.exhausts_at.is_none());
+                assert_eq!(forecast.lasts_until_valid_until, Some(true));
+            }
+        }
+    }
+
+    #[test]
+    fn test_days_to_duration_out_of_range() {
+        assert_eq!(days_to_duration(1.0), Some(Duration::days(1)));
+        assert!(days_to_duration(1e300).is_none());
+        assert!(days_to_duration(f64::INFINITY).is_none());
+    }
+}
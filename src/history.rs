@@ -0,0 +1,461 @@
+use crate::config;
+use crate::cycle::{self, HistoryEvent};
+use crate::error::Result;
+use crate::parser;
+use crate::source::Source;
+use crate::types::{DataUsage, PassUsage};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// History settings (`[history]` config section)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Record readings; the section being present enables it
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Store location (default: `$XDG_DATA_HOME/datapass/history.jsonl`)
+    pub path: Option<PathBuf>,
+
+    /// Drop readings older than this many days
+    pub retention_days: Option<i64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl HistoryConfig {
+    /// The store this config points at
+    pub fn store(&self) -> HistoryStore {
+        HistoryStore::new(self.path.clone().unwrap_or_else(HistoryStore::default_path))
+    }
+
+    /// Retention as a duration
+    pub fn retention(&self) -> Option<Duration> {
+        self.retention_days.map(Duration::days)
+    }
+}
+
+/// One stored reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the reading was fetched
+    pub timestamp: DateTime<Utc>,
+
+    /// The reading itself
+    #[serde(flatten)]
+    pub usage: DataUsage,
+
+    /// The individual passes on the page
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub passes: Vec<PassUsage>,
+
+    /// Cycle events detected since the previous reading
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<HistoryEvent>,
+}
+
+/// Append-only JSONL store of readings, one entry per line in fetch order
+/// (default: `$XDG_DATA_HOME/datapass/history.jsonl`)
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Default location: `$XDG_DATA_HOME/datapass/history.jsonl`
+    pub fn default_path() -> PathBuf {
+        config::data_dir().join(HISTORY_FILE)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read all entries; a missing store is empty and unreadable lines are skipped
+    pub fn read(&self) -> Result<Vec<HistoryEntry>> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let entries = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    log::warn!("Skipping malformed history line: {}", e);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Entries with `since <= timestamp < until`
+    pub fn range(
+        &self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<HistoryEntry>> {
+        Ok(self
+            .read()?
+            .into_iter()
+            .filter(|entry| since.map_or(true, |since| entry.timestamp >= since))
+            .filter(|entry| until.map_or(true, |until| entry.timestamp < until))
+            .collect())
+    }
+
+    /// Append one entry
+    pub fn append(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+
+        Ok(())
+    }
+
+    /// Drop entries older than `cutoff`; returns how many were removed
+    pub fn prune(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let entries = self.read()?;
+        let before = entries.len();
+        let kept: Vec<_> = entries
+            .into_iter()
+            .filter(|entry| entry.timestamp >= cutoff)
+            .collect();
+
+        let removed = before - kept.len();
+        if removed > 0 {
+            self.write_all(&kept)?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Merge readings from elsewhere into the store, skipping those whose
+    /// timestamp is already present; returns how many were added. The merged
+    /// store is kept in timestamp order and its cycle events are detected anew.
+    pub fn import(&self, entries: Vec<HistoryEntry>) -> Result<usize> {
+        let mut merged = self.read()?;
+        let mut seen: HashSet<DateTime<Utc>> = merged.iter().map(|entry| entry.timestamp).collect();
+        let before = merged.len();
+        merged.extend(
+            entries
+                .into_iter()
+                .filter(|entry| seen.insert(entry.timestamp)),
+        );
+
+        let added = merged.len() - before;
+        if added > 0 {
+            merged.sort_by_key(|entry| entry.timestamp);
+            for index in 0..merged.len() {
+                merged[index].events = index
+                    .checked_sub(1)
+                    .and_then(|previous| {
+                        cycle::detect(&merged[previous].usage, &merged[index].usage)
+                    })
+                    .into_iter()
+                    .collect();
+            }
+            self.write_all(&merged)?;
+        }
+
+        Ok(added)
+    }
+
+    /// Timestamp of the first entry, read without loading the whole store
+    fn first_timestamp(&self) -> Option<DateTime<Utc>> {
+        let file = std::fs::File::open(&self.path).ok()?;
+        let line = BufReader::new(file).lines().next()?.ok()?;
+        serde_json::from_str::<HistoryEntry>(&line)
+            .ok()
+            .map(|entry| entry.timestamp)
+    }
+
+    /// Replace the store's content, through a temporary file and a rename
+    fn write_all(&self, entries: &[HistoryEntry]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut text = String::new();
+        for entry in entries {
+            text.push_str(&serde_json::to_string(entry)?);
+            text.push('\n');
+        }
+
+        let temp_path = self
+            .path
+            .with_extension(format!("tmp.{}", std::process::id()));
+        std::fs::write(&temp_path, text)?;
+        std::fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// Records readings into the configured store, marks cycle events and
+/// applies retention
+pub struct History {
+    store: HistoryStore,
+    retention: Option<Duration>,
+    last: Option<DataUsage>,
+}
+
+impl History {
+    pub fn new(config: &HistoryConfig) -> Self {
+        Self {
+            store: config.store(),
+            retention: config.retention(),
+            last: None,
+        }
+    }
+
+    /// Append a reading, with the events since the previous one; returns
+    /// those events
+    pub fn record(&mut self, mut entry: HistoryEntry) -> Result<Vec<HistoryEvent>> {
+        if self.last.is_none() {
+            self.last = self.store.read()?.pop().map(|last| last.usage);
+        }
+
+        entry.events = self
+            .last
+            .as_ref()
+            .and_then(|last| cycle::detect(last, &entry.usage))
+            .into_iter()
+            .collect();
+        for event in &entry.events {
+            log::info!("History: {}", event.describe());
+        }
+
+        self.store.append(&entry)?;
+        self.last = Some(entry.usage.clone());
+
+        // Rewriting the store is only worth it once the oldest entry expired
+        if let Some(retention) = self.retention {
+            let cutoff = entry.timestamp - retention;
+            if self
+                .store
+                .first_timestamp()
+                .is_some_and(|first| first < cutoff)
+            {
+                let removed = self.store.prune(cutoff)?;
+                log::info!("Pruned {} history entries older than {}", removed, cutoff);
+            }
+        }
+
+        Ok(entry.events)
+    }
+}
+
+/// Wraps a source and records every successfully parsed page in the history
+pub struct RecordingSource<S> {
+    inner: S,
+    history: History,
+}
+
+impl<S: Source> RecordingSource<S> {
+    pub fn new(inner: S, history: History) -> Self {
+        Self { inner, history }
+    }
+}
+
+impl<S: Source> Source for RecordingSource<S> {
+    fn fetch(&mut self) -> Result<String> {
+        self.inner.fetch()
+    }
+
+    fn fetch_usage(&mut self) -> Result<DataUsage> {
+        let html = self.inner.fetch()?;
+        let usage = parser::parse_html(&html)?;
+
+        let entry = HistoryEntry {
+            timestamp: Utc::now(),
+            usage: usage.clone(),
+            passes: parser::parse_passes(&html).unwrap_or_default(),
+            events: Vec::new(),
+        };
+
+        // A broken history must not cost the user the reading
+        if let Err(e) = self.history.record(entry) {
+            log::warn!("Failed to record the reading in the history: {}", e);
+        }
+
+        Ok(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: DateTime<Utc>, remaining_gb: f64) -> HistoryEntry {
+        HistoryEntry {
+            timestamp,
+            usage: DataUsage::new(remaining_gb, 51.0, Some("Prepaid XL".to_string()), None),
+            passes: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_append_read_and_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path().join("history.jsonl"));
+        let start = Utc::now() - Duration::hours(3);
+
+        for hour in 0..3 {
+            store
+                .append(&entry(start + Duration::hours(hour), 40.0 - hour as f64))
+                .unwrap();
+        }
+
+        let entries = store.read().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].usage.remaining_gb, 38.0);
+        assert_eq!(entries[0].usage.plan_name, Some("Prepaid XL".to_string()));
+
+        let middle = store
+            .range(
+                Some(start + Duration::minutes(30)),
+                Some(start + Duration::minutes(90)),
+            )
+            .unwrap();
+        assert_eq!(middle.len(), 1);
+        assert_eq!(middle[0].usage.remaining_gb, 39.0);
+    }
+
+    #[test]
+    fn test_import_merges_and_skips_known_timestamps() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path().join("history.jsonl"));
+        let start = Utc::now() - Duration::hours(3);
+        store.append(&entry(start, 40.0)).unwrap();
+        store
+            .append(&entry(start + Duration::hours(2), 38.0))
+            .unwrap();
+
+        // One known reading, one in between and a renewal after the last
+        let added = store
+            .import(vec![
+                entry(start, 45.0),
+                entry(start + Duration::hours(1), 39.0),
+                entry(start + Duration::hours(3), 51.0),
+            ])
+            .unwrap();
+        assert_eq!(added, 2);
+
+        let entries = store.read().unwrap();
+        let remaining: Vec<f64> = entries
+            .iter()
+            .map(|entry| entry.usage.remaining_gb)
+            .collect();
+        assert_eq!(remaining, [40.0, 39.0, 38.0, 51.0]);
+        assert!(matches!(
+            entries[3].events[..],
+            [HistoryEvent::CycleStarted { .. }]
+        ));
+
+        assert_eq!(store.import(vec![entry(start, 40.0)]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_record_applies_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HistoryConfig {
+            enabled: true,
+            path: Some(dir.path().join("history.jsonl")),
+            retention_days: Some(7),
+        };
+        let now = Utc::now();
+
+        config
+            .store()
+            .append(&entry(now - Duration::days(10), 45.0))
+            .unwrap();
+
+        History::new(&config).record(entry(now, 38.36)).unwrap();
+
+        let entries = config.store().read().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].usage.remaining_gb, 38.36);
+    }
+
+    #[test]
+    fn test_record_marks_cycle_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HistoryConfig {
+            enabled: true,
+            path: Some(dir.path().join("history.jsonl")),
+            retention_days: None,
+        };
+        let now = Utc::now();
+        config.store().append(&entry(now, 1.0)).unwrap();
+
+        // A new History picks up the last stored reading
+        let mut history = History::new(&config);
+        let events = history.record(entry(now, 51.0)).unwrap();
+        assert!(matches!(events[..], [HistoryEvent::CycleStarted { .. }]));
+        assert!(history.record(entry(now, 50.0)).unwrap().is_empty());
+
+        let entries = config.store().read().unwrap();
+        assert_eq!(entries[1].events, events);
+        assert!(entries[2].events.is_empty());
+    }
+
+    #[test]
+    fn test_recording_source_skips_failed_fetches() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HistoryConfig {
+            enabled: true,
+            path: Some(dir.path().join("history.jsonl")),
+            retention_days: None,
+        };
+        let page = r#"
+            <html><head><title>Data usage - Prepaid XL</title></head><body>
+            <section class="data-pass-instance">
+                <div class="remaining-volume-value">38,36</div>
+                <div class="start-volume">51</div>
+            </section>
+            </body></html>
+        "#;
+        let mut source = RecordingSource::new(
+            crate::source::MemorySource::scripted([
+                Ok(page.to_string()),
+                Err(crate::error::DatapassError::DataNotFound(
+                    "offline".to_string(),
+                )),
+            ]),
+            History::new(&config),
+        );
+
+        assert!(source.fetch_usage().is_ok());
+        assert!(source.fetch_usage().is_err());
+
+        let entries = config.store().read().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].usage.remaining_gb, 38.36);
+        assert_eq!(entries[0].passes.len(), 1);
+    }
+}
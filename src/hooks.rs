@@ -0,0 +1,362 @@
+use crate::alert::Alert;
+use crate::cycle::{self, HistoryEvent};
+use crate::display;
+use crate::snapshot::SnapshotStore;
+use crate::types::DataUsage;
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+/// External commands run on events (`[hooks]` config section). Commands run
+/// through the shell with the reading as JSON on stdin and as `DATAPASS_*`
+/// environment variables.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// After every fresh reading
+    pub on_fetch: Option<String>,
+
+    /// For every alert that fires (see `[alerts]`)
+    pub on_alert: Option<String>,
+
+    /// When fetching fails
+    pub on_error: Option<String>,
+
+    /// When a reading shows that a new billing cycle began
+    pub on_cycle_start: Option<String>,
+
+    /// Kill a hook still running after this many seconds
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_timeout_seconds() -> u64 {
+    10
+}
+
+/// Event a hook runs for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Fetch,
+    Alert,
+    Error,
+    CycleStart,
+}
+
+impl HookEvent {
+    /// Name passed in `DATAPASS_EVENT`, e.g. "cycle_start"
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::Fetch => "fetch",
+            HookEvent::Alert => "alert",
+            HookEvent::Error => "error",
+            HookEvent::CycleStart => "cycle_start",
+        }
+    }
+}
+
+/// How a hook run ended
+#[derive(Debug)]
+pub enum HookOutcome {
+    Exited(ExitStatus),
+    TimedOut,
+}
+
+/// Runs the configured hooks
+pub struct Hooks {
+    config: HooksConfig,
+
+    /// Previous reading, to tell when a new cycle began
+    last: Option<DataUsage>,
+}
+
+impl Hooks {
+    /// Set up the hooks; the last stored reading (from an earlier run) is the
+    /// baseline for detecting a new cycle
+    pub fn new(config: HooksConfig) -> Self {
+        let last = config
+            .on_cycle_start
+            .as_ref()
+            .and_then(|_| SnapshotStore::new(SnapshotStore::default_path()).load())
+            .map(|snapshot| snapshot.usage);
+
+        Self { config, last }
+    }
+
+    /// Run `on_fetch` and, when the cycle changed, `on_cycle_start`
+    pub fn on_fetch(&mut self, data: &DataUsage) {
+        if let Some(command) = &self.config.on_fetch {
+            self.run(
+                command,
+                HookEvent::Fetch,
+                display::to_json(data),
+                usage_env(data),
+            );
+        }
+
+        let before = self.last.replace(data.clone());
+        if let (Some(command), Some(before)) = (&self.config.on_cycle_start, before) {
+            if let Some(HistoryEvent::CycleStarted { .. }) = cycle::detect(&before, data) {
+                let input = display::to_json(data);
+                self.run(command, HookEvent::CycleStart, input, usage_env(data));
+            }
+        }
+    }
+
+    /// Run `on_alert` for an alert, with `DATAPASS_ALERT_RULE`,
+    /// `DATAPASS_ALERT_MESSAGE` and `DATAPASS_ALERT_SEVERITY` set
+    pub fn on_alert(&self, data: &DataUsage, alert: &Alert) {
+        if let Some(command) = &self.config.on_alert {
+            let mut env = usage_env(data);
+            env.push(("DATAPASS_ALERT_RULE", alert.rule.clone()));
+            env.push(("DATAPASS_ALERT_MESSAGE", alert.message.clone()));
+            env.push(("DATAPASS_ALERT_SEVERITY", alert.severity.to_string()));
+            self.run(command, HookEvent::Alert, display::to_json(data), env);
+        }
+    }
+
+    /// Run `on_error` for a failed fetch, with `{"error": ...}` on stdin and
+    /// `DATAPASS_ERROR` set
+    pub fn on_error(&self, error: &str) {
+        if let Some(command) = &self.config.on_error {
+            let input = display::to_json(&serde_json::json!({ "error": error }));
+            let env = vec![("DATAPASS_ERROR", error.to_string())];
+            self.run(command, HookEvent::Error, input, env);
+        }
+    }
+
+    /// Run one hook, logging how it ended
+    fn run(
+        &self,
+        command: &str,
+        event: HookEvent,
+        input: serde_json::Result<String>,
+        env: Vec<(&'static str, String)>,
+    ) {
+        let input = match input {
+            Ok(input) => input,
+            Err(e) => {
+                log::error!("Hook {} input could not be serialized: {}", event.name(), e);
+                return;
+            }
+        };
+
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        match run_hook(command, event, &input, &env, timeout) {
+            Ok(HookOutcome::Exited(status)) if status.success() => {
+                log::debug!("Hook {} finished", event.name());
+            }
+            Ok(HookOutcome::Exited(status)) => {
+                log::warn!("Hook {} failed: {}", event.name(), status);
+            }
+            Ok(HookOutcome::TimedOut) => {
+                log::warn!(
+                    "Hook {} killed after {}s",
+                    event.name(),
+                    self.config.timeout_seconds
+                );
+            }
+            Err(e) => log::error!("Hook {} could not run: {}", event.name(), e),
+        }
+    }
+}
+
+/// Environment variables describing a reading
+pub fn usage_env(data: &DataUsage) -> Vec<(&'static str, String)> {
+    vec![
+        ("DATAPASS_REMAINING_GB", data.remaining_gb.to_string()),
+        ("DATAPASS_TOTAL_GB", data.total_gb.to_string()),
+        ("DATAPASS_USED_GB", data.used_gb.to_string()),
+        ("DATAPASS_PERCENTAGE", data.percentage.to_string()),
+        (
+            "DATAPASS_PLAN_NAME",
+            data.plan_name.clone().unwrap_or_default(),
+        ),
+        (
+            "DATAPASS_VALID_UNTIL",
+            data.valid_until.clone().unwrap_or_default(),
+        ),
+        ("DATAPASS_IS_UNLIMITED", data.is_unlimited.to_string()),
+    ]
+}
+
+/// Run a command through the shell with `input` on stdin and `env` (plus
+/// `DATAPASS_EVENT`) in its environment, killing it after `timeout`. Its
+/// stdout and stderr go to the log line by line.
+pub fn run_hook(
+    command: &str,
+    event: HookEvent,
+    input: &str,
+    env: &[(&str, String)],
+    timeout: Duration,
+) -> std::io::Result<HookOutcome> {
+    let mut shell = shell(command);
+    shell
+        .env("DATAPASS_EVENT", event.name())
+        .envs(env.iter().map(|(name, value)| (*name, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    log::info!("Running {} hook: {}", event.name(), command);
+    let mut child = shell.spawn()?;
+
+    // Feed stdin and drain the output on threads, so a hook that ignores its
+    // input or writes a lot cannot block us
+    let input = input.to_string();
+    let stdin = child.stdin.take().map(|mut stdin| {
+        std::thread::spawn(move || {
+            // A hook may exit without reading its input
+            let _ = stdin.write_all(input.as_bytes());
+        })
+    });
+    let name = event.name();
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| log_lines(out, move |line| log::info!("Hook {}: {}", name, line)));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| log_lines(err, move |line| log::warn!("Hook {}: {}", name, line)));
+
+    let outcome = wait(&mut child, timeout)?;
+
+    // After a timeout, processes started by the hook may still hold the
+    // pipes open; leave the threads to finish on their own then
+    if let HookOutcome::Exited(_) = outcome {
+        for thread in [stdin, stdout, stderr].into_iter().flatten() {
+            let _ = thread.join();
+        }
+    }
+
+    Ok(outcome)
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+fn log_lines<R: Read + Send + 'static>(
+    reader: R,
+    log_line: impl Fn(&str) + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(|line| line.ok()) {
+            log_line(&line);
+        }
+    })
+}
+
+/// Wait for the child to exit, killing it after `timeout`
+fn wait(child: &mut Child, timeout: Duration) -> std::io::Result<HookOutcome> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(HookOutcome::Exited(status));
+        }
+
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(HookOutcome::TimedOut);
+        }
+
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_gets_json_and_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let command = format!(
+            "cat > '{0}.json'; echo \"$DATAPASS_EVENT $DATAPASS_REMAINING_GB $DATAPASS_ALERT_RULE\" > '{0}.env'",
+            out.display()
+        );
+        let data = DataUsage::new(38.36, 51.0, Some("Prepaid XL".to_string()), None);
+        let mut env = usage_env(&data);
+        env.push(("DATAPASS_ALERT_RULE", "usage_percent:80".to_string()));
+
+        let outcome = run_hook(
+            &command,
+            HookEvent::Alert,
+            &display::to_json(&data).unwrap(),
+            &env,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert!(matches!(outcome, HookOutcome::Exited(status) if status.success()));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(out.with_extension("json")).unwrap())
+                .unwrap();
+        assert_eq!(json["remaining_gb"], 38.36);
+        assert_eq!(json["plan_name"], "Prepaid XL");
+        assert_eq!(
+            std::fs::read_to_string(out.with_extension("env")).unwrap(),
+            "alert 38.36 usage_percent:80\n"
+        );
+    }
+
+    #[test]
+    fn test_hook_is_killed_after_timeout() {
+        let start = Instant::now();
+        let outcome = run_hook(
+            "sleep 10",
+            HookEvent::Fetch,
+            "{}",
+            &[],
+            Duration::from_millis(200),
+        )
+        .unwrap();
+
+        assert!(matches!(outcome, HookOutcome::TimedOut));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_cycle_start_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("cycle");
+        let mut hooks = Hooks {
+            config: HooksConfig {
+                on_fetch: None,
+                on_alert: None,
+                on_error: None,
+                on_cycle_start: Some(format!(
+                    "echo \"$DATAPASS_TOTAL_GB\" >> '{}'",
+                    marker.display()
+                )),
+                timeout_seconds: 5,
+            },
+            last: None,
+        };
+        let usage = |remaining_gb, valid_until: &str| {
+            DataUsage::new(remaining_gb, 51.0, None, Some(valid_until.to_string()))
+        };
+
+        hooks.on_fetch(&usage(2.0, "1. November 2026"));
+        hooks.on_fetch(&usage(1.0, "1. November 2026"));
+        assert!(!marker.exists());
+
+        hooks.on_fetch(&usage(51.0, "1. December 2026"));
+        assert_eq!(std::fs::read_to_string(&marker).unwrap(), "51\n");
+    }
+}
@@ -0,0 +1,207 @@
+use crate::error::{DatapassError, Result};
+use crate::export::{self, ExportRow};
+use crate::history::HistoryEntry;
+use crate::types::DataUsage;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// File extensions picked up when importing a directory
+const EXTENSIONS: &[&str] = &["json", "jsonl", "ndjson", "csv"];
+
+/// Read the readings in a file, or in the files of a directory and its
+/// subdirectories (in name order, only `.json`, `.jsonl`, `.ndjson` and
+/// `.csv` files)
+pub fn read_path(path: &Path) -> Result<Vec<HistoryEntry>> {
+    if !path.is_dir() {
+        return read_file(path);
+    }
+
+    let mut entries = Vec::new();
+    for file in files_in(path)? {
+        entries.extend(read_file(&file)?);
+    }
+    Ok(entries)
+}
+
+/// Read the readings in one file. Accepted are:
+///
+/// - CSV with a header line, as written by `datapass export`
+/// - JSON Lines, as written by `datapass export --format jsonl` or found in
+///   the history store
+/// - JSON: one `datapass --format json` output, or an array of readings
+///
+/// Readings without a timestamp (plain `--format json` output) are dated by
+/// the file's modification time, or by when a stale reading was fetched.
+pub fn read_file(path: &Path) -> Result<Vec<HistoryEntry>> {
+    read_file_inner(path).map_err(|e| {
+        let message = match e {
+            DatapassError::ImportError(message) => message,
+            other => other.to_string(),
+        };
+        DatapassError::ImportError(format!("{}: {}", path.display(), message))
+    })
+}
+
+fn read_file_inner(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let text = std::fs::read_to_string(path)?;
+    let is_csv = match extension(path).as_deref() {
+        Some("csv") => true,
+        Some("json" | "jsonl" | "ndjson") => false,
+        _ => !text.trim_start().starts_with(['{', '[']),
+    };
+
+    if is_csv {
+        return Ok(export::read_csv(&text)?
+            .into_iter()
+            .map(ExportRow::into_entry)
+            .collect());
+    }
+
+    let modified: DateTime<Utc> = std::fs::metadata(path)?.modified()?.into();
+
+    // A whole JSON document, or else one per line
+    let records = match serde_json::from_str(&text) {
+        Ok(Value::Array(records)) => records,
+        Ok(record) => vec![record],
+        Err(_) => text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| DatapassError::ImportError(format!("line {}: {}", index + 1, e)))
+            })
+            .collect::<Result<_>>()?,
+    };
+
+    records
+        .into_iter()
+        .map(|record| entry_from_json(record, modified))
+        .collect()
+}
+
+/// A reading from a history line, an exported row or a `--format json` output
+fn entry_from_json(record: Value, modified: DateTime<Utc>) -> Result<HistoryEntry> {
+    let mut entry = if record.get("timestamp").is_some() {
+        match serde_json::from_value::<HistoryEntry>(record.clone()) {
+            Ok(entry) => entry,
+            Err(_) => serde_json::from_value::<ExportRow>(record)
+                .map_err(|e| DatapassError::ImportError(e.to_string()))?
+                .into_entry(),
+        }
+    } else {
+        let usage: DataUsage = serde_json::from_value(record)
+            .map_err(|e| DatapassError::ImportError(e.to_string()))?;
+        HistoryEntry {
+            timestamp: usage
+                .stale
+                .as_ref()
+                .map_or(modified, |stale| stale.fetched_at),
+            usage,
+            passes: Vec::new(),
+            events: Vec::new(),
+        }
+    };
+
+    // Stored readings are fresh ones without a forecast; events are
+    // detected again when merging
+    entry.usage.stale = None;
+    entry.usage.forecast = None;
+    entry.events.clear();
+
+    Ok(entry)
+}
+
+/// Importable files under `dir`, in name order
+fn files_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for item in std::fs::read_dir(&dir)? {
+            let path = item?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if extension(&path).is_some_and(|ext| EXTENSIONS.contains(&ext.as_str())) {
+                files.push(path);
+            } else {
+                log::debug!("Skipping {}", path.display());
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_json_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = r#"{
+            "remaining_gb": 38.36, "total_gb": 51.0, "used_gb": 12.64,
+            "percentage": 24.78, "plan_name": "Prepaid XL", "is_unlimited": false
+        }"#;
+        std::fs::write(dir.path().join("2026-10-01.json"), output).unwrap();
+
+        // A stale output is dated by when it was actually fetched
+        let stale = r#"{
+            "remaining_gb": 30.0, "total_gb": 51.0, "used_gb": 21.0,
+            "percentage": 41.18, "is_unlimited": false,
+            "stale": { "fetched_at": "2026-10-02T06:00:00Z", "age_seconds": 7200 }
+        }"#;
+        std::fs::create_dir(dir.path().join("older")).unwrap();
+        std::fs::write(dir.path().join("older").join("stale.json"), stale).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a reading").unwrap();
+
+        let entries = read_path(dir.path()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].usage.remaining_gb, 38.36);
+        assert!(Utc::now() - entries[0].timestamp < chrono::Duration::minutes(5));
+        assert_eq!(
+            entries[1].timestamp.to_rfc3339(),
+            "2026-10-02T06:00:00+00:00"
+        );
+        assert!(entries[1].usage.stale.is_none());
+    }
+
+    #[test]
+    fn test_read_history_and_export_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("readings.jsonl");
+        let lines = [
+            r#"{"timestamp":"2026-10-01T08:00:00Z","remaining_gb":40.0,"total_gb":51.0,"used_gb":11.0,"percentage":21.57,"is_unlimited":false,"events":[{"type":"PassBooked","added_gb":5.0}]}"#,
+            r#"{"timestamp":"2026-10-01T09:00:00Z","remaining_gb":39.0,"total_gb":51.0,"used_gb":12.0,"percentage":23.53,"plan_name":null,"valid_until":null,"is_unlimited":false,"pass_name":"Prepaid XL","stale":false}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let entries = read_path(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].events.is_empty());
+        assert_eq!(entries[1].usage.remaining_gb, 39.0);
+    }
+
+    #[test]
+    fn test_errors_name_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.csv");
+        std::fs::write(&path, "timestamp,remaining_gb\nyesterday,3\n").unwrap();
+
+        let error = read_path(dir.path()).unwrap_err().to_string();
+        assert!(
+            error.contains("broken.csv: row 2: timestamp 'yesterday'"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::types::{DataUsage, BYTES_PER_GB};
+use chrono::{DateTime, Utc};
+
+/// Measurement the readings are written to
+const MEASUREMENT: &str = "datapass";
+
+/// A reading as one line of InfluxDB line protocol, timestamped in
+/// nanoseconds with the time it was fetched (`now` for fresh readings)
+pub fn line(data: &DataUsage, now: DateTime<Utc>) -> String {
+    let mut line = MEASUREMENT.to_string();
+    for (name, value) in [
+        ("plan", &data.plan_name),
+        ("pass", &data.pass_name),
+        ("zone", &data.zone),
+    ] {
+        if let Some(value) = value.as_deref().filter(|value| !value.is_empty()) {
+            line.push_str(&format!(",{}={}", name, escape_tag(value)));
+        }
+    }
+
+    let mut fields = Vec::new();
+    if !data.is_unlimited {
+        fields.push(format!(
+            "remaining_bytes={}i",
+            (data.remaining_gb * BYTES_PER_GB).round() as i64
+        ));
+        fields.push(format!(
+            "used_bytes={}i",
+            (data.used_gb * BYTES_PER_GB).round() as i64
+        ));
+        fields.push(format!(
+            "total_bytes={}i",
+            (data.total_gb * BYTES_PER_GB).round() as i64
+        ));
+        fields.push(format!("used_ratio={}", data.percentage / 100.0));
+    }
+    fields.push(format!("unlimited={}", data.is_unlimited));
+    fields.push(format!("stale={}", data.is_stale()));
+    if let Some(valid_until) = data.valid_until_end() {
+        fields.push(format!("valid_until={}i", valid_until.timestamp()));
+    }
+
+    let time = data.stale.as_ref().map_or(now, |stale| stale.fetched_at);
+    format!(
+        "{} {} {}",
+        line,
+        fields.join(","),
+        time.timestamp() * 1_000_000_000 + i64::from(time.timestamp_subsec_nanos())
+    )
+}
+
+/// Escape a tag value: commas, equals signs and spaces
+fn escape_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ',' | '=' | ' ' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            // Line breaks would end the line
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        let data = DataUsage {
+            pass_name: Some("MagentaMobil Prepaid XL".to_string()),
+            zone: Some("Germany, Country group 1".to_string()),
+            ..DataUsage::new(
+                38.36,
+                51.0,
+                Some("MagentaMobil Prepaid XL".to_string()),
+                None,
+            )
+        };
+        let now = "2026-10-18T08:00:00.5Z".parse().unwrap();
+
+        assert_eq!(
+            line(&data, now),
+            "datapass,plan=MagentaMobil\\ Prepaid\\ XL,pass=MagentaMobil\\ Prepaid\\ XL,\
+             zone=Germany\\,\\ Country\\ group\\ 1 \
+             remaining_bytes=38360000000i,used_bytes=12640000000i,total_bytes=51000000000i,\
+             used_ratio=0.24784313725490198,unlimited=false,stale=false \
+             1792310400500000000"
+        );
+    }
+
+    #[test]
+    fn test_unlimited_line_has_no_volume() {
+        let data = DataUsage::new_unlimited(None, None);
+        let now = "2026-10-18T08:00:00Z".parse().unwrap();
+
+        assert_eq!(
+            line(&data, now),
+            "datapass unlimited=true,stale=false 1792310400000000000"
+        );
+    }
+}
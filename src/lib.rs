@@ -1,26 +1,93 @@
 // Library interface for testing
 
+#[cfg(feature = "cli")]
+pub mod alert;
+#[cfg(feature = "http")]
+pub mod blocking;
+#[cfg(feature = "http")]
+pub mod booking;
+#[cfg(feature = "http")]
+pub mod cache;
+#[cfg(feature = "cli")]
+pub mod check;
+#[cfg(feature = "cli")]
 pub mod cli;
+#[cfg(feature = "http")]
+pub mod client;
+#[cfg(feature = "cli")]
+pub mod config;
+#[cfg(feature = "cli")]
+pub mod csv;
+#[cfg(feature = "cli")]
+pub mod cycle;
+#[cfg(feature = "desktop")]
+pub mod desktop;
+#[cfg(feature = "cli")]
 pub mod display;
+#[cfg(feature = "email")]
+pub mod email;
 pub mod error;
+#[cfg(feature = "cli")]
+pub mod export;
+#[cfg(feature = "http")]
 pub mod fetcher;
+#[cfg(feature = "cli")]
+pub mod forecast;
+#[cfg(feature = "cli")]
+pub mod history;
+#[cfg(feature = "cli")]
+pub mod hooks;
+#[cfg(feature = "cli")]
+pub mod import;
+#[cfg(feature = "cli")]
+pub mod influx;
+#[cfg(feature = "cli")]
+pub mod metrics;
+#[cfg(feature = "cli")]
+pub mod monitor;
+#[cfg(feature = "cli")]
+pub mod mqtt;
+#[cfg(feature = "cli")]
+pub mod notify;
 pub mod parser;
+#[cfg(feature = "cli")]
+pub mod report;
+#[cfg(feature = "cli")]
+pub mod snapshot;
+pub mod source;
+#[cfg(feature = "cli")]
+pub mod statsd;
+#[cfg(feature = "cli")]
+pub mod topup;
+#[cfg(feature = "tui")]
 pub mod tui;
 pub mod types;
+#[cfg(feature = "cli")]
+pub mod webhook;
 
+#[cfg(feature = "async")]
+pub use client::DatapassClient;
+#[cfg(feature = "http")]
+pub use client::{ClientBuilder, Locale, Provider};
 pub use error::{DatapassError, Result};
-pub use types::DataUsage;
+#[cfg(feature = "http")]
+pub use source::HttpSource;
+pub use source::{FileSource, MemorySource, Source, StdinSource};
+pub use types::{
+    DataReport, DataUsage, FetchMetadata, Forecast, ForecastMethod, PassOffer, PassUsage,
+    Staleness, TariffTransparency,
+};
 
 /// Main entry point for library usage
+#[cfg(feature = "http")]
 pub fn get_data_usage(url: Option<&str>) -> Result<DataUsage> {
-    let html = fetcher::fetch_html(url, None)?;
-    parser::parse_html(&html)
+    blocking_client(url, None)?.fetch()
 }
 
 /// Get data usage with cookie authentication
+#[cfg(feature = "http")]
 pub fn get_data_usage_with_cookie(url: Option<&str>, cookie: &str) -> Result<DataUsage> {
-    let html = fetcher::fetch_html(url, Some(cookie))?;
-    parser::parse_html(&html)
+    blocking_client(url, Some(cookie))?.fetch()
 }
 
 /// Parse data usage from HTML string
@@ -30,6 +97,39 @@ pub fn parse_data_usage(html: &str) -> Result<DataUsage> {
 
 /// Read and parse data usage from local file
 pub fn get_data_usage_from_file(file_path: &str) -> Result<DataUsage> {
-    let html = fetcher::read_local_file(file_path)?;
-    parser::parse_html(&html)
+    FileSource::new(file_path).fetch_usage()
+}
+
+/// Fetch and parse data usage from any page source
+pub fn get_data_usage_from(source: &mut dyn Source) -> Result<DataUsage> {
+    source.fetch_usage()
+}
+
+/// Fetch and parse tariff transparency information (advertised speeds and pricing)
+#[cfg(feature = "http")]
+pub fn get_tariff_transparency(url: Option<&str>) -> Result<TariffTransparency> {
+    blocking_client(url, None)?.fetch_tariff()
+}
+
+/// Parse tariff transparency information from HTML string
+pub fn parse_tariff_transparency(html: &str) -> Result<TariffTransparency> {
+    parser::parse_tariff_transparency(html)
+}
+
+/// Parse the data pass offers from HTML string
+pub fn parse_offers(html: &str) -> Result<Vec<PassOffer>> {
+    parser::parse_offers(html)
+}
+
+/// One-off blocking client for the free functions
+#[cfg(feature = "http")]
+fn blocking_client(url: Option<&str>, cookie: Option<&str>) -> Result<blocking::DatapassClient> {
+    let mut builder = ClientBuilder::new();
+    if let Some(url) = url {
+        builder = builder.url(url);
+    }
+    if let Some(cookie) = cookie {
+        builder = builder.cookie(cookie);
+    }
+    builder.build_blocking()
 }
@@ -1,17 +1,14 @@
-mod cli;
-mod display;
-mod error;
-mod fetcher;
-mod parser;
-mod tui;
-mod types;
-
 use clap::{CommandFactory, Parser};
 use clap_complete::{generate, Shell as ClapShell};
 use clap_mangen::Man;
-use cli::{Cli, Shell};
+use cli::{Cli, Command, HistoryCommand, NotifyCommand, Shell};
+use datapass::{
+    alert, booking, cache, check, cli, config, cycle, display, error, export, fetcher, forecast,
+    history, import, metrics, monitor, notify, parser, report, snapshot, source, tui, types,
+};
 use error::Result;
-use std::io;
+use source::Source;
+use std::io::{self, BufRead, IsTerminal, Write};
 
 fn main() {
     if let Err(e) = run() {
@@ -21,7 +18,15 @@ fn main() {
 }
 
 fn run() -> Result<()> {
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Monitoring plugins report usage errors as UNKNOWN
+        Err(e) if e.use_stderr() && std::env::args().any(|arg| arg == "check") => {
+            let _ = e.print();
+            std::process::exit(check::CheckState::Unknown.exit_code());
+        }
+        Err(e) => e.exit(),
+    };
 
     // Handle shell completions generation
     if let Some(shell) = cli.generate_completions {
@@ -48,6 +53,10 @@ fn run() -> Result<()> {
 
     // Validate CLI arguments
     if let Err(e) = cli.validate() {
+        if matches!(cli.command, Some(Command::Check { .. })) {
+            println!("{}", check::CheckResult::unknown(&e).line);
+            std::process::exit(check::CheckState::Unknown.exit_code());
+        }
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
@@ -55,6 +64,46 @@ fn run() -> Result<()> {
     // Setup logging
     setup_logging(&cli)?;
 
+    // Subcommands
+    match &cli.command {
+        Some(Command::Tariff) => return run_tariff(&cli),
+        Some(Command::Offers) => return run_offers(&cli),
+        Some(Command::Book {
+            offer,
+            yes,
+            dry_run,
+        }) => return run_book(&cli, offer, *yes, *dry_run),
+        Some(Command::Daemon { interval }) => return run_daemon_mode(*interval, &cli),
+        Some(Command::Report {
+            by,
+            since,
+            until,
+            cycle,
+        }) => return run_report(&cli, *by, *since, *until, *cycle),
+        Some(Command::Cycles) => return run_cycles(&cli),
+        Some(Command::History { command }) => return run_history(&cli, command),
+        Some(Command::Export { since, until }) => return run_export(&cli, *since, *until),
+        Some(Command::Import { paths }) => return run_import(&cli, paths),
+        Some(Command::Check {
+            warning,
+            critical,
+            min_days,
+        }) => {
+            let options = check::CheckOptions {
+                warning: Some(*warning),
+                critical: Some(*critical),
+                min_days: *min_days,
+            };
+            run_check(&cli, &options)
+        }
+        Some(Command::Serve {
+            metrics,
+            min_interval,
+        }) => return run_serve(&cli, metrics, *min_interval),
+        Some(Command::Notify { command }) => return run_notify(&cli, command),
+        None => {}
+    }
+
     // Watch mode (TUI)
     if let Some(interval) = cli.watch {
         run_watch_mode(interval, &cli)?;
@@ -62,40 +111,455 @@ fn run() -> Result<()> {
     }
 
     // Single fetch mode
-    let html = if let Some(file_path) = &cli.file {
-        fetcher::read_local_file(file_path)?
-    } else {
-        fetcher::fetch_html(cli.url.as_deref(), cli.cookie.as_deref())?
+    let config = config::Config::load(cli.config.as_deref())?;
+    let mut monitor = monitor::Monitor::single_run(&config);
+    let data = FetchOptions::from_cli(&cli, &config)
+        .fetch_usage()
+        .map_err(|e| {
+            monitor.on_error(&e);
+            e
+        })?;
+    let alerts = monitor.on_fetch(&data);
+    let output_format = cli.get_output_format();
+    match &cli.output {
+        Some(path) => write_output(path, &display::usage_text(&data, output_format, false))?,
+        None => display::display(&data, output_format, cli.color),
+    }
+    display::display_alerts(&alerts, cli.color);
+
+    Ok(())
+}
+
+/// Replace `path` with `text` through a temporary file in the same
+/// directory, so readers never see a partial file
+fn write_output(path: &str, text: &str) -> Result<()> {
+    let path = std::path::Path::new(path);
+    let temp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    std::fs::write(&temp_path, text)?;
+    if let Err(e) = std::fs::rename(&temp_path, path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+fn run_tariff(cli: &Cli) -> Result<()> {
+    let url = match &cli.file {
+        Some(_) => None,
+        None => Some(fetcher::tariff_transparency_url(cli.url.as_deref())?),
     };
+    let html = open_source(cli, url.as_deref()).fetch()?;
 
-    let data = parser::parse_html(&html)?;
-    let output_format = cli.get_output_format();
-    display::display(&data, output_format, cli.color);
+    let tariff = parser::parse_tariff_transparency(&html)?;
+    display::display_tariff(&tariff, cli.get_output_format(), cli.color);
+
+    Ok(())
+}
+
+fn run_offers(cli: &Cli) -> Result<()> {
+    let html = open_source(cli, cli.url.as_deref()).fetch()?;
+
+    let offers = parser::parse_offers(&html)?;
+    display::display_offers(&offers, cli.get_output_format(), cli.color);
+
+    Ok(())
+}
+
+fn run_book(cli: &Cli, offer_id: &str, yes: bool, dry_run: bool) -> Result<()> {
+    let mut session = booking::BookingSession::new(cli.url.as_deref(), cli.cookie.as_deref())?;
+    let offer = session.find_offer(offer_id)?;
+
+    // A dry run never submits anything, so it needs no confirmation
+    if !yes && !dry_run {
+        display::display_offer_summary(&offer, cli.color);
+
+        if !confirm("Book this data pass?")? {
+            return Err(error::DatapassError::BookingError(
+                "cancelled by user".to_string(),
+            ));
+        }
+    }
+
+    let result = session.book(&offer, dry_run)?;
+    display::display_booking(&result, cli.get_output_format(), cli.color);
+
+    if result.status == booking::BookingStatus::Rejected {
+        return Err(error::DatapassError::BookingError(
+            result
+                .message
+                .unwrap_or_else(|| "The booking was rejected".to_string()),
+        ));
+    }
+
+    Ok(())
+}
+
+fn run_report(
+    cli: &Cli,
+    by: report::Bucket,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+    cycle: Option<cycle::CycleRef>,
+) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let entries = config.history_store().read()?;
+
+    let periods = match cycle {
+        Some(selected) => {
+            let cycles = cycle::cycles(&entries);
+            let cycle = selected.find(&cycles).ok_or_else(|| {
+                error::DatapassError::DataNotFound(format!(
+                    "No cycle '{}' in the history",
+                    selected
+                ))
+            })?;
+            report::cycle_usage_by(&entries, cycle, by)
+        }
+        None => report::usage_by(&entries, by, since, until),
+    };
+    display::display_report(&periods, cli.get_output_format(), cli.color);
+
+    Ok(())
+}
+
+fn run_cycles(cli: &Cli) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let entries = config.history_store().read()?;
+
+    display::display_cycles(&cycle::cycles(&entries), cli.get_output_format(), cli.color);
+
+    Ok(())
+}
+
+fn run_history(cli: &Cli, command: &HistoryCommand) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let store = config.history_store();
+
+    match command {
+        HistoryCommand::List {
+            since,
+            until,
+            limit,
+        } => {
+            let mut entries = store.range(*since, *until)?;
+            if let Some(limit) = limit {
+                entries.drain(..entries.len().saturating_sub(*limit));
+            }
+            display::display_history(&entries, cli.get_output_format(), cli.color);
+        }
+        HistoryCommand::Prune { older_than } => {
+            let retention = match older_than {
+                Some(age) => chrono::Duration::from_std(*age)
+                    .map_err(|e| error::DatapassError::ConfigError(e.to_string()))?,
+                None => config
+                    .history
+                    .as_ref()
+                    .and_then(|history| history.retention())
+                    .ok_or_else(|| {
+                        error::DatapassError::ConfigError(
+                            "No retention configured; use --older-than or set history.retention_days"
+                                .to_string(),
+                        )
+                    })?,
+            };
+
+            let cutoff = chrono::Utc::now() - retention;
+            let removed = store.prune(cutoff)?;
+            println!(
+                "Removed {} readings recorded before {}",
+                removed,
+                cutoff
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn run_export(
+    cli: &Cli,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let entries = config.history_store().range(since, until)?;
+    let mut out = io::stdout().lock();
+
+    match cli.format {
+        cli::Format::Human | cli::Format::Csv => export::write_csv(&entries, &mut out)?,
+        cli::Format::Jsonl => export::write_jsonl(&entries, &mut out)?,
+        cli::Format::Json => export::write_json(&entries, &mut out)?,
+        // Rejected by `Cli::validate` already
+        cli::Format::Prometheus | cli::Format::Influx => {
+            return Err(error::DatapassError::ConfigError(
+                "datapass export writes csv, jsonl or json".to_string(),
+            ));
+        }
+    }
 
     Ok(())
 }
 
+fn run_import(cli: &Cli, paths: &[std::path::PathBuf]) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let store = config.history_store();
+
+    let mut entries = Vec::new();
+    for path in paths {
+        entries.extend(import::read_path(path)?);
+    }
+
+    let found = entries.len();
+    let added = store.import(entries)?;
+    println!(
+        "Imported {} of {} readings into {} ({} already recorded)",
+        added,
+        found,
+        store.path().display(),
+        found - added
+    );
+
+    Ok(())
+}
+
+fn run_notify(cli: &Cli, command: &NotifyCommand) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+
+    match command {
+        NotifyCommand::Test => {
+            let mut notifiers = notify::notifiers(&config);
+            if notifiers.is_empty() {
+                return Err(error::DatapassError::ConfigError(
+                    "No notifiers configured; add a [notify.*] section".to_string(),
+                ));
+            }
+
+            // The last reading if there is one, so templates show real values
+            let data = snapshot::SnapshotStore::new(snapshot::SnapshotStore::default_path())
+                .load()
+                .map(|snapshot| snapshot.usage)
+                .unwrap_or_else(|| {
+                    types::DataUsage::new(38.36, 51.0, Some("Test Plan".to_string()), None)
+                });
+            let alert = alert::Alert::test();
+
+            let mut failed = 0;
+            for notifier in &mut notifiers {
+                match notifier.notify(&alert, &data) {
+                    Ok(()) => println!("{}: sent", notifier.name()),
+                    Err(e) => {
+                        eprintln!("{}: {}", notifier.name(), e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            if failed > 0 {
+                return Err(error::DatapassError::NotifyError(format!(
+                    "{} of {} notifiers failed",
+                    failed,
+                    notifiers.len()
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Ask a yes/no question on the terminal; refuses when stdin is not interactive
+fn confirm(question: &str) -> Result<bool> {
+    let stdin = io::stdin();
+
+    if !stdin.is_terminal() {
+        return Err(error::DatapassError::BookingError(
+            "Confirmation required; use --yes to book non-interactively".to_string(),
+        ));
+    }
+
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
 fn run_watch_mode(interval: u64, cli: &Cli) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let mut monitor = monitor::Monitor::new(
+        &config,
+        cli.url.as_deref(),
+        cli.cookie.as_deref(),
+        cli.file.is_none(),
+    );
     let mut app = tui::TuiApp::new(interval);
 
     // Create a closure that captures the CLI config
-    let url = cli.url.clone();
-    let file = cli.file.clone();
-    let cookie = cli.cookie.clone();
+    let mut options = FetchOptions::from_cli(cli, &config);
 
     let fetch_fn = move || -> Result<types::DataUsage> {
-        let html = if let Some(ref file_path) = file {
-            fetcher::read_local_file(file_path)?
-        } else {
-            fetcher::fetch_html(url.as_deref(), cookie.as_deref())?
-        };
-        parser::parse_html(&html)
+        let data = options.fetch_usage().map_err(|e| {
+            monitor.on_error(&e);
+            e
+        })?;
+        // Alerts are logged; printing them would garble the screen
+        monitor.on_fetch(&data);
+        Ok(data)
     };
 
     app.run(fetch_fn)?;
     Ok(())
 }
 
+fn run_daemon_mode(interval: u64, cli: &Cli) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let mut monitor = monitor::Monitor::new(
+        &config,
+        cli.url.as_deref(),
+        cli.cookie.as_deref(),
+        cli.file.is_none(),
+    );
+
+    let mut options = FetchOptions::from_cli(cli, &config);
+
+    log::info!("Daemon started, fetching every {}s", interval);
+
+    loop {
+        match options.fetch_usage() {
+            Ok(data) => {
+                println!("{}", display::status_line(&data));
+                let alerts = monitor.on_fetch(&data);
+                display::display_alerts(&alerts, cli.color);
+            }
+            Err(e) => {
+                log::error!("Fetch failed: {}", e);
+                eprintln!("Error: {}", e);
+                monitor.on_error(&e);
+            }
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(interval));
+    }
+}
+
+/// Print the check's status line and exit with its state
+fn run_check(cli: &Cli, options: &check::CheckOptions) -> ! {
+    let result = config::Config::load(cli.config.as_deref())
+        .and_then(|config| FetchOptions::from_cli(cli, &config).fetch_usage())
+        .map(|data| check::check(&data, options, chrono::Local::now().date_naive()))
+        .unwrap_or_else(|e| check::CheckResult::unknown(&e));
+
+    println!("{}", result.line);
+    std::process::exit(result.state.exit_code());
+}
+
+fn run_serve(cli: &Cli, address: &str, min_interval: std::time::Duration) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let mut monitor = monitor::Monitor::new(
+        &config,
+        cli.url.as_deref(),
+        cli.cookie.as_deref(),
+        cli.file.is_none(),
+    );
+
+    let mut options = FetchOptions::from_cli(cli, &config);
+    let listener = metrics::listen(address)?;
+    println!(
+        "Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+
+    let fetch = move || -> Result<types::DataUsage> {
+        let data = options.fetch_usage().map_err(|e| {
+            monitor.on_error(&e);
+            e
+        })?;
+        monitor.on_fetch(&data);
+        Ok(data)
+    };
+
+    metrics::Exporter::new(fetch, min_interval).serve(&listener)
+}
+
+/// Where and how to get the datapass page
+struct FetchOptions {
+    source: Box<dyn Source>,
+    snapshots: Option<snapshot::SnapshotStore>,
+    allow_stale: Option<std::time::Duration>,
+    forecaster: Option<forecast::Forecaster>,
+}
+
+impl FetchOptions {
+    fn from_cli(cli: &Cli, config: &config::Config) -> Self {
+        let mut source = open_source(cli, cli.url.as_deref());
+        let mut forecaster = None;
+
+        // Only network readings are worth recording
+        if let Some(settings) = &config.history {
+            if settings.enabled && cli.file.is_none() {
+                let history = history::History::new(settings);
+                source = Box::new(history::RecordingSource::new(source, history));
+
+                let forecast = config.forecast.clone().unwrap_or_default();
+                if forecast.enabled {
+                    forecaster = Some(forecast::Forecaster::new(settings.store(), forecast));
+                }
+            }
+        }
+
+        Self {
+            source,
+            forecaster,
+            // Only network readings are worth falling back to
+            snapshots: cli
+                .file
+                .is_none()
+                .then(|| snapshot::SnapshotStore::new(snapshot::SnapshotStore::default_path())),
+            allow_stale: cli.allow_stale,
+        }
+    }
+
+    /// Fetch (or read) the page and parse the data usage
+    fn fetch_usage(&mut self) -> Result<types::DataUsage> {
+        let mut data = match &self.snapshots {
+            Some(store) => store.fetch_usage(&mut self.source, self.allow_stale)?,
+            None => self.source.fetch_usage()?,
+        };
+
+        if let Some(forecaster) = &self.forecaster {
+            match forecaster.forecast(&data, chrono::Utc::now()) {
+                Ok(forecast) => data.forecast = forecast,
+                Err(e) => log::warn!("Failed to forecast from the history: {}", e),
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// The page source selected on the command line: `--file` (`-` for stdin),
+/// or the given URL over HTTP
+fn open_source(cli: &Cli, url: Option<&str>) -> Box<dyn Source> {
+    match cli.file.as_deref() {
+        Some("-") => Box::new(source::StdinSource::new()),
+        Some(path) => Box::new(source::FileSource::new(path)),
+        None => {
+            let http = source::HttpSource::new(url, cli.cookie.as_deref());
+            if cli.no_cache {
+                Box::new(http)
+            } else {
+                let cache = cache::ResponseCache::new(cache::ResponseCache::default_dir());
+                Box::new(http.with_cache(cache, cli.max_age))
+            }
+        }
+    }
+}
+
 fn setup_logging(cli: &Cli) -> Result<()> {
     let log_level = if cli.verbose {
         log::LevelFilter::Debug
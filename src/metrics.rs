@@ -0,0 +1,411 @@
+use crate::error::{DatapassError, Result};
+use crate::types::{DataUsage, BYTES_PER_GB};
+use chrono::{DateTime, Utc};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+/// Give up on a client that does not send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Content type of the Prometheus text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Fetch outcomes reported along with the reading
+#[derive(Debug, Clone, Default)]
+pub struct FetchStats {
+    /// Failed fetches, including ones answered from a stored reading
+    pub errors: u64,
+
+    /// When the last fresh reading was fetched
+    pub last_success: Option<DateTime<Utc>>,
+}
+
+impl FetchStats {
+    /// The outcome of a single fetch that returned `data`: a stored reading
+    /// stands in for a failed fetch
+    pub fn of_reading(data: &DataUsage, now: DateTime<Utc>) -> Self {
+        match &data.stale {
+            Some(stale) => Self {
+                errors: 1,
+                last_success: Some(stale.fetched_at),
+            },
+            None => Self {
+                errors: 0,
+                last_success: Some(now),
+            },
+        }
+    }
+}
+
+/// Render a reading (if there is one yet) and the fetch counters in the
+/// Prometheus text format 0.0.4. Both the exporter and node_exporter's
+/// textfile collector read this format; OpenMetrics, which declares counters
+/// without their `_total` suffix, would lose the counter in the latter.
+pub fn render(data: Option<&DataUsage>, stats: &FetchStats) -> String {
+    let mut out = Writer {
+        text: String::new(),
+    };
+
+    if let Some(data) = data {
+        if !data.is_unlimited {
+            out.gauge(
+                "datapass_remaining_bytes",
+                "Data volume left in the current pass",
+                "",
+                data.remaining_gb * BYTES_PER_GB,
+            );
+            out.gauge(
+                "datapass_used_bytes",
+                "Data volume used in the current pass",
+                "",
+                data.used_gb * BYTES_PER_GB,
+            );
+            out.gauge(
+                "datapass_total_bytes",
+                "Data volume of the current pass",
+                "",
+                data.total_gb * BYTES_PER_GB,
+            );
+            out.gauge(
+                "datapass_used_ratio",
+                "Share of the data volume used, from 0 to 1",
+                "",
+                data.percentage / 100.0,
+            );
+        }
+        out.gauge(
+            "datapass_unlimited",
+            "Whether the plan has unlimited data (1) or not (0)",
+            "",
+            if data.is_unlimited { 1.0 } else { 0.0 },
+        );
+        if let Some(valid_until) = data.valid_until_end() {
+            out.gauge(
+                "datapass_valid_until_timestamp_seconds",
+                "When the current pass ends, in seconds since the epoch",
+                "",
+                valid_until.timestamp() as f64,
+            );
+        }
+        let mut labels = format!(
+            "plan=\"{}\"",
+            escape_label(data.plan_name.as_deref().unwrap_or(""))
+        );
+        for (name, value) in [("pass", &data.pass_name), ("zone", &data.zone)] {
+            if let Some(value) = value {
+                let _ = write!(labels, ",{}=\"{}\"", name, escape_label(value));
+            }
+        }
+        out.gauge(
+            "datapass_pass_info",
+            "The current plan, and the active pass and its zone; always 1",
+            &labels,
+            1.0,
+        );
+    }
+
+    out.counter(
+        "datapass_fetch_errors_total",
+        "Failed fetches of the datapass page",
+        stats.errors as f64,
+    );
+    if let Some(last_success) = stats.last_success {
+        out.gauge(
+            "datapass_last_success_timestamp",
+            "When the datapass page was last fetched, in seconds since the epoch",
+            "",
+            last_success.timestamp() as f64,
+        );
+    }
+
+    out.text
+}
+
+/// Metric families written one after another
+struct Writer {
+    text: String,
+}
+
+impl Writer {
+    fn gauge(&mut self, name: &str, help: &str, labels: &str, value: f64) {
+        self.family(name, "gauge", help);
+        self.sample(name, labels, value);
+    }
+
+    /// A counter; `name` ends in `_total`, for the family and the sample alike
+    fn counter(&mut self, name: &str, help: &str, value: f64) {
+        self.family(name, "counter", help);
+        self.sample(name, "", value);
+    }
+
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {} {}", name, help);
+        let _ = writeln!(self.text, "# TYPE {} {}", name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &str, value: f64) {
+        if labels.is_empty() {
+            let _ = writeln!(self.text, "{} {}", name, value);
+        } else {
+            let _ = writeln!(self.text, "{}{{{}}} {}", name, labels, value);
+        }
+    }
+}
+
+/// Escape a label value: backslash, double quote and line feed
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serves the metrics over HTTP, fetching at most once per `min_interval`
+/// however often it is scraped. A failed fetch keeps the last reading.
+pub struct Exporter<F> {
+    fetch: F,
+    min_interval: Duration,
+    last_attempt: Option<Instant>,
+    data: Option<DataUsage>,
+    stats: FetchStats,
+}
+
+impl<F> Exporter<F>
+where
+    F: FnMut() -> Result<DataUsage>,
+{
+    pub fn new(fetch: F, min_interval: Duration) -> Self {
+        Self {
+            fetch,
+            min_interval,
+            last_attempt: None,
+            data: None,
+            stats: FetchStats::default(),
+        }
+    }
+
+    /// Fetch a new reading unless the last attempt is too recent
+    pub fn refresh(&mut self) {
+        if let Some(last_attempt) = self.last_attempt {
+            if last_attempt.elapsed() < self.min_interval {
+                return;
+            }
+        }
+        self.last_attempt = Some(Instant::now());
+
+        match (self.fetch)() {
+            Ok(data) if data.is_stale() => {
+                self.stats.errors += 1;
+                self.data = Some(data);
+            }
+            Ok(data) => {
+                self.stats.last_success = Some(Utc::now());
+                self.data = Some(data);
+            }
+            Err(e) => {
+                log::error!("Fetch failed: {}", e);
+                self.stats.errors += 1;
+            }
+        }
+    }
+
+    /// The current metrics, refreshed if due
+    pub fn metrics(&mut self) -> String {
+        self.refresh();
+        render(self.data.as_ref(), &self.stats)
+    }
+
+    /// Answer requests one at a time, forever
+    pub fn serve(&mut self, listener: &TcpListener) -> Result<()> {
+        self.refresh();
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) = self.handle(stream) {
+                        log::warn!("Metrics request failed: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Metrics connection failed: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle(&mut self, stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or("");
+        let path = parts.next().unwrap_or("");
+        let path = path.split('?').next().unwrap_or(path);
+
+        // Skip the headers; there is no request body to read
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 && !line.trim_end().is_empty() {
+            line.clear();
+        }
+        log::debug!("Metrics request: {} {}", method, path);
+
+        let (status, content_type, body) = match (method, path) {
+            ("GET" | "HEAD", "/metrics") => ("200 OK", CONTENT_TYPE, self.metrics()),
+            ("GET" | "HEAD", "/") => (
+                "200 OK",
+                "text/html; charset=utf-8",
+                "<html><body><h1>datapass</h1><p><a href=\"/metrics\">Metrics</a></p></body></html>\n"
+                    .to_string(),
+            ),
+            ("GET" | "HEAD", _) => (
+                "404 Not Found",
+                "text/plain; charset=utf-8",
+                "Not found\n".to_string(),
+            ),
+            _ => (
+                "405 Method Not Allowed",
+                "text/plain; charset=utf-8",
+                "Method not allowed\n".to_string(),
+            ),
+        };
+
+        let mut stream = &stream;
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            content_type,
+            body.len()
+        )?;
+        if method != "HEAD" {
+            stream.write_all(body.as_bytes())?;
+        }
+        stream.flush()
+    }
+}
+
+/// Bind the metrics address, e.g. `:9877` (all interfaces) or
+/// `127.0.0.1:9877`
+pub fn listen(address: &str) -> Result<TcpListener> {
+    let bind = match address.strip_prefix(':') {
+        Some(port) => format!("0.0.0.0:{}", port),
+        None => address.to_string(),
+    };
+
+    TcpListener::bind(&bind)
+        .map_err(|e| DatapassError::ConfigError(format!("Cannot listen on {}: {}", address, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn test_render_reading() {
+        let data = DataUsage::new(
+            38.36,
+            51.0,
+            Some("MagentaMobil \"Prepaid\" XL".to_string()),
+            Some("18.11.2026".to_string()),
+        );
+        let stats = FetchStats {
+            errors: 2,
+            last_success: Some("2026-10-18T08:00:00Z".parse().unwrap()),
+        };
+
+        let text = render(Some(&data), &stats);
+        assert!(text.contains("# TYPE datapass_remaining_bytes gauge\n"));
+        assert!(text.contains("\ndatapass_remaining_bytes 38360000000\n"));
+        assert!(text.contains("\ndatapass_total_bytes 51000000000\n"));
+        assert!(text.contains("\ndatapass_unlimited 0\n"));
+        assert!(text.contains("\ndatapass_pass_info{plan=\"MagentaMobil \\\"Prepaid\\\" XL\"} 1\n"));
+        assert!(text.contains("# TYPE datapass_fetch_errors_total counter\n"));
+        assert!(text.contains("\ndatapass_fetch_errors_total 2\n"));
+        assert!(text.contains("\ndatapass_last_success_timestamp 1792310400\n"));
+
+        let valid_until = Local
+            .with_ymd_and_hms(2026, 11, 19, 0, 0, 0)
+            .unwrap()
+            .timestamp();
+        assert!(text.contains(&format!(
+            "\ndatapass_valid_until_timestamp_seconds {}\n",
+            valid_until
+        )));
+    }
+
+    #[test]
+    fn test_pass_info_labels() {
+        let data = DataUsage {
+            pass_name: Some("Datenbonus".to_string()),
+            zone: Some("Germany, Country group 1".to_string()),
+            ..DataUsage::new(1.0, 2.0, Some("MagentaMobil Prepaid L".to_string()), None)
+        };
+
+        let text = render(Some(&data), &FetchStats::default());
+        assert!(text.contains(
+            "\ndatapass_pass_info{plan=\"MagentaMobil Prepaid L\",pass=\"Datenbonus\",zone=\"Germany, Country group 1\"} 1\n"
+        ));
+    }
+
+    #[test]
+    fn test_render_unlimited_and_without_reading() {
+        let data = DataUsage::new_unlimited(None, None);
+        let text = render(Some(&data), &FetchStats::default());
+        assert!(!text.contains("datapass_remaining_bytes"));
+        assert!(text.contains("\ndatapass_unlimited 1\n"));
+        assert!(text.contains("\ndatapass_pass_info{plan=\"\"} 1\n"));
+
+        let text = render(None, &FetchStats::default());
+        assert!(text.starts_with("# HELP datapass_fetch_errors_total "));
+        assert!(!text.contains("datapass_last_success_timestamp"));
+        assert!(!text.contains("# EOF"));
+    }
+
+    #[test]
+    fn test_render_fetch_stats_of_reading() {
+        let data = DataUsage::new(
+            38.36,
+            51.0,
+            Some("MagentaMobil Prepaid XL".to_string()),
+            None,
+        );
+        let now = "2026-10-18T08:00:00Z".parse().unwrap();
+
+        let text = render(Some(&data), &FetchStats::of_reading(&data, now));
+        assert!(text.contains(
+            "\n# TYPE datapass_fetch_errors_total counter\ndatapass_fetch_errors_total 0\n"
+        ));
+        assert!(text.contains("\ndatapass_pass_info{plan=\"MagentaMobil Prepaid XL\"} 1\n"));
+        assert!(text.contains("\ndatapass_last_success_timestamp 1792310400\n"));
+    }
+
+    #[test]
+    fn test_refresh_is_rate_limited() {
+        let mut fetches = 0;
+        let mut exporter = Exporter::new(
+            || {
+                fetches += 1;
+                if fetches == 1 {
+                    Ok(DataUsage::new(2.5, 51.0, None, None))
+                } else {
+                    Err(DatapassError::DataNotFound("volume".to_string()))
+                }
+            },
+            Duration::from_secs(3600),
+        );
+
+        let first = exporter.metrics();
+        assert_eq!(exporter.metrics(), first);
+        assert!(first.contains("\ndatapass_fetch_errors_total 0\n"));
+
+        exporter.min_interval = Duration::ZERO;
+        let failed = exporter.metrics();
+        assert!(failed.contains("\ndatapass_remaining_bytes 2500000000\n"));
+        assert!(failed.contains("\ndatapass_fetch_errors_total 1\n"));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::alert::{Alert, Alerts};
+use crate::config::Config;
+use crate::error::DatapassError;
+use crate::hooks::Hooks;
+#[cfg(feature = "mqtt")]
+use crate::mqtt::MqttPublisher;
+use crate::notify::{self, Notifier};
+use crate::statsd::StatsdEmitter;
+use crate::topup::TopUp;
+use crate::types::DataUsage;
+
+/// Work done after every successful fetch
+pub struct Monitor {
+    topup: Option<TopUp>,
+    alerts: Option<Alerts>,
+    hooks: Option<Hooks>,
+    notifiers: Vec<Box<dyn Notifier>>,
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<MqttPublisher>,
+    statsd: Option<StatsdEmitter>,
+}
+
+impl Monitor {
+    /// Set up the configured actions for watch and daemon mode. `can_book`
+    /// is false when the data does not come from the network (e.g. `--file`),
+    /// which disables top-ups.
+    pub fn new(config: &Config, url: Option<&str>, cookie: Option<&str>, can_book: bool) -> Self {
+        let topup = match &config.topup {
+            Some(policy) if policy.enabled && can_book => {
+                log::info!("Top-up policy enabled for '{}'", policy.offer);
+                Some(TopUp::new(policy.clone(), url, cookie))
+            }
+            Some(policy) if policy.enabled => {
+                log::warn!("Top-up policy is disabled when not fetching from the network");
+                None
+            }
+            _ => None,
+        };
+
+        #[cfg(not(feature = "mqtt"))]
+        if config.mqtt.as_ref().is_some_and(|mqtt| mqtt.enabled) {
+            log::warn!("MQTT publishing needs the `mqtt` feature; ignoring [mqtt]");
+        }
+
+        Self {
+            topup,
+            alerts: Self::alerts(config),
+            hooks: Self::hooks(config),
+            notifiers: notify::notifiers(config),
+            #[cfg(feature = "mqtt")]
+            mqtt: Self::mqtt(config),
+            statsd: Self::statsd(config),
+        }
+    }
+
+    /// Set up the actions for a single run: alerts, hooks and notifiers;
+    /// top-ups, MQTT and StatsD are left to the long-running modes. Set up
+    /// before fetching, so that the hooks see the previous reading.
+    pub fn single_run(config: &Config) -> Self {
+        Self {
+            topup: None,
+            alerts: Self::alerts(config),
+            hooks: Self::hooks(config),
+            notifiers: notify::notifiers(config),
+            #[cfg(feature = "mqtt")]
+            mqtt: None,
+            statsd: None,
+        }
+    }
+
+    #[cfg(feature = "mqtt")]
+    fn mqtt(config: &Config) -> Option<MqttPublisher> {
+        let mqtt = config.mqtt.as_ref().filter(|mqtt| mqtt.enabled)?;
+        MqttPublisher::new(mqtt.clone())
+            .map_err(|e| log::error!("MQTT publishing not set up: {}", e))
+            .ok()
+    }
+
+    fn statsd(config: &Config) -> Option<StatsdEmitter> {
+        let statsd = config.statsd.as_ref().filter(|statsd| statsd.enabled)?;
+        StatsdEmitter::new(statsd.clone())
+            .map_err(|e| log::error!("StatsD not set up: {}", e))
+            .ok()
+    }
+
+    fn alerts(config: &Config) -> Option<Alerts> {
+        config
+            .alerts
+            .as_ref()
+            .filter(|alerts| alerts.enabled)
+            .map(|alerts| Alerts::new(alerts.clone()))
+    }
+
+    fn hooks(config: &Config) -> Option<Hooks> {
+        config.hooks.clone().map(Hooks::new)
+    }
+
+    /// Run the configured actions for a fresh reading; returns the alerts
+    /// that fired
+    pub fn on_fetch(&mut self, data: &DataUsage) -> Vec<Alert> {
+        // Stored readings were already handled when they were fresh
+        if data.is_stale() {
+            log::debug!("Skipping post-fetch actions for a stale reading");
+            return Vec::new();
+        }
+
+        let alerts = match &mut self.alerts {
+            Some(alerts) => alerts.check(data).unwrap_or_else(|e| {
+                log::error!("Alert check failed: {}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+
+        if let Some(hooks) = &mut self.hooks {
+            hooks.on_fetch(data);
+            for alert in &alerts {
+                hooks.on_alert(data, alert);
+            }
+        }
+
+        for notifier in &mut self.notifiers {
+            if let Err(e) = notifier.on_reading(data) {
+                log::error!("The {} notifier failed: {}", notifier.name(), e);
+            }
+            for alert in &alerts {
+                if let Err(e) = notifier.notify(alert, data) {
+                    log::error!("Sending the {} notification failed: {}", notifier.name(), e);
+                }
+            }
+        }
+
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish(data);
+        }
+
+        if let Some(statsd) = &self.statsd {
+            statsd.send(data);
+        }
+
+        if let Some(topup) = &mut self.topup {
+            if let Err(e) = topup.check(data) {
+                log::error!("Top-up policy failed: {}", e);
+            }
+        }
+
+        alerts
+    }
+
+    /// Run the configured actions for a failed fetch
+    pub fn on_error(&self, error: &DatapassError) {
+        if let Some(hooks) = &self.hooks {
+            hooks.on_error(&error.to_string());
+        }
+
+        if let Some(statsd) = &self.statsd {
+            statsd.count_error();
+        }
+    }
+}
@@ -0,0 +1,435 @@
+use crate::alert::PlanStatus;
+use crate::types::DataUsage;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[cfg(feature = "mqtt")]
+pub use publisher::MqttPublisher;
+
+/// Publishing readings to an MQTT broker (`[mqtt]` config section), in watch
+/// and daemon mode
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MqttConfig {
+    /// Publish; the section being present enables it
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Broker host name or address
+    pub host: String,
+
+    /// Broker port (default: 1883, or 8883 with TLS)
+    pub port: Option<u16>,
+
+    /// Connect with TLS
+    #[serde(default)]
+    pub tls: bool,
+
+    /// PEM file with the CA certificates to trust instead of the built-in
+    /// roots, e.g. for a self-signed broker
+    pub ca_file: Option<PathBuf>,
+
+    /// PEM files with a client certificate and its key, for brokers that
+    /// authenticate clients by certificate
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+
+    /// Login, if the broker requires one
+    pub username: Option<String>,
+    pub password: Option<String>,
+
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+
+    /// Readings go to `<topic_prefix>/remaining_gb` and so on
+    #[serde(default = "default_topic_prefix")]
+    pub topic_prefix: String,
+
+    /// Publish retained messages, so that subscribers get the last reading
+    /// right away
+    #[serde(default = "default_true")]
+    pub retain: bool,
+
+    /// Quality of service: 0, 1 or 2
+    #[serde(default = "default_qos")]
+    pub qos: u8,
+
+    /// Announce the sensors for Home Assistant's MQTT discovery
+    #[serde(default = "default_true")]
+    pub discovery: bool,
+
+    #[serde(default = "default_discovery_prefix")]
+    pub discovery_prefix: String,
+
+    /// Device ID in Home Assistant; use one per SIM
+    #[serde(default = "default_client_id")]
+    pub node_id: String,
+
+    #[serde(default = "default_keep_alive_seconds")]
+    pub keep_alive_seconds: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_client_id() -> String {
+    "datapass".to_string()
+}
+
+fn default_topic_prefix() -> String {
+    "datapass".to_string()
+}
+
+fn default_qos() -> u8 {
+    1
+}
+
+fn default_discovery_prefix() -> String {
+    "homeassistant".to_string()
+}
+
+fn default_keep_alive_seconds() -> u64 {
+    60
+}
+
+impl MqttConfig {
+    /// The configured port, or the usual one
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(if self.tls { 8883 } else { 1883 })
+    }
+
+    /// Topic of one value, e.g. "datapass/remaining_gb"
+    pub fn topic(&self, name: &str) -> String {
+        format!("{}/{}", self.topic_prefix.trim_end_matches('/'), name)
+    }
+
+    /// Topic with "online" while connected and "offline" (the last will)
+    /// after
+    pub fn availability_topic(&self) -> String {
+        self.topic("availability")
+    }
+}
+
+/// A value published for every reading, and its Home Assistant sensor
+struct Sensor {
+    /// Topic suffix and sensor object ID
+    key: &'static str,
+    name: &'static str,
+    unit: Option<&'static str>,
+    device_class: Option<&'static str>,
+    icon: &'static str,
+}
+
+const SENSORS: &[Sensor] = &[
+    Sensor {
+        key: "remaining_gb",
+        name: "Remaining",
+        unit: Some("GB"),
+        device_class: Some("data_size"),
+        icon: "mdi:signal-cellular-3",
+    },
+    Sensor {
+        key: "used_gb",
+        name: "Used",
+        unit: Some("GB"),
+        device_class: Some("data_size"),
+        icon: "mdi:chart-donut",
+    },
+    Sensor {
+        key: "total_gb",
+        name: "Total",
+        unit: Some("GB"),
+        device_class: Some("data_size"),
+        icon: "mdi:database",
+    },
+    Sensor {
+        key: "percentage",
+        name: "Used share",
+        unit: Some("%"),
+        device_class: None,
+        icon: "mdi:percent",
+    },
+    Sensor {
+        key: "valid_until",
+        name: "Valid until",
+        unit: None,
+        device_class: None,
+        icon: "mdi:calendar-end",
+    },
+    Sensor {
+        key: "plan",
+        name: "Plan",
+        unit: None,
+        device_class: None,
+        icon: "mdi:sim",
+    },
+    Sensor {
+        key: "status",
+        name: "Status",
+        unit: None,
+        device_class: Some("enum"),
+        icon: "mdi:speedometer",
+    },
+];
+
+/// The messages (topic and payload) published for a reading
+pub fn state_messages(config: &MqttConfig, data: &DataUsage) -> Vec<(String, String)> {
+    SENSORS
+        .iter()
+        .map(|sensor| {
+            let payload = match sensor.key {
+                "remaining_gb" => format!("{:.2}", data.remaining_gb),
+                "used_gb" => format!("{:.2}", data.used_gb),
+                "total_gb" => format!("{:.2}", data.total_gb),
+                "percentage" => format!("{:.2}", data.percentage),
+                "valid_until" => data.valid_until.clone().unwrap_or_default(),
+                "plan" => data.plan_name.clone().unwrap_or_default(),
+                _ => PlanStatus::of(data).to_string(),
+            };
+            (config.topic(sensor.key), payload)
+        })
+        .collect()
+}
+
+/// Home Assistant discovery configs (topic and payload), one per sensor
+pub fn discovery_messages(config: &MqttConfig) -> Vec<(String, String)> {
+    let prefix = config.discovery_prefix.trim_end_matches('/');
+    let device = json!({
+        "identifiers": [config.node_id],
+        "name": "datapass",
+        "manufacturer": "datapass",
+        "model": "Mobile data usage",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+
+    SENSORS
+        .iter()
+        .map(|sensor| {
+            let mut payload = json!({
+                "name": sensor.name,
+                "unique_id": format!("{}_{}", config.node_id, sensor.key),
+                "state_topic": config.topic(sensor.key),
+                "availability_topic": config.availability_topic(),
+                "icon": sensor.icon,
+                "device": device,
+            });
+            if let Some(unit) = sensor.unit {
+                payload["unit_of_measurement"] = unit.into();
+                payload["state_class"] = "measurement".into();
+            }
+            if let Some(device_class) = sensor.device_class {
+                payload["device_class"] = device_class.into();
+            }
+            if sensor.key == "status" {
+                payload["options"] = json!(["available", "throttled", "unlimited"]);
+            }
+
+            (
+                format!("{}/sensor/{}/{}/config", prefix, config.node_id, sensor.key),
+                payload.to_string(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(feature = "mqtt")]
+mod publisher {
+    use super::{discovery_messages, state_messages, MqttConfig};
+    use crate::error::{DatapassError, Result};
+    use crate::types::DataUsage;
+    use rumqttc::tokio_rustls::rustls::pki_types::pem::PemObject;
+    use rumqttc::tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+    use rumqttc::tokio_rustls::rustls::{ClientConfig, RootCertStore};
+    use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS, TlsConfiguration, Transport};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Wait before connecting again after the connection failed
+    const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+    /// Publishes readings to the broker. The connection is kept (and
+    /// re-established) on a thread of its own; publishing never blocks.
+    pub struct MqttPublisher {
+        config: MqttConfig,
+        client: Client,
+    }
+
+    impl MqttPublisher {
+        pub fn new(config: MqttConfig) -> Result<Self> {
+            let qos = qos(config.qos)?;
+            let mut options = MqttOptions::new(&config.client_id, &config.host, config.port());
+            options
+                .set_keep_alive(Duration::from_secs(config.keep_alive_seconds.max(5)))
+                .set_last_will(LastWill::new(
+                    config.availability_topic(),
+                    "offline",
+                    qos,
+                    true,
+                ));
+            if let Some(username) = &config.username {
+                options.set_credentials(username, config.password.clone().unwrap_or_default());
+            }
+            if config.tls {
+                options.set_transport(Transport::tls_with_config(tls_config(&config)?));
+            }
+
+            let (client, mut connection) = Client::new(options, 64);
+
+            let announcer = client.clone();
+            let announced = config.clone();
+            std::thread::Builder::new()
+                .name("mqtt".to_string())
+                .spawn(move || {
+                    for event in connection.iter() {
+                        match event {
+                            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                                log::info!("Connected to MQTT broker {}", announced.host);
+                                announce(&announcer, &announced, qos);
+                            }
+                            Ok(_) => {}
+                            Err(e) => {
+                                log::warn!("MQTT broker {}: {}", announced.host, e);
+                                std::thread::sleep(RECONNECT_DELAY);
+                            }
+                        }
+                    }
+                })?;
+
+            Ok(Self { config, client })
+        }
+
+        /// Queue the values of a reading for publishing
+        pub fn publish(&self, data: &DataUsage) {
+            // Checked when connecting
+            let qos = qos(self.config.qos).unwrap_or(QoS::AtLeastOnce);
+
+            for (topic, payload) in state_messages(&self.config, data) {
+                if let Err(e) = self
+                    .client
+                    .try_publish(&topic, qos, self.config.retain, payload)
+                {
+                    log::error!("Publishing {} failed: {}", topic, e);
+                }
+            }
+        }
+    }
+
+    /// Mark the device online and (re-)announce the sensors after connecting
+    fn announce(client: &Client, config: &MqttConfig, qos: QoS) {
+        let mut messages = vec![(config.availability_topic(), "online".to_string())];
+        if config.discovery {
+            messages.extend(discovery_messages(config));
+        }
+
+        for (topic, payload) in messages {
+            if let Err(e) = client.try_publish(&topic, qos, true, payload) {
+                log::error!("Publishing {} failed: {}", topic, e);
+            }
+        }
+    }
+
+    fn qos(level: u8) -> Result<QoS> {
+        match level {
+            0 => Ok(QoS::AtMostOnce),
+            1 => Ok(QoS::AtLeastOnce),
+            2 => Ok(QoS::ExactlyOnce),
+            _ => Err(DatapassError::ConfigError(format!(
+                "mqtt.qos must be 0, 1 or 2, not {}",
+                level
+            ))),
+        }
+    }
+
+    /// TLS trusting the built-in roots or the configured CA file, with the
+    /// client certificate if one is configured
+    fn tls_config(config: &MqttConfig) -> Result<TlsConfiguration> {
+        let invalid = |what: &str, e: &dyn std::fmt::Display| {
+            DatapassError::ConfigError(format!("mqtt.{}: {}", what, e))
+        };
+
+        let mut roots = RootCertStore::empty();
+        match &config.ca_file {
+            Some(path) => {
+                for cert in
+                    CertificateDer::pem_file_iter(path).map_err(|e| invalid("ca_file", &e))?
+                {
+                    let cert = cert.map_err(|e| invalid("ca_file", &e))?;
+                    roots.add(cert).map_err(|e| invalid("ca_file", &e))?;
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+
+        let builder = ClientConfig::builder().with_root_certificates(roots);
+        let tls = match (&config.client_cert, &config.client_key) {
+            (Some(cert), Some(key)) => {
+                let certs = CertificateDer::pem_file_iter(cert)
+                    .and_then(|certs| certs.collect::<std::result::Result<Vec<_>, _>>())
+                    .map_err(|e| invalid("client_cert", &e))?;
+                let key =
+                    PrivateKeyDer::from_pem_file(key).map_err(|e| invalid("client_key", &e))?;
+                builder
+                    .with_client_auth_cert(certs, key)
+                    .map_err(|e| invalid("client_key", &e))?
+            }
+            (None, None) => builder.with_no_client_auth(),
+            _ => {
+                return Err(DatapassError::ConfigError(
+                    "mqtt.client_cert and mqtt.client_key go together".to_string(),
+                ))
+            }
+        };
+
+        Ok(TlsConfiguration::Rustls(Arc::new(tls)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> MqttConfig {
+        toml::from_str("host = \"broker\"\ntopic_prefix = \"home/sim1/\"\nnode_id = \"sim1\"")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_state_messages() {
+        let data = DataUsage::new(
+            38.36,
+            51.0,
+            Some("Prepaid XL".to_string()),
+            Some("1. November 2026".to_string()),
+        );
+
+        let messages = state_messages(&config(), &data);
+        assert_eq!(
+            messages[0],
+            ("home/sim1/remaining_gb".into(), "38.36".into())
+        );
+        assert_eq!(messages[3], ("home/sim1/percentage".into(), "24.78".into()));
+        assert_eq!(messages[4].1, "1. November 2026");
+        assert_eq!(messages[6], ("home/sim1/status".into(), "available".into()));
+    }
+
+    #[test]
+    fn test_discovery_messages() {
+        let messages = discovery_messages(&config());
+        assert_eq!(messages.len(), SENSORS.len());
+
+        let (topic, payload) = &messages[0];
+        assert_eq!(topic, "homeassistant/sensor/sim1/remaining_gb/config");
+        let payload: serde_json::Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(payload["unique_id"], "sim1_remaining_gb");
+        assert_eq!(payload["state_topic"], "home/sim1/remaining_gb");
+        assert_eq!(payload["availability_topic"], "home/sim1/availability");
+        assert_eq!(payload["unit_of_measurement"], "GB");
+        assert_eq!(payload["device"]["identifiers"][0], "sim1");
+    }
+}
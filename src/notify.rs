@@ -0,0 +1,209 @@
+use crate::alert::Alert;
+use crate::config::Config;
+use crate::error::Result;
+use crate::types::DataUsage;
+use crate::webhook::{WebhookConfig, WebhookNotifier};
+use chrono::Weekday;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[cfg(feature = "desktop")]
+use crate::desktop::DesktopNotifier;
+#[cfg(feature = "email")]
+use crate::email::EmailNotifier;
+
+/// Where alerts are sent (`[notify]` config section)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// Desktop notifications over D-Bus (`[notify.desktop]`)
+    pub desktop: Option<DesktopConfig>,
+
+    /// Webhooks alerts are posted to (`[[notify.webhook]]`)
+    pub webhook: Vec<WebhookConfig>,
+
+    /// Emails over SMTP (`[notify.email]`)
+    pub email: Option<EmailConfig>,
+}
+
+/// Desktop notification settings (`[notify.desktop]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesktopConfig {
+    /// Send notifications; the section being present enables it
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Icon name or path shown with the notification
+    pub icon: Option<String>,
+
+    /// How long notifications stay, in milliseconds (default: the
+    /// notification server decides)
+    pub expire_timeout_ms: Option<i32>,
+}
+
+/// Email settings (`[notify.email]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailConfig {
+    /// Send emails; the section being present enables it
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// SMTP server
+    pub host: String,
+
+    /// SMTP port (default: 587 for STARTTLS, 465 for TLS, 25 without)
+    pub port: Option<u16>,
+
+    /// How the connection is encrypted
+    #[serde(default)]
+    pub tls: EmailTls,
+
+    /// Login, if the server requires one
+    pub username: Option<String>,
+    pub password: Option<String>,
+
+    /// Sender, e.g. "datapass <datapass@example.com>"
+    pub from: String,
+
+    /// Recipients
+    pub to: Vec<String>,
+
+    /// Put in front of every subject
+    #[serde(default = "default_subject_prefix")]
+    pub subject_prefix: String,
+
+    /// Also send a summary of the week's usage once a week
+    #[serde(default)]
+    pub weekly_digest: bool,
+
+    /// Day the weekly digest is due (default: Monday)
+    #[serde(default = "default_digest_weekday")]
+    pub digest_weekday: Weekday,
+
+    /// When the last digest was sent (default:
+    /// `$XDG_STATE_HOME/datapass/email_digest.json`); give each config its
+    /// own file when several run on one host
+    pub digest_state: Option<PathBuf>,
+
+    /// Give up on the server after this many seconds
+    #[serde(default = "default_email_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+/// Encryption of the SMTP connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailTls {
+    /// Plain connection upgraded with STARTTLS, which the server must offer
+    #[default]
+    Starttls,
+    /// TLS from the start ("SMTPS")
+    Tls,
+    /// No encryption, for a relay on the local host only
+    None,
+}
+
+impl EmailConfig {
+    /// The configured port, or the usual one for the encryption
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.tls {
+            EmailTls::Starttls => 587,
+            EmailTls::Tls => 465,
+            EmailTls::None => 25,
+        })
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_subject_prefix() -> String {
+    "[datapass]".to_string()
+}
+
+fn default_digest_weekday() -> Weekday {
+    Weekday::Mon
+}
+
+fn default_email_timeout_seconds() -> u64 {
+    30
+}
+
+/// Something that passes alerts on
+pub trait Notifier {
+    /// Short name for log messages, e.g. "desktop"
+    fn name(&self) -> &'static str;
+
+    /// Send one alert about a reading
+    fn notify(&mut self, alert: &Alert, data: &DataUsage) -> Result<()>;
+
+    /// Called for every fresh reading, alerts or not (e.g. for digests)
+    fn on_reading(&mut self, _data: &DataUsage) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The notifiers enabled in the config
+pub fn notifiers(config: &Config) -> Vec<Box<dyn Notifier>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+    let notify = &config.notify;
+
+    if let Some(desktop) = notify.desktop.as_ref().filter(|desktop| desktop.enabled) {
+        #[cfg(feature = "desktop")]
+        notifiers.push(Box::new(DesktopNotifier::new(desktop.clone())));
+
+        #[cfg(not(feature = "desktop"))]
+        {
+            let _ = desktop;
+            log::warn!(
+                "Desktop notifications need the `desktop` feature; ignoring [notify.desktop]"
+            );
+        }
+    }
+
+    for webhook in notify.webhook.iter().filter(|webhook| webhook.enabled) {
+        match WebhookNotifier::new(webhook.clone()) {
+            Ok(notifier) => notifiers.push(Box::new(notifier)),
+            Err(e) => log::error!("Webhook {} not set up: {}", webhook.url, e),
+        }
+    }
+
+    if let Some(email) = notify.email.as_ref().filter(|email| email.enabled) {
+        #[cfg(feature = "email")]
+        match EmailNotifier::new(email.clone(), config.history_store()) {
+            Ok(notifier) => notifiers.push(Box::new(notifier)),
+            Err(e) => log::error!("Email notifications not set up: {}", e),
+        }
+
+        #[cfg(not(feature = "email"))]
+        {
+            let _ = email;
+            log::warn!("Email notifications need the `email` feature; ignoring [notify.email]");
+        }
+    }
+
+    notifiers
+}
+
+/// Title for an alert, e.g. "Mobile data: critical"
+pub fn summary(alert: &Alert) -> String {
+    format!("Mobile data: {}", alert.severity)
+}
+
+/// Text for an alert: what happened and the reading it happened at
+pub fn body(alert: &Alert, data: &DataUsage) -> String {
+    let plan = data.plan_name.as_deref().unwrap_or("Unknown Plan");
+    let volume = if data.is_unlimited {
+        "unlimited".to_string()
+    } else {
+        format!(
+            "{:.2} GB of {:.2} GB remaining",
+            data.remaining_gb, data.total_gb
+        )
+    };
+
+    format!("{}\n{}: {}", alert.message, plan, volume)
+}
@@ -1,6 +1,7 @@
 use crate::error::{DatapassError, Result};
-use crate::types::DataUsage;
-use scraper::{Html, Selector};
+use crate::types::{DataUsage, PassOffer, PassUsage, TariffTransparency};
+use chrono::NaiveDate;
+use scraper::{ElementRef, Html, Selector};
 
 /// Parse HTML content to extract data usage information
 pub fn parse_html(html: &str) -> Result<DataUsage> {
@@ -8,11 +9,7 @@ pub fn parse_html(html: &str) -> Result<DataUsage> {
 
     // Check if this is an authentication/redirect page
     if is_auth_required_page(&document) {
-        return Err(DatapassError::DataNotFound(
-            "Access denied. This website requires an active Telekom mobile data connection.\n  \
-            \nTo test without Telekom network, use: --file <saved-html-file>"
-                .to_string(),
-        ));
+        return Err(auth_required_error());
     }
 
     // Extract plan name from title
@@ -21,20 +18,26 @@ pub fn parse_html(html: &str) -> Result<DataUsage> {
     // Extract validity date (optional)
     let valid_until = extract_valid_until(&document);
 
+    // Name and zone of the active data pass (optional)
+    let (pass_name, zone) = extract_active_pass(&document);
+
     // Check if this is an unlimited plan
     if is_unlimited_plan(&document) {
-        return Ok(DataUsage::new_unlimited(Some(plan_name), valid_until));
+        return Ok(DataUsage {
+            pass_name,
+            zone,
+            ..DataUsage::new_unlimited(Some(plan_name), valid_until)
+        });
     }
 
     // Extract data usage from the active data pass
     let (remaining_gb, total_gb) = extract_data_usage(&document)?;
 
-    Ok(DataUsage::new(
-        remaining_gb,
-        total_gb,
-        Some(plan_name),
-        valid_until,
-    ))
+    Ok(DataUsage {
+        pass_name,
+        zone,
+        ..DataUsage::new(remaining_gb, total_gb, Some(plan_name), valid_until)
+    })
 }
 
 /// Extract plan name from the HTML title
@@ -144,24 +147,173 @@ fn extract_data_usage(document: &Html) -> Result<(f64, f64)> {
     ))
 }
 
+/// Extract the name and zone of the active data pass: the first one (not
+/// the summation) showing a volume or unlimited data
+fn extract_active_pass(document: &Html) -> (Option<String>, Option<String>) {
+    let (
+        Ok(section_selector),
+        Ok(headline_selector),
+        Ok(remaining_selector),
+        Ok(volume_selector),
+        Ok(info_row_selector),
+    ) = (
+        Selector::parse("section.data-pass-instance"),
+        Selector::parse(".data-pass-instance__headline"),
+        Selector::parse("div.remaining-volume-value"),
+        Selector::parse("div.volume"),
+        Selector::parse("div.info-row"),
+    )
+    else {
+        return (None, None);
+    };
+
+    for section in document.select(&section_selector) {
+        if section.value().attr("id") == Some("summationPass") {
+            continue;
+        }
+
+        let is_active = section.select(&remaining_selector).next().is_some()
+            || section.select(&volume_selector).any(|elem| {
+                let text = elem.text().collect::<String>().to_lowercase();
+                text.contains("unlimited") || text.contains("unbegrenzt")
+            });
+        if !is_active {
+            continue;
+        }
+
+        let name = section
+            .select(&headline_selector)
+            .next()
+            .map(|elem| normalize_text(&elem.text().collect::<String>()))
+            .filter(|name| !name.is_empty());
+        let zone = section
+            .select(&info_row_selector)
+            .find_map(|elem| zone_from_row(&elem.text().collect::<String>()));
+
+        return (name, zone);
+    }
+
+    (None, None)
+}
+
 /// Extract validity date from the HTML (optional)
 /// Looks for "Valid until:" or "Gültig bis:" in div.info-row elements
 fn extract_valid_until(document: &Html) -> Option<String> {
     let info_row_selector = Selector::parse("div.info-row").ok()?;
 
-    for elem in document.select(&info_row_selector) {
-        let text = elem.text().collect::<String>();
+    document
+        .select(&info_row_selector)
+        .find_map(|elem| valid_until_from_row(&elem.text().collect::<String>()))
+}
+
+/// Extract the date from a "Valid until: ..." info row
+fn valid_until_from_row(text: &str) -> Option<String> {
+    // Check for both German and English variants
+    if text.contains("Valid until:") || text.contains("Gültig bis:") {
+        // Extract the date part after the colon
+        return text.split(':').nth(1).map(|s| s.trim().to_string());
+    }
+
+    None
+}
+
+/// Extract the zone from a "Valid in ..." info row
+fn zone_from_row(text: &str) -> Option<String> {
+    let text = normalize_text(text);
+    ["Valid in ", "Gültig in "]
+        .iter()
+        .find_map(|label| text.strip_prefix(label))
+        .map(|zone| zone.trim().to_string())
+        .filter(|zone| !zone.is_empty())
+}
+
+/// Parse every data pass shown on the page, skipping the summation
+pub fn parse_passes(html: &str) -> Result<Vec<PassUsage>> {
+    let document = Html::parse_document(html);
+
+    if is_auth_required_page(&document) {
+        return Err(auth_required_error());
+    }
+
+    let section_selector = Selector::parse("section.data-pass-instance")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let headline_selector = Selector::parse(".data-pass-instance__headline")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let remaining_selector = Selector::parse("div.remaining-volume-value")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let total_selector = Selector::parse("div.start-volume")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let volume_selector = Selector::parse("div.volume")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let exhausted_selector = Selector::parse("div.volume-ssd")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
 
-        // Check for both German and English variants
-        if text.contains("Valid until:") || text.contains("Gültig bis:") {
-            // Extract the date part after the colon
-            let date = text.split(':').nth(1).map(|s| s.trim().to_string())?;
+    let info_row_selector = Selector::parse("div.info-row")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
 
-            return Some(date);
+    let mut passes = Vec::new();
+
+    for section in document.select(&section_selector) {
+        let id = section.value().attr("id");
+        if id == Some("summationPass") {
+            continue;
         }
+
+        let text_of = |selector: &Selector| {
+            section
+                .select(selector)
+                .next()
+                .map(|elem| normalize_text(&elem.text().collect::<String>()))
+        };
+
+        let is_unlimited = section.select(&volume_selector).any(|elem| {
+            let text = elem.text().collect::<String>().to_lowercase();
+            text.contains("unlimited") || text.contains("unbegrenzt")
+        });
+
+        let remaining_gb = text_of(&remaining_selector).and_then(|t| parse_number(&t).ok());
+        let total_gb = text_of(&total_selector).and_then(|t| parse_number(&t).ok());
+
+        passes.push(PassUsage {
+            id: id.map(str::to_string),
+            name: text_of(&headline_selector).filter(|name| !name.is_empty()),
+            remaining_gb,
+            total_gb,
+            is_unlimited,
+            is_exhausted: section.select(&exhausted_selector).next().is_some()
+                || remaining_gb == Some(0.0),
+            valid_until: section
+                .select(&info_row_selector)
+                .find_map(|elem| valid_until_from_row(&elem.text().collect::<String>())),
+            zone: section
+                .select(&info_row_selector)
+                .find_map(|elem| zone_from_row(&elem.text().collect::<String>())),
+        });
     }
 
-    None
+    Ok(passes)
+}
+
+/// Parse the carrier's "last update" time (e.g., "24.01.2026 at 13:47")
+pub fn parse_last_update(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let last_update_selector = Selector::parse("div.last-update").ok()?;
+    let tooltip_selector = Selector::parse(".hover-anker").ok()?;
+
+    let element = document.select(&last_update_selector).next()?;
+    let mut text = element.text().collect::<String>();
+
+    // Drop the label and the delay disclaimer in the tooltip
+    for tooltip in element.select(&tooltip_selector) {
+        text = text.replace(&tooltip.text().collect::<String>(), "");
+    }
+
+    Some(normalize_text(&text)).filter(|text| !text.is_empty())
 }
 
 /// Check if the page is an authentication/redirect page
@@ -181,6 +333,311 @@ fn is_auth_required_page(document: &Html) -> bool {
         || (body_text.contains("weiterleitung") && body_text.contains("nicht möglich"))
 }
 
+/// Parse the data pass offers ("Verfügbare Datenpässe") from the main page.
+/// Promotional cards (e.g. roaming reservation) are skipped.
+pub fn parse_offers(html: &str) -> Result<Vec<PassOffer>> {
+    let document = Html::parse_document(html);
+
+    if is_auth_required_page(&document) {
+        return Err(auth_required_error());
+    }
+
+    let card_selector = Selector::parse("div.pass-offer-card")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let headline_selector = Selector::parse("div.pass-offer-card__headline")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let volume_selector = Selector::parse("div.volume")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let description_selector = Selector::parse("div.description")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let price_selector = Selector::parse("span.price")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let link_selector = Selector::parse("a.pass-offer-card__call-to-action-link")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let mut offers = Vec::new();
+
+    for card in document.select(&card_selector) {
+        // Skip promotional cards such as the roaming pass reservation
+        if card.value().classes().any(|class| class == "option-ad") {
+            continue;
+        }
+
+        let name = match card.select(&headline_selector).next() {
+            Some(elem) => normalize_text(&elem.text().collect::<String>()),
+            None => continue,
+        };
+
+        let volume_text = card
+            .select(&volume_selector)
+            .next()
+            .map(|elem| normalize_text(&elem.text().collect::<String>()));
+
+        let is_unlimited = volume_text.as_deref().is_some_and(|text| {
+            let text = text.to_lowercase();
+            text.contains("unlimited") || text.contains("unbegrenzt")
+        });
+
+        let volume_gb = if is_unlimited {
+            None
+        } else {
+            volume_text.as_deref().and_then(parse_volume)
+        };
+
+        let description = card
+            .select(&description_selector)
+            .next()
+            .map(|elem| normalize_text(&elem.text().collect::<String>()))
+            .filter(|text| !text.is_empty());
+
+        let price_eur = card
+            .select(&price_selector)
+            .next()
+            .and_then(|elem| parse_price(&elem.text().collect::<String>()));
+
+        let booking_path = card
+            .select(&link_selector)
+            .next()
+            .and_then(|elem| elem.value().attr("href"))
+            .filter(|href| href.contains("/pass/select/"))
+            .map(|href| href.to_string());
+
+        let id = booking_path.as_deref().and_then(extract_offer_id);
+
+        offers.push(PassOffer {
+            id,
+            name,
+            volume_gb,
+            is_unlimited,
+            price_eur,
+            description,
+            booking_path,
+        });
+    }
+
+    Ok(offers)
+}
+
+/// Extract the offer ID from a booking link like "/pass/select/GDPXDF13?crt=..."
+fn extract_offer_id(href: &str) -> Option<String> {
+    let rest = href.split("/pass/select/").nth(1)?;
+    let id = rest.split(['?', '/', '#']).next()?;
+
+    if id.is_empty() {
+        None
+    } else {
+        Some(id.to_string())
+    }
+}
+
+/// Parse a volume like "3GB" or "500 MB" into GB
+fn parse_volume(text: &str) -> Option<f64> {
+    let lower = text.to_lowercase();
+    let number: String = lower
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit() || *c == ',' || *c == '.')
+        .collect();
+    let value = parse_number(&number).ok()?;
+
+    if lower.contains("mb") {
+        Some(value / 1000.0)
+    } else {
+        Some(value)
+    }
+}
+
+/// Parse a price like "6,95 €" into EUR
+fn parse_price(text: &str) -> Option<f64> {
+    let number: String = text
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == ',' || *c == '.')
+        .collect();
+    parse_number(&number).ok()
+}
+
+/// Parse the tariff transparency page (`/tariffTransparency/domestic`)
+pub fn parse_tariff_transparency(html: &str) -> Result<TariffTransparency> {
+    let document = Html::parse_document(html);
+
+    if is_auth_required_page(&document) {
+        return Err(auth_required_error());
+    }
+
+    // The title may or may not carry the tariff name on this page
+    let mut tariff = TariffTransparency {
+        plan_name: extract_plan_name(&document).ok(),
+        ..Default::default()
+    };
+
+    for row in extract_tariff_rows(&document)? {
+        apply_tariff_row(&mut tariff, &row);
+    }
+
+    if !tariff.has_speeds() {
+        return Err(DatapassError::DataNotFound(
+            "Could not find tariff transparency information".to_string(),
+        ));
+    }
+
+    Ok(tariff)
+}
+
+/// Collect label/value rows from tables, lists, info rows and definition lists
+fn extract_tariff_rows(document: &Html) -> Result<Vec<String>> {
+    let row_selector = Selector::parse("tr, li, div.info-row")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let term_selector = Selector::parse("dt")
+        .map_err(|e| DatapassError::ParseError(format!("Invalid selector: {:?}", e)))?;
+
+    let mut rows: Vec<String> = document
+        .select(&row_selector)
+        .map(|elem| normalize_text(&elem.text().collect::<String>()))
+        .collect();
+
+    // Definition lists keep label and value in sibling elements
+    for term in document.select(&term_selector) {
+        let value = term
+            .next_siblings()
+            .filter_map(ElementRef::wrap)
+            .find(|elem| elem.value().name() == "dd");
+
+        if let Some(value) = value {
+            rows.push(normalize_text(&format!(
+                "{} {}",
+                term.text().collect::<String>(),
+                value.text().collect::<String>()
+            )));
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Assign a tariff row to the matching field; the first match for each field wins
+fn apply_tariff_row(tariff: &mut TariffTransparency, row: &str) {
+    let lower = row.to_lowercase();
+
+    if let Some(speed) = parse_speed(&lower) {
+        // German: "Geschätzte maximale Geschwindigkeit", English: "Estimated maximum speed"
+        let estimated = lower.contains("geschätzt") || lower.contains("estimated");
+
+        let slot = if lower.contains("download") || lower.contains("herunterladen") {
+            if estimated {
+                &mut tariff.estimated_download_mbps
+            } else {
+                &mut tariff.max_download_mbps
+            }
+        } else if lower.contains("upload") || lower.contains("hochladen") {
+            if estimated {
+                &mut tariff.estimated_upload_mbps
+            } else {
+                &mut tariff.max_upload_mbps
+            }
+        } else {
+            return;
+        };
+
+        slot.get_or_insert(speed);
+    } else if tariff.price.is_none()
+        && (lower.contains("preis") || lower.contains("price"))
+        && row.contains('€')
+    {
+        // Keep everything from the amount onwards, e.g. "9,95 € / 4 Wochen"
+        if let Some(start) = row.find(|c: char| c.is_ascii_digit()) {
+            tariff.price = Some(row[start..].trim().to_string());
+        }
+    }
+}
+
+/// Find the first speed value (e.g. "300 Mbit/s", "64 kbit/s") and normalize it to Mbit/s
+fn parse_speed(text: &str) -> Option<f64> {
+    let text = text.to_lowercase();
+    let unit_pos = text.find("bit/s")?;
+
+    let (factor, number_end) = match text[..unit_pos].chars().last()? {
+        'k' => (0.001, unit_pos - 1),
+        'm' => (1.0, unit_pos - 1),
+        'g' => (1000.0, unit_pos - 1),
+        _ => (0.000_001, unit_pos),
+    };
+
+    let number: String = text[..number_end]
+        .trim_end()
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_digit() || *c == ',' || *c == '.')
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    parse_number(&number).ok().map(|value| value * factor)
+}
+
+/// Parse a date as shown on the page: "21. Februar 2026", "12. February 2026" or "21.02.2026"
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    let parts: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == '.' || c == ',')
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    if parts.len() < 3 {
+        return None;
+    }
+
+    let day: u32 = parts[0].parse().ok()?;
+    let month = parts[1]
+        .parse::<u32>()
+        .ok()
+        .or_else(|| parse_month_name(parts[1]))?;
+    let year: i32 = parts[2].parse().ok()?;
+
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+/// Map German or English month names (full or abbreviated) to month numbers
+fn parse_month_name(name: &str) -> Option<u32> {
+    let prefix: String = name.to_lowercase().chars().take(3).collect();
+    let month = match prefix.as_str() {
+        "jan" | "jän" => 1,
+        "feb" => 2,
+        "mar" | "mär" | "mae" => 3,
+        "apr" => 4,
+        "may" | "mai" => 5,
+        "jun" => 6,
+        "jul" => 7,
+        "aug" => 8,
+        "sep" => 9,
+        "oct" | "okt" => 10,
+        "nov" => 11,
+        "dec" | "dez" => 12,
+        _ => return None,
+    };
+
+    Some(month)
+}
+
+/// Collapse whitespace (including non-breaking spaces) into single spaces
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Error returned when the page asks for a Telekom mobile data connection
+fn auth_required_error() -> DatapassError {
+    DatapassError::DataNotFound(
+        "Access denied. This website requires an active Telekom mobile data connection.\n  \
+        \nTo test without Telekom network, use: --file <saved-html-file>"
+            .to_string(),
+    )
+}
+
 /// Parse a number string, handling both German (comma) and English (period) decimal formats
 fn parse_number(s: &str) -> Result<f64> {
     // Replace German decimal comma with English period
@@ -278,6 +735,7 @@ mod tests {
         let data = result.unwrap();
         assert!(data.is_unlimited, "Plan should be marked as unlimited");
         assert_eq!(data.plan_name, Some("MagentaMobil Prepaid Max".to_string()));
+        assert_eq!(data.pass_name, Some("MagentaMobil Prepaid Max".to_string()));
         assert_eq!(data.valid_until, Some("27. February 2026".to_string()));
     }
 
@@ -316,4 +774,204 @@ mod tests {
         assert!(data.is_unlimited, "Plan should be marked as unlimited");
         assert_eq!(data.plan_name, Some("MagentaMobil Prepaid Max".to_string()));
     }
+
+    #[test]
+    fn test_parse_tariff_transparency_german() {
+        let html = r#"
+            <!DOCTYPE html>
+            <html>
+            <head>
+                <title>Tarifinformationen - MagentaMobil Prepaid L</title>
+            </head>
+            <body>
+                <table>
+                    <tr><th>Maximale Download-Geschwindigkeit</th><td>300 Mbit/s</td></tr>
+                    <tr><th>Geschätzte maximale Download-Geschwindigkeit</th><td>150,5 Mbit/s</td></tr>
+                    <tr><th>Maximale Upload-Geschwindigkeit</th><td>50 Mbit/s</td></tr>
+                    <tr><th>Geschätzte maximale Upload-Geschwindigkeit</th><td>512 kbit/s</td></tr>
+                    <tr><th>Grundpreis</th><td>9,95&nbsp;€ / 4 Wochen</td></tr>
+                </table>
+            </body>
+            </html>
+        "#;
+
+        let tariff = parse_tariff_transparency(html).expect("Failed to parse tariff page");
+
+        assert_eq!(tariff.plan_name, Some("MagentaMobil Prepaid L".to_string()));
+        assert_eq!(tariff.max_download_mbps, Some(300.0));
+        assert_eq!(tariff.estimated_download_mbps, Some(150.5));
+        assert_eq!(tariff.max_upload_mbps, Some(50.0));
+        assert_eq!(tariff.estimated_upload_mbps, Some(0.512));
+        assert_eq!(tariff.price, Some("9,95 € / 4 Wochen".to_string()));
+    }
+
+    #[test]
+    fn test_parse_tariff_transparency_definition_list() {
+        let html = r#"
+            <!DOCTYPE html>
+            <html>
+            <head>
+                <title>Tariff transparency</title>
+            </head>
+            <body>
+                <dl>
+                    <dt>Estimated maximum download speed</dt>
+                    <dd>21.6 Mbit/s</dd>
+                    <dt>Maximum upload speed</dt>
+                    <dd>1 Gbit/s</dd>
+                </dl>
+            </body>
+            </html>
+        "#;
+
+        let tariff = parse_tariff_transparency(html).expect("Failed to parse tariff page");
+
+        assert_eq!(tariff.plan_name, None);
+        assert_eq!(tariff.estimated_download_mbps, Some(21.6));
+        assert_eq!(tariff.max_download_mbps, None);
+        assert_eq!(tariff.max_upload_mbps, Some(1000.0));
+        assert_eq!(tariff.price, None);
+    }
+
+    #[test]
+    fn test_parse_tariff_transparency_missing_speeds() {
+        let html = r#"
+            <!DOCTYPE html>
+            <html>
+            <head><title>Tarifinformationen - Test</title></head>
+            <body><p>Keine Angaben</p></body>
+            </html>
+        "#;
+
+        assert!(parse_tariff_transparency(html).is_err());
+    }
+
+    #[test]
+    fn test_parse_offers() {
+        let html = r#"
+            <!DOCTYPE html>
+            <html>
+            <head><title>Datennutzung - MagentaMobil Prepaid L</title></head>
+            <body>
+                <div class="pass-offer-card">
+                    <div class="pass-offer-card__content">
+                        <div class="pass-offer-card__headline"><h3>DayFlat unlimited
+                        </h3></div>
+                        <div class="volume unlimited">unbegrenzt</div>
+                        <div class="description"><span class="hours">24</span>&nbsp;Stunden gültig</div>
+                        <div class="price"><span><span class="price">6,<span class="fraction">95</span><span class="currency">&#160;&euro;</span></span></span></div>
+                    </div>
+                    <a href="/pass/select/GDPXDF13?crt=46d86a43" class="pass-offer-card__call-to-action-link">Auswählen</a>
+                </div>
+                <div class="pass-offer-card option-ad">
+                    <div class="pass-offer-card__headline"><h3>Roaming</h3></div>
+                    <a href="/reservation" class="pass-offer-card__call-to-action-link">Anzeigen</a>
+                </div>
+                <div class="pass-offer-card">
+                    <div class="pass-offer-card__headline"><h3>SpeedOn M</h3></div>
+                    <div class="volume ">3<span class="unit">GB</span></div>
+                    <div class="description">gültig bis zum Ende des Abrechnungszyklus</div>
+                    <div class="price"><span><span class="price">14,<span class="fraction">95</span><span class="currency">&#160;&euro;</span></span></span></div>
+                </div>
+            </body>
+            </html>
+        "#;
+
+        let offers = parse_offers(html).expect("Failed to parse offers");
+        assert_eq!(offers.len(), 2, "Promotional card should be skipped");
+
+        let dayflat = &offers[0];
+        assert_eq!(dayflat.id, Some("GDPXDF13".to_string()));
+        assert_eq!(dayflat.name, "DayFlat unlimited");
+        assert!(dayflat.is_unlimited);
+        assert_eq!(dayflat.volume_gb, None);
+        assert_eq!(dayflat.price_eur, Some(6.95));
+        assert_eq!(dayflat.description, Some("24 Stunden gültig".to_string()));
+        assert_eq!(
+            dayflat.booking_path,
+            Some("/pass/select/GDPXDF13?crt=46d86a43".to_string())
+        );
+        assert!(dayflat.matches("gdpxdf13"));
+
+        let speedon = &offers[1];
+        assert_eq!(speedon.id, None);
+        assert_eq!(speedon.volume_gb, Some(3.0));
+        assert_eq!(speedon.price_eur, Some(14.95));
+        assert!(!speedon.is_bookable());
+        assert!(speedon.matches("speedon m"));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let expected = NaiveDate::from_ymd_opt(2026, 2, 21);
+        assert_eq!(parse_date("21. Februar 2026"), expected);
+        assert_eq!(parse_date("21. February 2026"), expected);
+        assert_eq!(parse_date("21.02.2026"), expected);
+        assert_eq!(
+            parse_date("3. März 2026"),
+            NaiveDate::from_ymd_opt(2026, 3, 3)
+        );
+        assert_eq!(parse_date("31. Februar 2026"), None);
+        assert_eq!(parse_date("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_passes_and_last_update() {
+        let html = r#"
+            <html>
+            <head><title>Data usage - MagentaMobil Prepaid L</title></head>
+            <body>
+                <section class="data-pass-instance" id="summationPass">
+                    <div class="remaining-volume-value">2.88</div>
+                    <div class="start-volume">26</div>
+                </section>
+                <section class="data-pass-instance" id="pass-4207738b0600000a">
+                    <div class="data-pass-instance__headline"><h2>MagentaMobil Prepaid L</h2></div>
+                    <div class="remaining-volume-value">2.88</div>
+                    <div class="start-volume">25</div>
+                    <div class="info-row">Valid until: 12. February 2026</div>
+                    <div class="info-row">
+                        Valid in <a href="/zoneInfo">Germany, Country group 1</a>
+                    </div>
+                </section>
+                <section class="data-pass-instance collapse" id="pass-3ea510781880400a">
+                    <div class="data-pass-instance__headline"><h2>Datenbonus</h2></div>
+                    <div class="volume-ssd">Volume exhausted</div>
+                </section>
+                <div class="last-update">
+                    <div class="hover-anker">
+                        <span class="tooltip-item"><label>Last update:</label></span>
+                        <span class="hover-box">The displayed information is subject to a time delay.</span>
+                    </div>
+                    24.01.2026 at 13:47
+                </div>
+            </body>
+            </html>
+        "#;
+
+        let passes = parse_passes(html).unwrap();
+        assert_eq!(passes.len(), 2);
+
+        assert_eq!(passes[0].id, Some("pass-4207738b0600000a".to_string()));
+        assert_eq!(passes[0].name, Some("MagentaMobil Prepaid L".to_string()));
+        assert_eq!(passes[0].remaining_gb, Some(2.88));
+        assert_eq!(passes[0].total_gb, Some(25.0));
+        assert_eq!(passes[0].valid_until, Some("12. February 2026".to_string()));
+        assert_eq!(passes[0].zone, Some("Germany, Country group 1".to_string()));
+        assert!(!passes[0].is_exhausted);
+
+        assert_eq!(passes[1].name, Some("Datenbonus".to_string()));
+        assert_eq!(passes[1].remaining_gb, None);
+        assert_eq!(passes[1].zone, None);
+        assert!(passes[1].is_exhausted);
+
+        let usage = parse_html(html).unwrap();
+        assert_eq!(usage.pass_name, Some("MagentaMobil Prepaid L".to_string()));
+        assert_eq!(usage.zone, Some("Germany, Country group 1".to_string()));
+
+        assert_eq!(
+            parse_last_update(html),
+            Some("24.01.2026 at 13:47".to_string())
+        );
+    }
 }
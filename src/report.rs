@@ -0,0 +1,291 @@
+use crate::cycle::{self, Cycle, HistoryEvent};
+use crate::history::HistoryEntry;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike, Utc};
+use serde::Serialize;
+
+/// Length of the periods a report is broken down into (local time)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Bucket {
+    Hour,
+    Day,
+    /// Weeks starting on Monday
+    Week,
+}
+
+impl Bucket {
+    /// Start of the period containing `time`
+    fn start_of(self, time: DateTime<Utc>) -> NaiveDateTime {
+        let local = time.with_timezone(&Local).naive_local();
+        let hour = local
+            .with_minute(0)
+            .and_then(|t| t.with_second(0))
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(local);
+        let midnight = local.date().and_hms_opt(0, 0, 0).unwrap_or(local);
+
+        match self {
+            Bucket::Hour => hour,
+            Bucket::Day => midnight,
+            Bucket::Week => {
+                midnight - Duration::days(local.weekday().num_days_from_monday() as i64)
+            }
+        }
+    }
+
+    fn length(self) -> Duration {
+        match self {
+            Bucket::Hour => Duration::hours(1),
+            Bucket::Day => Duration::days(1),
+            Bucket::Week => Duration::weeks(1),
+        }
+    }
+
+    fn label(self, start: NaiveDateTime) -> String {
+        match self {
+            Bucket::Hour => start.format("%Y-%m-%d %H:00").to_string(),
+            Bucket::Day => start.format("%Y-%m-%d").to_string(),
+            Bucket::Week => start.format("%G-W%V").to_string(),
+        }
+    }
+}
+
+/// Data used in one period
+#[derive(Debug, Clone, Serialize)]
+pub struct UsagePeriod {
+    /// Period name, e.g. "2026-10-18", "2026-10-18 08:00" or "2026-W42"
+    pub period: String,
+
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+
+    /// Data used in the period in GB
+    pub used_gb: f64,
+
+    /// Readings recorded in the period
+    pub readings: usize,
+
+    /// Cycles started in the period (renewals and plan changes)
+    pub renewals: usize,
+
+    /// Times volume was added by a booking (the total went up)
+    pub bookings: usize,
+}
+
+/// Break the consumption between consecutive readings (in chronological
+/// order) down into periods. Consumption between two readings is spread
+/// evenly over the time between them. Only periods overlapping
+/// `since..until` are returned.
+pub fn usage_by(
+    entries: &[HistoryEntry],
+    bucket: Bucket,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Vec<UsagePeriod> {
+    breakdown(entries, 0, bucket)
+        .into_iter()
+        .filter(|period| since.map_or(true, |since| period.end > since))
+        .filter(|period| until.map_or(true, |until| period.start < until))
+        .collect()
+}
+
+/// Break the consumption in one cycle down into periods. What was used
+/// between the last reading of the previous cycle and the first of this one
+/// counts towards this cycle.
+pub fn cycle_usage_by(entries: &[HistoryEntry], cycle: &Cycle, bucket: Bucket) -> Vec<UsagePeriod> {
+    let from = cycle.first.saturating_sub(1);
+    breakdown(&entries[from..=cycle.last], cycle.first - from, bucket)
+}
+
+/// Periods covering `entries`, with the readings from `first` on and the
+/// consumption up to them
+fn breakdown(entries: &[HistoryEntry], first: usize, bucket: Bucket) -> Vec<UsagePeriod> {
+    let (Some(start), Some(last)) = (entries.first(), entries.last()) else {
+        return Vec::new();
+    };
+
+    let mut periods = Vec::new();
+    let mut start = bucket.start_of(start.timestamp);
+    let last_start = bucket.start_of(last.timestamp);
+    while start <= last_start {
+        let end = start + bucket.length();
+        periods.push(UsagePeriod {
+            period: bucket.label(start),
+            start: to_utc(start),
+            end: to_utc(end),
+            used_gb: 0.0,
+            readings: 0,
+            renewals: 0,
+            bookings: 0,
+        });
+        start = end;
+    }
+
+    for (index, entry) in entries.iter().enumerate().skip(first) {
+        let period = period_index(&periods, entry.timestamp);
+        periods[period].readings += 1;
+
+        let Some(before) = index.checked_sub(1).map(|previous| &entries[previous]) else {
+            continue;
+        };
+
+        match cycle::detect(&before.usage, &entry.usage) {
+            Some(HistoryEvent::CycleStarted { .. }) => periods[period].renewals += 1,
+            Some(HistoryEvent::PassBooked { .. }) => periods[period].bookings += 1,
+            None => {}
+        }
+
+        spread(
+            &mut periods,
+            before.timestamp,
+            entry.timestamp,
+            cycle::consumption(&before.usage, &entry.usage),
+        );
+    }
+
+    periods
+}
+
+/// Index of the period containing `time`
+fn period_index(periods: &[UsagePeriod], time: DateTime<Utc>) -> usize {
+    periods
+        .iter()
+        .rposition(|period| period.start <= time)
+        .unwrap_or(0)
+}
+
+/// Add `used_gb`, used evenly between `from` and `to`, to the overlapping periods
+fn spread(periods: &mut [UsagePeriod], from: DateTime<Utc>, to: DateTime<Utc>, used_gb: f64) {
+    let total = (to - from).num_milliseconds() as f64;
+
+    for period in periods.iter_mut() {
+        if total <= 0.0 {
+            // Readings at the same time: book it where they were taken
+            if period.start <= to && to < period.end {
+                period.used_gb += used_gb;
+            }
+            continue;
+        }
+
+        let overlap = (to.min(period.end) - from.max(period.start)).num_milliseconds() as f64;
+        if overlap > 0.0 {
+            period.used_gb += used_gb * overlap / total;
+        }
+    }
+}
+
+/// A local time as UTC; skipped local times (DST) move to the next valid one
+fn to_utc(local: NaiveDateTime) -> DateTime<Utc> {
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DataUsage;
+
+    fn entry(
+        time: DateTime<Utc>,
+        remaining_gb: f64,
+        total_gb: f64,
+        valid_until: &str,
+    ) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: time,
+            usage: DataUsage::new(remaining_gb, total_gb, None, Some(valid_until.to_string())),
+            passes: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    fn local(day: u32, hour: u32) -> DateTime<Utc> {
+        Local
+            .with_ymd_and_hms(2026, 10, day, hour, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_daily_usage_is_spread_over_the_gap() {
+        let entries = [
+            entry(local(1, 12), 50.0, 51.0, "31. October 2026"),
+            // 2 GB used between noon on day 1 and noon on day 2
+            entry(local(2, 12), 48.0, 51.0, "31. October 2026"),
+            entry(local(2, 18), 47.0, 51.0, "31. October 2026"),
+        ];
+
+        let days = usage_by(&entries, Bucket::Day, None, None);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].period, "2026-10-01");
+        assert!((days[0].used_gb - 1.0).abs() < 1e-9);
+        assert!((days[1].used_gb - 2.0).abs() < 1e-9);
+        assert_eq!(days[1].readings, 2);
+    }
+
+    #[test]
+    fn test_renewal_and_booking() {
+        let entries = [
+            entry(local(1, 10), 1.0, 51.0, "1. October 2026"),
+            // Renewed with 51 GB, 0.5 GB used since
+            entry(local(1, 11), 50.5, 51.0, "31. October 2026"),
+            // 1 GB used, then a 5 GB pass was booked
+            entry(local(1, 12), 54.5, 56.0, "31. October 2026"),
+        ];
+
+        let hours = usage_by(&entries, Bucket::Hour, None, None);
+        assert_eq!(hours.len(), 3);
+        assert_eq!(hours[1].renewals, 1);
+        assert!((hours[0].used_gb - 0.5).abs() < 1e-9);
+        assert_eq!(hours[2].bookings, 1);
+        assert!((hours[1].used_gb - 1.0).abs() < 1e-9);
+        assert!(hours.iter().all(|hour| hour.used_gb >= 0.0));
+    }
+
+    #[test]
+    fn test_cycle_breakdown() {
+        let entries = [
+            entry(local(1, 10), 1.0, 51.0, "1. October 2026"),
+            entry(local(2, 10), 50.0, 51.0, "31. October 2026"),
+            entry(local(3, 10), 48.0, 51.0, "31. October 2026"),
+        ];
+        let cycles = cycle::cycles(&entries);
+
+        let days = cycle_usage_by(&entries, &cycles[1], Bucket::Day);
+        let used: f64 = days.iter().map(|day| day.used_gb).sum();
+        assert!((used - 3.0).abs() < 1e-9);
+        assert_eq!(days.iter().map(|day| day.readings).sum::<usize>(), 2);
+
+        assert!(cycle_usage_by(&entries, &cycles[0], Bucket::Day)
+            .iter()
+            .all(|day| day.used_gb == 0.0));
+    }
+
+    #[test]
+    fn test_range_filter_and_weeks() {
+        let entries = [
+            entry(local(1, 12), 50.0, 51.0, "31. October 2026"),
+            entry(local(20, 12), 30.0, 51.0, "31. October 2026"),
+        ];
+
+        let weeks = usage_by(&entries, Bucket::Week, None, None);
+        assert_eq!(weeks[0].period, "2026-W40");
+        assert_eq!(weeks.len(), 4);
+
+        let filtered = usage_by(
+            &entries,
+            Bucket::Day,
+            Some(local(10, 0)),
+            Some(local(12, 0)),
+        );
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].period, "2026-10-10");
+    }
+}
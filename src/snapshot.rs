@@ -0,0 +1,183 @@
+use crate::config::state_dir;
+use crate::error::Result;
+use crate::source::Source;
+use crate::types::{DataUsage, Staleness};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The last successfully fetched reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// When the reading was fetched
+    pub fetched_at: DateTime<Utc>,
+
+    /// The reading itself
+    pub usage: DataUsage,
+}
+
+impl Snapshot {
+    /// Time since the reading was fetched
+    pub fn age(&self, now: DateTime<Utc>) -> Duration {
+        (now - self.fetched_at).to_std().unwrap_or(Duration::ZERO)
+    }
+
+    /// The reading, marked as stale
+    pub fn into_stale_usage(self, now: DateTime<Utc>) -> DataUsage {
+        let age_seconds = self.age(now).as_secs();
+        DataUsage {
+            stale: Some(Staleness {
+                fetched_at: self.fetched_at,
+                age_seconds,
+            }),
+            ..self.usage
+        }
+    }
+}
+
+/// Keeps the last good reading on disk
+/// (default: `$XDG_STATE_HOME/datapass/last-usage.json`)
+pub struct SnapshotStore {
+    path: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Default snapshot location
+    pub fn default_path() -> PathBuf {
+        state_dir().join("last-usage.json")
+    }
+
+    /// The stored snapshot, if there is a readable one
+    pub fn load(&self) -> Option<Snapshot> {
+        let text = std::fs::read_to_string(&self.path).ok()?;
+
+        match serde_json::from_str(&text) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                log::warn!(
+                    "Ignoring unreadable snapshot {}: {}",
+                    self.path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Replace the stored snapshot with a fresh reading
+    pub fn save(&self, usage: &DataUsage, fetched_at: DateTime<Utc>) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let snapshot = Snapshot {
+            fetched_at,
+            usage: DataUsage {
+                stale: None,
+                ..usage.clone()
+            },
+        };
+
+        let temp_path = self
+            .path
+            .with_extension(format!("tmp.{}", std::process::id()));
+        std::fs::write(&temp_path, serde_json::to_string_pretty(&snapshot)?)?;
+        std::fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+
+    /// Fetch the data usage from `source` and store it. With `allow_stale`,
+    /// a failed fetch falls back to a stored reading up to that old.
+    pub fn fetch_usage(
+        &self,
+        source: &mut dyn Source,
+        allow_stale: Option<Duration>,
+    ) -> Result<DataUsage> {
+        match source.fetch_usage() {
+            Ok(data) => {
+                if let Err(e) = self.save(&data, Utc::now()) {
+                    log::warn!("Failed to store the last reading: {}", e);
+                }
+                Ok(data)
+            }
+            Err(e) => match allow_stale.and_then(|max_age| self.stale_usage(max_age, Utc::now())) {
+                Some(data) => {
+                    log::warn!("Fetch failed ({}), using the last stored reading", e);
+                    Ok(data)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// The stored reading, marked stale, if it is not older than `max_age`
+    pub fn stale_usage(&self, max_age: Duration, now: DateTime<Utc>) -> Option<DataUsage> {
+        self.load()
+            .filter(|snapshot| snapshot.age(now) <= max_age)
+            .map(|snapshot| snapshot.into_stale_usage(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_usage_respects_max_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::new(dir.path().join("last-usage.json"));
+        let fetched_at = Utc::now() - chrono::Duration::hours(2);
+
+        assert!(store
+            .stale_usage(Duration::from_secs(3600), Utc::now())
+            .is_none());
+
+        store
+            .save(&DataUsage::new(38.36, 51.0, None, None), fetched_at)
+            .unwrap();
+
+        let now = fetched_at + chrono::Duration::hours(2);
+        assert!(store.stale_usage(Duration::from_secs(3600), now).is_none());
+
+        let usage = store
+            .stale_usage(Duration::from_secs(3 * 3600), now)
+            .unwrap();
+        assert_eq!(usage.remaining_gb, 38.36);
+        let stale = usage.stale.unwrap();
+        assert_eq!(stale.fetched_at, fetched_at);
+        assert_eq!(stale.age_seconds, 7200);
+    }
+
+    #[test]
+    fn test_fetch_usage_falls_back_when_allowed() {
+        use crate::error::DatapassError;
+        use crate::source::MemorySource;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::new(dir.path().join("last-usage.json"));
+        let page = r#"<html><head><title>Data usage - Prepaid XL</title></head><body>
+            <section class="data-pass-instance">
+                <div class="remaining-volume-value">38,36</div>
+                <div class="start-volume">51</div>
+            </section></body></html>"#;
+        let offline = || Err(DatapassError::DataNotFound("offline".to_string()));
+        let mut source = MemorySource::scripted([Ok(page.to_string()), offline(), offline()]);
+
+        let fresh = store.fetch_usage(&mut source, None).unwrap();
+        assert!(!fresh.is_stale());
+
+        assert!(store.fetch_usage(&mut source, None).is_err());
+
+        let stale = store
+            .fetch_usage(&mut source, Some(Duration::from_secs(60)))
+            .unwrap();
+        assert!(stale.is_stale());
+        assert_eq!(stale.remaining_gb, 38.36);
+    }
+}
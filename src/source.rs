@@ -0,0 +1,197 @@
+#[cfg(feature = "http")]
+use crate::blocking;
+#[cfg(feature = "http")]
+use crate::cache::ResponseCache;
+#[cfg(feature = "http")]
+use crate::client::ClientBuilder;
+use crate::error::{DatapassError, Result};
+use crate::parser;
+use crate::types::DataUsage;
+use std::collections::VecDeque;
+use std::io::Read;
+#[cfg(feature = "http")]
+use std::time::Duration;
+
+/// Where the datapass page comes from. Implement this to feed the parser
+/// from anywhere, e.g. a fake that returns a scripted series of pages.
+pub trait Source {
+    /// Get the current page
+    fn fetch(&mut self) -> Result<String>;
+
+    /// Get the current page and parse the data usage
+    fn fetch_usage(&mut self) -> Result<DataUsage> {
+        parser::parse_html(&self.fetch()?)
+    }
+}
+
+impl<S: Source + ?Sized> Source for Box<S> {
+    fn fetch(&mut self) -> Result<String> {
+        (**self).fetch()
+    }
+
+    fn fetch_usage(&mut self) -> Result<DataUsage> {
+        (**self).fetch_usage()
+    }
+}
+
+/// Fetches the page over HTTP with a [`blocking::DatapassClient`], which is
+/// created on the first fetch and then reused
+#[cfg(feature = "http")]
+pub struct HttpSource {
+    builder: ClientBuilder,
+    client: Option<blocking::DatapassClient>,
+}
+
+#[cfg(feature = "http")]
+impl HttpSource {
+    /// Fetch from the given URL (default: datapass.de) with the given
+    /// cookies (default: the Apollo language cookies)
+    pub fn new(url: Option<&str>, cookie: Option<&str>) -> Self {
+        let mut builder = ClientBuilder::new();
+        if let Some(url) = url {
+            builder = builder.url(url);
+        }
+        if let Some(cookie) = cookie {
+            builder = builder.cookie(cookie);
+        }
+        Self::from_builder(builder)
+    }
+
+    /// Fetch with a client configured by `builder`
+    pub fn from_builder(builder: ClientBuilder) -> Self {
+        Self {
+            builder,
+            client: None,
+        }
+    }
+
+    /// Revalidate through `cache`, serving pages younger than `max_age`
+    /// without a request
+    pub fn with_cache(mut self, cache: ResponseCache, max_age: Option<Duration>) -> Self {
+        self.builder = self.builder.cache(cache);
+        if let Some(max_age) = max_age {
+            self.builder = self.builder.max_age(max_age);
+        }
+        self
+    }
+}
+
+#[cfg(feature = "http")]
+impl Source for HttpSource {
+    fn fetch(&mut self) -> Result<String> {
+        let client = match &mut self.client {
+            Some(client) => client,
+            client @ None => client.insert(self.builder.clone().build_blocking()?),
+        };
+        client.fetch_html()
+    }
+}
+
+/// Reads the page from a saved HTML file on every fetch
+pub struct FileSource {
+    path: String,
+}
+
+impl FileSource {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+        }
+    }
+}
+
+impl Source for FileSource {
+    fn fetch(&mut self) -> Result<String> {
+        log::info!("Reading HTML from local file: {}", self.path);
+        Ok(std::fs::read_to_string(&self.path)?)
+    }
+}
+
+/// Reads the page from standard input. Stdin can only be read once, so
+/// later fetches return the same page.
+#[derive(Default)]
+pub struct StdinSource {
+    page: Option<String>,
+}
+
+impl StdinSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Source for StdinSource {
+    fn fetch(&mut self) -> Result<String> {
+        if let Some(page) = &self.page {
+            return Ok(page.clone());
+        }
+
+        log::info!("Reading HTML from stdin");
+        let mut html = String::new();
+        std::io::stdin().read_to_string(&mut html)?;
+
+        Ok(self.page.insert(html).clone())
+    }
+}
+
+/// Serves pages from memory: either one page on every fetch, or a scripted
+/// series of pages and errors. The last scripted page keeps being served
+/// once the script runs out.
+pub struct MemorySource {
+    pages: VecDeque<Result<String>>,
+}
+
+impl MemorySource {
+    /// Serve the same page on every fetch
+    pub fn new(html: &str) -> Self {
+        Self::scripted([Ok(html.to_string())])
+    }
+
+    /// Serve the given pages (or errors) in order
+    pub fn scripted<I>(pages: I) -> Self
+    where
+        I: IntoIterator<Item = Result<String>>,
+    {
+        Self {
+            pages: pages.into_iter().collect(),
+        }
+    }
+}
+
+impl Source for MemorySource {
+    fn fetch(&mut self) -> Result<String> {
+        match self.pages.front() {
+            Some(Ok(page)) if self.pages.len() == 1 => Ok(page.clone()),
+            Some(_) => self.pages.pop_front().unwrap(),
+            None => Err(DatapassError::SourceExhausted),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_source_script() {
+        let mut source = MemorySource::scripted([
+            Ok("first".to_string()),
+            Err(DatapassError::DataNotFound("offline".to_string())),
+            Ok("last".to_string()),
+        ]);
+
+        assert_eq!(source.fetch().unwrap(), "first");
+        assert!(source.fetch().is_err());
+        assert_eq!(source.fetch().unwrap(), "last");
+        assert_eq!(source.fetch().unwrap(), "last");
+    }
+
+    #[test]
+    fn test_empty_memory_source() {
+        let mut source = MemorySource::scripted([]);
+        assert!(matches!(
+            source.fetch(),
+            Err(DatapassError::SourceExhausted)
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::error::{DatapassError, Result};
+use crate::types::{DataUsage, BYTES_PER_GB};
+use serde::Deserialize;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+/// Sending readings as StatsD gauges over UDP (`[statsd]` config section),
+/// in watch and daemon mode
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatsdConfig {
+    /// Send; the section being present enables it
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// StatsD server (or Telegraf's statsd input) host name or address
+    #[serde(default = "default_host")]
+    pub host: String,
+
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    /// Metrics are named `<prefix>.remaining_bytes` and so on
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    8125
+}
+
+fn default_prefix() -> String {
+    "datapass".to_string()
+}
+
+impl StatsdConfig {
+    /// Full name of a metric, e.g. "datapass.remaining_bytes"
+    pub fn metric(&self, name: &str) -> String {
+        match self.prefix.trim_end_matches('.') {
+            "" => name.to_string(),
+            prefix => format!("{}.{}", prefix, name),
+        }
+    }
+}
+
+/// The gauges sent for a reading, one `name:value|g` line each
+pub fn gauges(config: &StatsdConfig, data: &DataUsage) -> Vec<String> {
+    let mut values = Vec::new();
+    if !data.is_unlimited {
+        values.push((
+            "remaining_bytes",
+            ((data.remaining_gb * BYTES_PER_GB).round() as i64).to_string(),
+        ));
+        values.push((
+            "used_bytes",
+            ((data.used_gb * BYTES_PER_GB).round() as i64).to_string(),
+        ));
+        values.push((
+            "total_bytes",
+            ((data.total_gb * BYTES_PER_GB).round() as i64).to_string(),
+        ));
+        values.push(("used_ratio", (data.percentage / 100.0).to_string()));
+    }
+    values.push(("unlimited", u8::from(data.is_unlimited).to_string()));
+    if let Some(valid_until) = data.valid_until_end() {
+        values.push((
+            "valid_until_timestamp_seconds",
+            valid_until.timestamp().to_string(),
+        ));
+    }
+
+    values
+        .into_iter()
+        .map(|(name, value)| format!("{}:{}|g", config.metric(name), value))
+        .collect()
+}
+
+/// Sends gauges for every fresh reading and counts failed fetches
+pub struct StatsdEmitter {
+    config: StatsdConfig,
+    socket: UdpSocket,
+}
+
+impl StatsdEmitter {
+    /// Resolve the server once and open a socket to it
+    pub fn new(config: StatsdConfig) -> Result<Self> {
+        let address = (config.host.as_str(), config.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| {
+                DatapassError::ConfigError(format!("Cannot resolve StatsD host {}", config.host))
+            })?;
+
+        let socket = UdpSocket::bind(if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        })?;
+        socket.connect(address)?;
+        log::info!("Sending StatsD gauges to {}", address);
+
+        Ok(Self { config, socket })
+    }
+
+    /// Send the gauges of a reading in one datagram
+    pub fn send(&self, data: &DataUsage) {
+        self.send_lines(&gauges(&self.config, data));
+    }
+
+    /// Count a failed fetch (`<prefix>.fetch_errors`)
+    pub fn count_error(&self) {
+        self.send_lines(&[format!("{}:1|c", self.config.metric("fetch_errors"))]);
+    }
+
+    // UDP gives no delivery guarantee anyway, so a failed send is only logged
+    fn send_lines(&self, lines: &[String]) {
+        if let Err(e) = self.socket.send(lines.join("\n").as_bytes()) {
+            log::warn!("Sending to StatsD failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(prefix: &str) -> StatsdConfig {
+        toml::from_str(&format!("prefix = \"{}\"", prefix)).unwrap()
+    }
+
+    #[test]
+    fn test_gauges() {
+        let data = DataUsage::new(2.5, 51.0, None, Some("18.11.2026".to_string()));
+        let gauges = gauges(&config("sim1.datapass"), &data);
+
+        assert_eq!(gauges[0], "sim1.datapass.remaining_bytes:2500000000|g");
+        assert_eq!(gauges[2], "sim1.datapass.total_bytes:51000000000|g");
+        assert_eq!(gauges[4], "sim1.datapass.unlimited:0|g");
+        assert!(gauges[5].starts_with("sim1.datapass.valid_until_timestamp_seconds:"));
+        assert_eq!(gauges.len(), 6);
+    }
+
+    #[test]
+    fn test_unlimited_gauges() {
+        let data = DataUsage::new_unlimited(None, None);
+        assert_eq!(gauges(&config(""), &data), ["unlimited:1|g"]);
+    }
+}
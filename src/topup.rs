@@ -0,0 +1,512 @@
+use crate::booking::{BookingSession, BookingStatus};
+use crate::config;
+use crate::error::Result;
+use crate::types::DataUsage;
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+const AUDIT_LOG_FILE: &str = "topup-audit.jsonl";
+
+/// Automatic top-up policy (`[topup]` config section): book a pass when the
+/// remaining volume runs low, within per-cycle count and spending limits
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TopUpPolicy {
+    /// The policy only runs when explicitly enabled
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Offer ID or name to book (e.g. "SpeedOn M")
+    pub offer: String,
+
+    /// Book when less than this many MB remain
+    pub below_mb: f64,
+
+    /// Only book while more than this many days are left in the cycle
+    #[serde(default)]
+    pub min_days_left: i64,
+
+    /// Maximum number of bookings per billing cycle
+    #[serde(default = "default_max_per_cycle")]
+    pub max_per_cycle: u32,
+
+    /// Maximum total spend per billing cycle in EUR
+    pub max_spend_eur: f64,
+
+    /// Minimum time between two booking attempts, so the page can catch up
+    /// with the newly booked volume before the policy looks again
+    #[serde(default = "default_cooldown_minutes")]
+    pub cooldown_minutes: i64,
+
+    /// Walk through the booking flow without submitting the order
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// Audit log location (default: `$XDG_STATE_HOME/datapass/topup-audit.jsonl`)
+    pub audit_log: Option<PathBuf>,
+}
+
+fn default_max_per_cycle() -> u32 {
+    1
+}
+
+fn default_cooldown_minutes() -> i64 {
+    60
+}
+
+/// What the policy did about a reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopUpAction {
+    /// No booking was needed or allowed
+    Skipped,
+    /// The pass was booked
+    Booked,
+    /// The booking flow stopped before submission (`dry_run = true`)
+    DryRun,
+    /// The carrier rejected the booking
+    Rejected,
+    /// The booking was submitted but the result could not be confirmed
+    Unconfirmed,
+    /// The booking flow failed before the order was submitted
+    Failed,
+}
+
+impl TopUpAction {
+    /// Whether an order was submitted (and counts towards the cycle limits)
+    pub fn is_submitted(self) -> bool {
+        matches!(self, TopUpAction::Booked | TopUpAction::Unconfirmed)
+    }
+
+    /// Whether the booking flow was run, whatever its outcome (and starts
+    /// the cooldown)
+    pub fn is_attempt(self) -> bool {
+        self != TopUpAction::Skipped
+    }
+}
+
+/// One line of the top-up audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+
+    /// Billing cycle the decision belongs to (the `valid_until` date)
+    pub cycle: Option<String>,
+
+    /// Remaining volume at the time of the decision
+    pub remaining_gb: f64,
+
+    pub action: TopUpAction,
+
+    /// Configured offer
+    pub offer: String,
+
+    /// Price of the offer, once it was looked up
+    pub price_eur: Option<f64>,
+
+    /// Why the policy acted (or did not)
+    pub reason: String,
+}
+
+/// Outcome of evaluating the policy against a reading
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    /// Book now (subject to the offer's price fitting the remaining budget)
+    Book {
+        /// Budget left for this cycle in EUR
+        budget_eur: f64,
+    },
+    /// Do nothing, with the reason
+    Skip(String),
+}
+
+/// Decide whether the policy should book, given earlier audited decisions
+pub fn evaluate(
+    policy: &TopUpPolicy,
+    usage: &DataUsage,
+    history: &[AuditEntry],
+    now: DateTime<Utc>,
+    today: NaiveDate,
+) -> Decision {
+    if usage.is_unlimited {
+        return Decision::Skip("unlimited plan".to_string());
+    }
+
+    let remaining_mb = usage.remaining_gb * 1000.0;
+    if remaining_mb >= policy.below_mb {
+        // The remaining volume is in the audit entry; keeping it out of the
+        // reason lets repeated skips be recognized
+        return Decision::Skip(format!("above the {:.0} MB threshold", policy.below_mb));
+    }
+
+    match usage.days_left(today) {
+        None => return Decision::Skip("validity date unknown".to_string()),
+        Some(days) if days <= policy.min_days_left => {
+            return Decision::Skip(format!(
+                "{} days left, more than {} required",
+                days, policy.min_days_left
+            ));
+        }
+        Some(_) => {}
+    }
+
+    let submitted: Vec<&AuditEntry> = history
+        .iter()
+        .filter(|entry| entry.action.is_submitted() && entry.cycle == usage.valid_until)
+        .collect();
+
+    if submitted.len() >= policy.max_per_cycle as usize {
+        return Decision::Skip(format!(
+            "already booked {} of {} times this cycle",
+            submitted.len(),
+            policy.max_per_cycle
+        ));
+    }
+
+    // Rejected, failed and dry-run attempts don't count towards the limits,
+    // but are not repeated right away either
+    let last_attempt = history
+        .iter()
+        .filter(|entry| entry.action.is_attempt() && entry.cycle == usage.valid_until)
+        .map(|entry| entry.timestamp)
+        .max();
+
+    if let Some(last) = last_attempt {
+        if now - last < Duration::minutes(policy.cooldown_minutes) {
+            return Decision::Skip(format!(
+                "last attempt at {} is within the {} minute cooldown",
+                last.to_rfc3339(),
+                policy.cooldown_minutes
+            ));
+        }
+    }
+
+    let spent: f64 = submitted.iter().filter_map(|entry| entry.price_eur).sum();
+    let budget_eur = policy.max_spend_eur - spent;
+
+    if budget_eur <= 0.0 {
+        return Decision::Skip(format!(
+            "spent {:.2} € of {:.2} € this cycle",
+            spent, policy.max_spend_eur
+        ));
+    }
+
+    Decision::Book { budget_eur }
+}
+
+/// Append-only JSON Lines log of top-up decisions
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Default location: `$XDG_STATE_HOME/datapass/topup-audit.jsonl`
+    pub fn default_path() -> PathBuf {
+        config::state_dir().join(AUDIT_LOG_FILE)
+    }
+
+    /// Read all entries; a missing log is empty and unreadable lines are skipped
+    pub fn read(&self) -> Result<Vec<AuditEntry>> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let entries = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    log::warn!("Skipping malformed audit log line: {}", e);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Append one entry
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+
+        Ok(())
+    }
+}
+
+/// Runs the top-up policy against fresh readings and books through the
+/// regular booking flow
+pub struct TopUp {
+    policy: TopUpPolicy,
+    url: Option<String>,
+    cookie: Option<String>,
+    audit: AuditLog,
+}
+
+impl TopUp {
+    pub fn new(policy: TopUpPolicy, url: Option<&str>, cookie: Option<&str>) -> Self {
+        let audit_path = policy
+            .audit_log
+            .clone()
+            .unwrap_or_else(AuditLog::default_path);
+
+        Self {
+            policy,
+            url: url.map(str::to_string),
+            cookie: cookie.map(str::to_string),
+            audit: AuditLog::new(audit_path),
+        }
+    }
+
+    /// Evaluate the policy for a reading, book if needed and audit the
+    /// decision. A skip is only audited when its reason differs from the
+    /// last entry's, so polling does not grow the log with every reading.
+    pub fn check(&mut self, usage: &DataUsage) -> Result<AuditEntry> {
+        let history = self.audit.read()?;
+
+        let mut entry = AuditEntry {
+            timestamp: Utc::now(),
+            cycle: usage.valid_until.clone(),
+            remaining_gb: usage.remaining_gb,
+            action: TopUpAction::Skipped,
+            offer: self.policy.offer.clone(),
+            price_eur: None,
+            reason: String::new(),
+        };
+
+        match evaluate(
+            &self.policy,
+            usage,
+            &history,
+            entry.timestamp,
+            Local::now().date_naive(),
+        ) {
+            Decision::Skip(reason) => entry.reason = reason,
+            Decision::Book { budget_eur } => self.book(&mut entry, budget_eur),
+        }
+
+        let repeated = history.last().is_some_and(|last| {
+            entry.action == TopUpAction::Skipped
+                && last.action == TopUpAction::Skipped
+                && last.cycle == entry.cycle
+                && last.reason == entry.reason
+        });
+
+        if repeated {
+            log::debug!("Top-up decision unchanged: {}", entry.reason);
+        } else {
+            log::info!("Top-up decision: {:?} ({})", entry.action, entry.reason);
+            self.audit.append(&entry)?;
+        }
+
+        Ok(entry)
+    }
+
+    /// Run the booking flow; failures are recorded in the audit entry
+    fn book(&self, entry: &mut AuditEntry, budget_eur: f64) {
+        if let Err(e) = self.try_book(entry, budget_eur) {
+            entry.action = TopUpAction::Failed;
+            entry.reason = e.to_string();
+        }
+    }
+
+    /// Look up the offer, check it against the budget and book it
+    fn try_book(&self, entry: &mut AuditEntry, budget_eur: f64) -> Result<()> {
+        let mut session = BookingSession::new(self.url.as_deref(), self.cookie.as_deref())?;
+        let offer = session.find_offer(&self.policy.offer)?;
+        entry.price_eur = offer.price_eur;
+
+        match offer.price_eur {
+            Some(price) if price > budget_eur => {
+                entry.reason = format!(
+                    "price {:.2} € exceeds remaining budget {:.2} €",
+                    price, budget_eur
+                );
+                return Ok(());
+            }
+            None => {
+                entry.reason = "offer price unknown".to_string();
+                return Ok(());
+            }
+            Some(_) => {}
+        }
+
+        let booking = session.book(&offer, self.policy.dry_run)?;
+
+        entry.action = match booking.status {
+            BookingStatus::DryRun => TopUpAction::DryRun,
+            BookingStatus::Booked => TopUpAction::Booked,
+            BookingStatus::Rejected => TopUpAction::Rejected,
+            BookingStatus::Unconfirmed => TopUpAction::Unconfirmed,
+        };
+        entry.reason = booking
+            .message
+            .unwrap_or_else(|| "booking submitted".to_string());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> TopUpPolicy {
+        TopUpPolicy {
+            enabled: true,
+            offer: "SpeedOn M".to_string(),
+            below_mb: 500.0,
+            min_days_left: 3,
+            max_per_cycle: 2,
+            max_spend_eur: 30.0,
+            cooldown_minutes: 60,
+            dry_run: false,
+            audit_log: None,
+        }
+    }
+
+    fn usage(remaining_gb: f64) -> DataUsage {
+        DataUsage::new(
+            remaining_gb,
+            50.0,
+            Some("Test Plan".to_string()),
+            Some("21. Februar 2026".to_string()),
+        )
+    }
+
+    fn booked(timestamp: DateTime<Utc>, price: f64) -> AuditEntry {
+        AuditEntry {
+            timestamp,
+            cycle: Some("21. Februar 2026".to_string()),
+            remaining_gb: 0.2,
+            action: TopUpAction::Booked,
+            offer: "SpeedOn M".to_string(),
+            price_eur: Some(price),
+            reason: String::new(),
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, 10).unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-02-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_books_when_below_threshold() {
+        let decision = evaluate(&policy(), &usage(0.4), &[], now(), today());
+        assert_eq!(decision, Decision::Book { budget_eur: 30.0 });
+    }
+
+    #[test]
+    fn test_skips_above_threshold_and_unlimited() {
+        assert!(matches!(
+            evaluate(&policy(), &usage(0.6), &[], now(), today()),
+            Decision::Skip(_)
+        ));
+
+        let unlimited = DataUsage::new_unlimited(None, Some("21. Februar 2026".to_string()));
+        assert!(matches!(
+            evaluate(&policy(), &unlimited, &[], now(), today()),
+            Decision::Skip(_)
+        ));
+    }
+
+    #[test]
+    fn test_skips_near_cycle_end() {
+        let late = NaiveDate::from_ymd_opt(2026, 2, 18).unwrap();
+        assert!(matches!(
+            evaluate(&policy(), &usage(0.1), &[], now(), late),
+            Decision::Skip(_)
+        ));
+
+        let mut unknown = usage(0.1);
+        unknown.valid_until = None;
+        assert!(matches!(
+            evaluate(&policy(), &unknown, &[], now(), today()),
+            Decision::Skip(_)
+        ));
+    }
+
+    #[test]
+    fn test_limits_per_cycle() {
+        let two_hours_ago = now() - Duration::hours(2);
+        let one_booking = [booked(two_hours_ago, 14.95)];
+        assert_eq!(
+            evaluate(&policy(), &usage(0.1), &one_booking, now(), today()),
+            Decision::Book {
+                budget_eur: 30.0 - 14.95
+            }
+        );
+
+        let two_bookings = [booked(two_hours_ago, 5.95), booked(two_hours_ago, 5.95)];
+        assert!(matches!(
+            evaluate(&policy(), &usage(0.1), &two_bookings, now(), today()),
+            Decision::Skip(_)
+        ));
+
+        // Bookings from the previous cycle don't count
+        let mut previous = booked(two_hours_ago, 14.95);
+        previous.cycle = Some("21. Januar 2026".to_string());
+        let previous = [previous.clone(), previous];
+        assert_eq!(
+            evaluate(&policy(), &usage(0.1), &previous, now(), today()),
+            Decision::Book { budget_eur: 30.0 }
+        );
+    }
+
+    #[test]
+    fn test_cooldown_and_budget() {
+        let recent = [booked(now() - Duration::minutes(10), 5.95)];
+        assert!(matches!(
+            evaluate(&policy(), &usage(0.1), &recent, now(), today()),
+            Decision::Skip(_)
+        ));
+
+        for action in [
+            TopUpAction::Failed,
+            TopUpAction::Rejected,
+            TopUpAction::DryRun,
+        ] {
+            let mut attempt = booked(now() - Duration::minutes(10), 5.95);
+            attempt.action = action;
+            assert!(matches!(
+                evaluate(&policy(), &usage(0.1), &[attempt.clone()], now(), today()),
+                Decision::Skip(_)
+            ));
+            attempt.timestamp = now() - Duration::hours(2);
+            assert_eq!(
+                evaluate(&policy(), &usage(0.1), &[attempt], now(), today()),
+                Decision::Book { budget_eur: 30.0 }
+            );
+        }
+
+        let expensive = [booked(now() - Duration::hours(5), 30.0)];
+        assert!(matches!(
+            evaluate(&policy(), &usage(0.1), &expensive, now(), today()),
+            Decision::Skip(_)
+        ));
+    }
+}
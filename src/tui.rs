@@ -1,5 +1,5 @@
 use crate::error::Result;
-use crate::types::DataUsage;
+use crate::types::{format_age, DataUsage};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     execute,
@@ -7,7 +7,7 @@ use crossterm::{
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Gauge, Paragraph},
@@ -152,6 +152,13 @@ impl TuiApp {
                 )));
             }
 
+            if let Some(ref stale) = data.stale {
+                lines.push(Line::from(Span::styled(
+                    format!("Stale: {}", stale.notice()),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+
             lines
         } else {
             vec![Line::from("Loading...")]
@@ -181,7 +188,7 @@ impl TuiApp {
                 ])]
             } else {
                 // Display standard metered plan info
-                vec![
+                let mut lines = vec![
                     Line::from(vec![
                         Span::styled("Used:      ", Style::default().fg(Color::White)),
                         Span::styled(
@@ -207,7 +214,24 @@ impl TuiApp {
                             Style::default().fg(Color::Green),
                         ),
                     ]),
-                ]
+                ];
+
+                if let Some(ref forecast) = data.forecast {
+                    let color = if forecast.runs_out_early() {
+                        Color::Red
+                    } else {
+                        Color::Magenta
+                    };
+                    lines.push(Line::from(vec![
+                        Span::styled("Forecast:  ", Style::default().fg(Color::White)),
+                        Span::styled(
+                            forecast.summary(chrono::Utc::now()),
+                            Style::default().fg(color),
+                        ),
+                    ]));
+                }
+
+                lines
             }
         } else {
             vec![Line::from("Loading data...")]
@@ -240,12 +264,40 @@ impl TuiApp {
             (0.0, "Loading...".to_string(), Color::Gray)
         };
 
+        let forecast = self.data.as_ref().and_then(|data| data.forecast.as_ref());
+        let title = match forecast.and_then(|forecast| forecast.exhausts_at) {
+            Some(exhausts_at) => format!(
+                "Progress (runs out in {})",
+                format_age((exhausts_at - chrono::Utc::now()).num_seconds().max(0) as u64)
+            ),
+            None => "Progress".to_string(),
+        };
+
         let gauge = Gauge::default()
-            .block(Block::default().borders(Borders::ALL).title("Progress"))
+            .block(Block::default().borders(Borders::ALL).title(title))
             .gauge_style(Style::default().fg(color).bg(Color::Black))
             .ratio(ratio)
             .label(label);
         frame.render_widget(gauge, area);
+
+        // Mark the usage projected for the end of the validity
+        if let Some(projected) = self.data.as_ref().and_then(projected_usage_ratio) {
+            let inner = area.inner(Margin {
+                horizontal: 1,
+                vertical: 1,
+            });
+            if inner.width > 0 {
+                let x = inner.x + ((inner.width - 1) as f64 * projected).round() as u16;
+                let style = if forecast.is_some_and(|forecast| forecast.runs_out_early()) {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                for y in inner.top()..inner.bottom() {
+                    frame.buffer_mut()[(x, y)].set_symbol("┃").set_style(style);
+                }
+            }
+        }
     }
 
     fn render_help(&self, frame: &mut Frame, area: Rect) {
@@ -267,3 +319,9 @@ impl TuiApp {
         frame.render_widget(help, area);
     }
 }
+
+/// Share of the volume projected to be used when the validity ends
+fn projected_usage_ratio(data: &DataUsage) -> Option<f64> {
+    let remaining = data.forecast.as_ref()?.remaining_at_valid_until_gb?;
+    (data.total_gb > 0.0).then(|| ((data.total_gb - remaining) / data.total_gb).clamp(0.0, 1.0))
+}
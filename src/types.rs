@@ -1,5 +1,9 @@
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Bytes per GB as shown on the datapass page (decimal units)
+pub const BYTES_PER_GB: f64 = 1e9;
+
 /// Represents mobile data usage information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataUsage {
@@ -23,9 +27,136 @@ pub struct DataUsage {
 
     /// Whether this is an unlimited data plan
     pub is_unlimited: bool,
+
+    /// Name of the active pass (e.g., "MagentaMobil Prepaid L"), when the
+    /// page shows one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pass_name: Option<String>,
+
+    /// Where the active pass is valid (e.g., "Germany, Country group 1")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+
+    /// Set when this is the last stored reading, shown because fetching failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale: Option<Staleness>,
+
+    /// Projected consumption, when enough readings are recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forecast: Option<Forecast>,
+}
+
+/// Age of a stored reading served in place of a failed fetch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Staleness {
+    /// When the reading was fetched
+    pub fetched_at: DateTime<Utc>,
+
+    /// Seconds since the reading was fetched
+    pub age_seconds: u64,
+}
+
+impl Staleness {
+    /// Describe the reading's age, e.g. "last reading 2h 5m ago (2026-10-18 08:00)"
+    pub fn notice(&self) -> String {
+        format!(
+            "last reading {} ago ({})",
+            format_age(self.age_seconds),
+            self.fetched_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+/// How the burn rate is estimated from recorded readings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForecastMethod {
+    /// Least-squares line through all readings in the window
+    #[default]
+    Linear,
+    /// Exponentially weighted average of the rates between readings,
+    /// favouring recent consumption
+    Ewma,
+}
+
+/// When the remaining volume is projected to run out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Forecast {
+    /// Estimator used for the burn rate
+    pub method: ForecastMethod,
+
+    /// Consumption in GB per day
+    pub burn_rate_gb_per_day: f64,
+
+    /// When the volume runs out at that rate (None while nothing is used)
+    pub exhausts_at: Option<DateTime<Utc>>,
+
+    /// Projected volume left when the validity ends (None if the date is unknown)
+    pub remaining_at_valid_until_gb: Option<f64>,
+
+    /// Whether the volume lasts until the validity ends (None if the date is unknown)
+    pub lasts_until_valid_until: Option<bool>,
+
+    /// Number of readings the forecast is based on
+    pub samples: usize,
+}
+
+impl Forecast {
+    /// Whether the volume is projected to run out before the validity ends
+    pub fn runs_out_early(&self) -> bool {
+        self.lasts_until_valid_until == Some(false)
+    }
+
+    /// Describe the projection, e.g.
+    /// "2.10 GB/day, runs out 2026-10-21 12:00 (in 3d 4h), before the validity ends"
+    pub fn summary(&self, now: DateTime<Utc>) -> String {
+        let mut text = format!("{:.2} GB/day", self.burn_rate_gb_per_day);
+
+        match self.exhausts_at {
+            Some(exhausts_at) => text.push_str(&format!(
+                ", runs out {} (in {})",
+                exhausts_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M"),
+                format_age((exhausts_at - now).num_seconds().max(0) as u64)
+            )),
+            None => text.push_str(", not running out"),
+        }
+
+        match self.lasts_until_valid_until {
+            Some(true) => text.push_str(", lasts until the validity ends"),
+            Some(false) => text.push_str(", before the validity ends"),
+            None => {}
+        }
+
+        text
+    }
+}
+
+/// Format a number of seconds with the two most significant units, e.g. "2h 5m"
+pub fn format_age(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let hours = seconds % 86_400 / 3600;
+    let minutes = seconds % 3600 / 60;
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", seconds)
+    }
 }
 
 impl DataUsage {
+    /// Fields left out of the JSON when they are not set
+    pub const OPTIONAL_FIELDS: &'static [&'static str] =
+        &["pass_name", "zone", "stale", "forecast"];
+
     pub fn new(
         remaining_gb: f64,
         total_gb: f64,
@@ -47,6 +178,10 @@ impl DataUsage {
             plan_name,
             valid_until,
             is_unlimited: false,
+            pass_name: None,
+            zone: None,
+            stale: None,
+            forecast: None,
         }
     }
 
@@ -60,10 +195,177 @@ impl DataUsage {
             plan_name,
             valid_until,
             is_unlimited: true,
+            pass_name: None,
+            zone: None,
+            stale: None,
+            forecast: None,
         }
     }
 
+    /// Whether this is a stored reading rather than a fresh fetch
+    pub fn is_stale(&self) -> bool {
+        self.stale.is_some()
+    }
+
     pub fn remaining_percentage(&self) -> f64 {
         100.0 - self.percentage
     }
+
+    /// Validity end date parsed from `valid_until`
+    pub fn valid_until_date(&self) -> Option<NaiveDate> {
+        self.valid_until
+            .as_deref()
+            .and_then(crate::parser::parse_date)
+    }
+
+    /// When the validity ends: local midnight after the `valid_until` date
+    pub fn valid_until_end(&self) -> Option<DateTime<Utc>> {
+        let end = self.valid_until_date()?.succ_opt()?.and_hms_opt(0, 0, 0)?;
+        Local
+            .from_local_datetime(&end)
+            .earliest()
+            .map(|time| time.with_timezone(&Utc))
+    }
+
+    /// Days left until the validity ends (0 on the last day)
+    pub fn days_left(&self, today: NaiveDate) -> Option<i64> {
+        self.valid_until_date()
+            .map(|date| (date - today).num_days())
+    }
+}
+
+/// One data pass shown on the datapass.de page (the plan's own volume,
+/// booked passes and bonuses)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassUsage {
+    /// Pass instance identifier from the page (e.g., "pass-4207738b0600000a")
+    pub id: Option<String>,
+
+    /// Pass name (e.g., "Datenbonus")
+    pub name: Option<String>,
+
+    /// Remaining data in GB (None for unlimited or exhausted passes)
+    pub remaining_gb: Option<f64>,
+
+    /// Total data in GB (None for unlimited or exhausted passes)
+    pub total_gb: Option<f64>,
+
+    /// Whether the pass has unlimited data volume
+    pub is_unlimited: bool,
+
+    /// Whether the pass volume is used up
+    pub is_exhausted: bool,
+
+    /// Valid until date (e.g., "12. February 2026")
+    pub valid_until: Option<String>,
+
+    /// Where the pass is valid (e.g., "Germany, Country group 1")
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+/// Everything read from one fetch of the datapass.de page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataReport {
+    /// Usage of the active data pass
+    pub usage: DataUsage,
+
+    /// All passes shown on the page
+    pub passes: Vec<PassUsage>,
+
+    /// Passes offered for booking
+    pub offers: Vec<PassOffer>,
+
+    /// How and when the page was fetched
+    pub metadata: FetchMetadata,
+}
+
+/// Details about a fetch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchMetadata {
+    /// URL the page was fetched from
+    pub url: String,
+
+    /// When the page was fetched
+    pub fetched_at: DateTime<Utc>,
+
+    /// Number of requests made, including retries
+    pub attempts: u32,
+
+    /// The carrier's "last update" time as shown on the page (e.g., "24.01.2026 at 13:47")
+    pub last_update: Option<String>,
+}
+
+/// Legally required tariff facts from the tariff transparency page
+/// (`/tariffTransparency/domestic`). Speeds are normalized to Mbit/s.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TariffTransparency {
+    /// Tariff name, if the page names it
+    pub plan_name: Option<String>,
+
+    /// Advertised maximum download speed in Mbit/s
+    pub max_download_mbps: Option<f64>,
+
+    /// Estimated maximum download speed in Mbit/s
+    pub estimated_download_mbps: Option<f64>,
+
+    /// Advertised maximum upload speed in Mbit/s
+    pub max_upload_mbps: Option<f64>,
+
+    /// Estimated maximum upload speed in Mbit/s
+    pub estimated_upload_mbps: Option<f64>,
+
+    /// Price as shown on the page (e.g., "9,95 € / 4 Wochen")
+    pub price: Option<String>,
+}
+
+impl TariffTransparency {
+    /// Whether any speed value was found
+    pub fn has_speeds(&self) -> bool {
+        self.max_download_mbps.is_some()
+            || self.estimated_download_mbps.is_some()
+            || self.max_upload_mbps.is_some()
+            || self.estimated_upload_mbps.is_some()
+    }
+}
+
+/// A data pass offered for booking on the datapass.de page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassOffer {
+    /// Offer identifier from the booking link (e.g., "GDPXDF13"), if bookable right now
+    pub id: Option<String>,
+
+    /// Offer name (e.g., "SpeedOn M")
+    pub name: String,
+
+    /// Included data volume in GB (None for unlimited offers)
+    pub volume_gb: Option<f64>,
+
+    /// Whether the offer has unlimited data volume
+    pub is_unlimited: bool,
+
+    /// One-time price in EUR
+    pub price_eur: Option<f64>,
+
+    /// Validity description (e.g., "24 Stunden gültig")
+    pub description: Option<String>,
+
+    /// Relative booking link (e.g., "/pass/select/GDPXDF13?crt=...")
+    pub booking_path: Option<String>,
+}
+
+impl PassOffer {
+    /// Whether the offer can be booked right now (offers shown for after the
+    /// current volume is used up carry no booking link)
+    pub fn is_bookable(&self) -> bool {
+        self.booking_path.is_some()
+    }
+
+    /// Whether this offer matches an offer ID or (case-insensitive) name
+    pub fn matches(&self, id_or_name: &str) -> bool {
+        self.id
+            .as_deref()
+            .is_some_and(|id| id.eq_ignore_ascii_case(id_or_name))
+            || self.name.eq_ignore_ascii_case(id_or_name.trim())
+    }
 }
@@ -0,0 +1,301 @@
+use crate::alert::Alert;
+use crate::error::{DatapassError, Result};
+use crate::notify::Notifier;
+use crate::types::DataUsage;
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const USER_AGENT: &str = concat!("datapass/", env!("CARGO_PKG_VERSION"));
+
+/// A webhook alerts are posted to (`[[notify.webhook]]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Send to this webhook; the section being present enables it
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Where to POST
+    pub url: String,
+
+    /// Request body with `{{field}}` placeholders (default: the alert and
+    /// the reading as JSON)
+    pub template: Option<String>,
+
+    /// Content type of the body (default: JSON for templates that start
+    /// with a JSON object, plain text otherwise)
+    pub content_type: Option<String>,
+
+    /// Extra request headers, e.g. `Authorization`
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    /// Further attempts after a network error, 429 or 5xx response
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+
+    /// Wait before the first retry; doubled for each further one
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+
+    /// Give up on a request after this many seconds
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1000
+}
+
+fn default_timeout_seconds() -> u64 {
+    10
+}
+
+impl WebhookConfig {
+    /// The configured content type, or one guessed from the template
+    pub fn content_type(&self) -> &str {
+        match (&self.content_type, &self.template) {
+            (Some(content_type), _) => content_type,
+            (None, Some(template)) if !is_json_object(template) => "text/plain; charset=utf-8",
+            (None, _) => "application/json",
+        }
+    }
+}
+
+/// Whether a template starts with `{` (and not with a `{{` placeholder)
+fn is_json_object(template: &str) -> bool {
+    let template = template.trim_start();
+    template.starts_with('{') && !template.starts_with("{{")
+}
+
+/// Posts alerts to a webhook
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    client: Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: WebhookConfig) -> Result<Self> {
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()?;
+
+        Ok(Self { config, client })
+    }
+
+    /// The request body for an alert
+    pub fn body(&self, alert: &Alert, data: &DataUsage) -> Result<String> {
+        let fields = fields(alert, data)?;
+
+        match &self.config.template {
+            Some(template) => {
+                let json = self.config.content_type().contains("json");
+                render(template, &fields, json)
+            }
+            None => serde_json::to_string(&serde_json::json!({
+                "alert": alert,
+                "usage": data,
+            }))
+            .map_err(|e| DatapassError::NotifyError(e.to_string())),
+        }
+    }
+
+    /// POST once; a failure says whether it is worth retrying
+    fn post(&self, body: &str) -> std::result::Result<(), (bool, String)> {
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .header(CONTENT_TYPE, self.config.content_type());
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+
+        match request.body(body.to_string()).send() {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                let retry = status.is_server_error() || status.as_u16() == 429;
+                Err((retry, format!("HTTP {}", status)))
+            }
+            Err(e) => Err((true, e.to_string())),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn notify(&mut self, alert: &Alert, data: &DataUsage) -> Result<()> {
+        let body = self.body(alert, data)?;
+        let mut delay = Duration::from_millis(self.config.retry_delay_ms);
+        let mut retries = self.config.retries;
+
+        loop {
+            match self.post(&body) {
+                Ok(()) => {
+                    log::debug!("Webhook {} notified", self.config.url);
+                    return Ok(());
+                }
+                Err((true, message)) if retries > 0 => {
+                    log::warn!(
+                        "Webhook {} failed ({}), retrying in {:?}",
+                        self.config.url,
+                        message,
+                        delay
+                    );
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                    retries -= 1;
+                }
+                Err((_, message)) => {
+                    return Err(DatapassError::NotifyError(format!(
+                        "{}: {}",
+                        self.config.url, message
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// Template fields: the reading's JSON fields plus `alert_rule`,
+/// `alert_message`, `alert_severity` and `timestamp`
+fn fields(alert: &Alert, data: &DataUsage) -> Result<Map<String, Value>> {
+    let mut fields = match serde_json::to_value(data) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_) => Map::new(),
+        Err(e) => return Err(DatapassError::NotifyError(e.to_string())),
+    };
+    // Unset optional fields are missing from the JSON but still known
+    for name in DataUsage::OPTIONAL_FIELDS {
+        fields.entry(*name).or_insert(Value::Null);
+    }
+
+    fields.insert("alert_rule".to_string(), alert.rule.clone().into());
+    fields.insert("alert_message".to_string(), alert.message.clone().into());
+    fields.insert(
+        "alert_severity".to_string(),
+        alert.severity.to_string().into(),
+    );
+    fields.insert("timestamp".to_string(), alert.timestamp.to_rfc3339().into());
+
+    Ok(fields)
+}
+
+/// Replace `{{field}}` placeholders. Missing values (`null`) become empty;
+/// with `json`, strings are escaped to be placed inside JSON quotes.
+pub fn render(template: &str, fields: &Map<String, Value>, json: bool) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| {
+            DatapassError::NotifyError("unclosed {{ in webhook template".to_string())
+        })?;
+        let name = after[..end].trim();
+
+        let value = fields.get(name).ok_or_else(|| {
+            DatapassError::NotifyError(format!("unknown webhook template field '{}'", name))
+        })?;
+        match value {
+            Value::Null => {}
+            Value::String(text) if json => {
+                let quoted = Value::String(text.clone()).to_string();
+                out.push_str(&quoted[1..quoted.len() - 1]);
+            }
+            Value::String(text) => out.push_str(text),
+            other => out.push_str(&other.to_string()),
+        }
+
+        rest = &after[end + 2..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::Severity;
+
+    fn alert() -> Alert {
+        Alert {
+            rule: "usage_percent:90".to_string(),
+            message: "91.2% of the volume used (alert at 90%)".to_string(),
+            severity: Severity::Critical,
+            timestamp: "2026-10-18T08:00:00Z".parse().unwrap(),
+        }
+    }
+
+    fn config(template: &str) -> WebhookConfig {
+        toml::from_str(&format!(
+            "url = \"http://localhost\"\ntemplate = '''{}'''",
+            template
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_json_template_escapes_strings() {
+        let data = DataUsage::new(4.5, 51.0, Some("Prepaid \"XL\"".to_string()), None);
+        let notifier = WebhookNotifier::new(config(
+            r#"{"text": "{{ plan_name }}: {{remaining_gb}} GB left", "valid": "{{valid_until}}"}"#,
+        ))
+        .unwrap();
+
+        let body = notifier.body(&alert(), &data).unwrap();
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["text"], "Prepaid \"XL\": 4.5 GB left");
+        assert_eq!(json["valid"], "");
+    }
+
+    #[test]
+    fn test_text_template() {
+        let data = DataUsage::new(4.5, 51.0, None, None);
+        let config = config("[{{alert_severity}}] {{alert_message}}");
+        assert_eq!(config.content_type(), "text/plain; charset=utf-8");
+
+        let body = WebhookNotifier::new(config)
+            .unwrap()
+            .body(&alert(), &data)
+            .unwrap();
+        assert_eq!(body, "[critical] 91.2% of the volume used (alert at 90%)");
+    }
+
+    #[test]
+    fn test_unset_optional_fields_are_empty() {
+        let data = DataUsage::new(4.5, 51.0, None, None);
+        let notifier =
+            WebhookNotifier::new(config("[{{stale}}|{{forecast}}|{{pass_name}}|{{zone}}]"))
+                .unwrap();
+
+        assert_eq!(notifier.body(&alert(), &data).unwrap(), "[|||]");
+    }
+
+    #[test]
+    fn test_unknown_field_is_an_error() {
+        let data = DataUsage::new(4.5, 51.0, None, None);
+        let notifier = WebhookNotifier::new(config("{{remaining}}")).unwrap();
+
+        let error = notifier.body(&alert(), &data).unwrap_err().to_string();
+        assert!(error.contains("unknown webhook template field 'remaining'"));
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::{datapass, usage_page, write_config, StandInServer, StubResponse};
+use predicates::prelude::*;
+
+#[test]
+fn test_alert_fires_once_across_runs() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        "[alerts]\nusage_percent = [20, 50]\nremaining_gb = [1]\n",
+    );
+
+    let output = datapass(home.path())
+        .args(["--no-cache", "--url", &server.url, "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Alert: 24.8% of the volume used (alert at 20%)"),
+        "stderr: {}",
+        stderr
+    );
+    assert_eq!(stderr.matches("Alert:").count(), 1);
+
+    // Alerts go to stderr, stdout stays parseable
+    let data: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(data["remaining_gb"], 38.36);
+
+    let state = home
+        .path()
+        .join("state")
+        .join("datapass")
+        .join("alerts.json");
+    let state: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(state).unwrap()).unwrap();
+    assert_eq!(state["active"], serde_json::json!(["usage_percent:20"]));
+    assert_eq!(state["status"], "available");
+
+    // The next run remembers the alert
+    let output = datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Alert:"));
+}
+
+#[test]
+fn test_no_alerts_without_section() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Alert:").not());
+    assert!(!home
+        .path()
+        .join("state")
+        .join("datapass")
+        .join("alerts.json")
+        .exists());
+}
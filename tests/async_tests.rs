@@ -0,0 +1,36 @@
+mod common;
+
+use common::{usage_page, RecordedRequest, StandInServer, StubResponse};
+use datapass::DatapassClient;
+
+fn stand_in(request: &RecordedRequest) -> StubResponse {
+    match request.path.as_str() {
+        "/" => StubResponse::html(&usage_page("38,36", "51", None)),
+        _ => StubResponse::status(404, "not found"),
+    }
+}
+
+#[tokio::test]
+async fn test_async_fetch() {
+    let server = StandInServer::start(stand_in);
+    let client = DatapassClient::builder()
+        .url(&server.url)
+        .cookie("Apollo-Lang=de_DE_TMDE")
+        .build()
+        .unwrap();
+
+    let usage = client.fetch().await.unwrap();
+    assert_eq!(usage.remaining_gb, 38.36);
+    assert_eq!(usage.plan_name, Some("MagentaMobil Prepaid XL".to_string()));
+
+    let requests = server.requests();
+    assert_eq!(requests[0].header("Cookie"), Some("Apollo-Lang=de_DE_TMDE"));
+}
+
+#[tokio::test]
+async fn test_async_fetch_reports_http_errors() {
+    let server = StandInServer::start(|_| StubResponse::status(503, "maintenance"));
+    let client = DatapassClient::builder().url(&server.url).build().unwrap();
+
+    assert!(client.fetch().await.is_err());
+}
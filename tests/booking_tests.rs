@@ -0,0 +1,194 @@
+mod common;
+
+use common::{RecordedRequest, StandInServer, StubResponse};
+use datapass::booking::{BookingSession, BookingStatus};
+
+const MAIN_PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+    <head><title>Data usage - MagentaMobil Prepaid L</title></head>
+    <body>
+        <div class="pass-offer-card">
+            <div class="pass-offer-card__headline"><h3>DayFlat unlimited</h3></div>
+            <div class="volume unlimited">unlimited</div>
+            <div class="description">valid for 24 hours</div>
+            <div class="price"><span class="price">6,<span class="fraction">95</span>&#160;&euro;</span></div>
+            <a href="/pass/select/GDPXDF13?crt=token123" class="pass-offer-card__call-to-action-link">Select</a>
+        </div>
+        <div class="pass-offer-card">
+            <div class="pass-offer-card__headline"><h3>SpeedOn M</h3></div>
+            <div class="volume ">3<span class="unit">GB</span></div>
+            <div class="price"><span class="price">14,<span class="fraction">95</span>&#160;&euro;</span></div>
+        </div>
+    </body>
+    </html>
+"#;
+
+const SELECT_PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+    <head><title>Data usage - Confirm booking</title></head>
+    <body>
+        <form id="confirm" action="/pass/confirm" method="post">
+            <input type="hidden" name="jakarta.faces.ViewState" value="-42:17" />
+            <button type="submit" name="confirm:buy" value="buy">Buy now</button>
+        </form>
+    </body>
+    </html>
+"#;
+
+const RESULT_PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+    <body>
+        <div class="message-box-container">Thank you! Your data pass has been booked.</div>
+    </body>
+    </html>
+"#;
+
+/// Stand-in for datapass.de: catalog, selection page (which starts a
+/// session), confirmation POST with redirect, and result page
+fn datapass_stand_in(request: &RecordedRequest) -> StubResponse {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => StubResponse::html(MAIN_PAGE),
+        ("GET", "/pass/select/GDPXDF13?crt=token123") => StubResponse::html(SELECT_PAGE)
+            .with_header("Set-Cookie", "JSESSIONID=session42; Path=/; HttpOnly"),
+        ("POST", "/pass/confirm") => StubResponse::redirect("/pass/result"),
+        ("GET", "/pass/result") => StubResponse::html(RESULT_PAGE),
+        _ => StubResponse::status(404, "not found"),
+    }
+}
+
+#[test]
+fn test_book_dry_run_stops_before_submission() {
+    let server = StandInServer::start(datapass_stand_in);
+    let mut session = BookingSession::new(Some(&server.url), None).unwrap();
+
+    let offer = session.find_offer("GDPXDF13").unwrap();
+    assert_eq!(offer.name, "DayFlat unlimited");
+
+    let result = session.book(&offer, true).unwrap();
+    assert_eq!(result.status, BookingStatus::DryRun);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(
+        requests.iter().all(|request| request.method == "GET"),
+        "A dry run must not submit the booking form"
+    );
+}
+
+#[test]
+fn test_book_submits_confirmation_form() {
+    let server = StandInServer::start(datapass_stand_in);
+    let mut session =
+        BookingSession::new(Some(&server.url), Some("Apollo-Lang=en_DE_TMDE")).unwrap();
+
+    let offer = session.find_offer("dayflat unlimited").unwrap();
+    let result = session.book(&offer, false).unwrap();
+
+    assert_eq!(result.status, BookingStatus::Booked);
+    assert_eq!(
+        result.message,
+        Some("Thank you! Your data pass has been booked.".to_string())
+    );
+
+    let requests = server.requests();
+    let post = requests
+        .iter()
+        .find(|request| request.method == "POST")
+        .expect("Booking form was not submitted");
+
+    assert_eq!(post.path, "/pass/confirm");
+    assert_eq!(
+        post.body,
+        "jakarta.faces.ViewState=-42%3A17&confirm%3Abuy=buy"
+    );
+    assert_eq!(
+        post.header("Cookie"),
+        Some("Apollo-Lang=en_DE_TMDE; JSESSIONID=session42")
+    );
+
+    // The redirect after the POST is followed with GET
+    assert_eq!(requests.last().unwrap().path, "/pass/result");
+}
+
+#[test]
+fn test_book_does_not_follow_redirects_off_site() {
+    let elsewhere = StandInServer::start(|_| StubResponse::html(SELECT_PAGE));
+    // Another host name for the same machine: a different site
+    let target = format!(
+        "{}/pass/select/GDPXDF13",
+        elsewhere.url.replace("127.0.0.1", "localhost")
+    );
+    let server = StandInServer::start(move |request| match request.path.as_str() {
+        "/pass/select/GDPXDF13?crt=token123" => StubResponse::redirect(&target),
+        _ => datapass_stand_in(request),
+    });
+    let mut session = BookingSession::new(Some(&server.url), Some("JSESSIONID=secret")).unwrap();
+
+    let offer = session.find_offer("GDPXDF13").unwrap();
+    let error = session.book(&offer, false).unwrap_err().to_string();
+    assert!(error.contains("Refusing"), "{}", error);
+    assert!(elsewhere.requests().is_empty());
+    assert!(server
+        .requests()
+        .iter()
+        .all(|request| request.method == "GET"));
+}
+
+#[test]
+fn test_book_rejects_unknown_and_unavailable_offers() {
+    let server = StandInServer::start(datapass_stand_in);
+    let mut session = BookingSession::new(Some(&server.url), None).unwrap();
+
+    assert!(session.find_offer("NOPE").is_err());
+    // SpeedOn M is only offered after the current volume is used up
+    assert!(session.find_offer("SpeedOn M").is_err());
+}
+
+#[test]
+fn test_book_cli_requires_confirmation() {
+    let server = StandInServer::start(datapass_stand_in);
+
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_datapass"))
+        .args(["book", "GDPXDF13", "--url", &server.url])
+        .write_stdin("y\n")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--yes"));
+    assert!(server
+        .requests()
+        .iter()
+        .all(|request| request.method == "GET"));
+}
+
+#[test]
+fn test_book_cli_with_yes() {
+    let server = StandInServer::start(datapass_stand_in);
+
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_datapass"))
+        .args([
+            "book",
+            "GDPXDF13",
+            "--yes",
+            "--format",
+            "json",
+            "--url",
+            &server.url,
+        ])
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["status"], "booked");
+    assert_eq!(json["offer"]["id"], "GDPXDF13");
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{usage_page, RecordedRequest, StandInServer, StubResponse};
+use datapass::cache::ResponseCache;
+use datapass::fetcher;
+use std::time::Duration;
+
+const ETAG: &str = "\"usage-v1\"";
+
+/// Stand-in that supports `If-None-Match` revalidation
+fn revalidating_stand_in(request: &RecordedRequest) -> StubResponse {
+    if request.header("If-None-Match") == Some(ETAG) {
+        StubResponse::status(304, "").with_header("ETag", ETAG)
+    } else {
+        StubResponse::html(&usage_page("38,36", "51", None))
+            .with_header("ETag", ETAG)
+            .with_header("Last-Modified", "Sat, 17 Oct 2026 10:00:00 GMT")
+    }
+}
+
+#[test]
+fn test_conditional_request_serves_cached_body() {
+    let server = StandInServer::start(revalidating_stand_in);
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ResponseCache::new(dir.path().to_path_buf());
+
+    let first = fetcher::fetch_html_cached(Some(&server.url), None, &cache, None).unwrap();
+    let second = fetcher::fetch_html_cached(Some(&server.url), None, &cache, None).unwrap();
+    assert_eq!(first, second);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].header("If-None-Match"), None);
+    assert_eq!(requests[1].header("If-None-Match"), Some(ETAG));
+    assert_eq!(
+        requests[1].header("If-Modified-Since"),
+        Some("Sat, 17 Oct 2026 10:00:00 GMT")
+    );
+}
+
+#[test]
+fn test_max_age_skips_request() {
+    let server = StandInServer::start(revalidating_stand_in);
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ResponseCache::new(dir.path().to_path_buf());
+    let max_age = Some(Duration::from_secs(60));
+
+    fetcher::fetch_html_cached(Some(&server.url), None, &cache, max_age).unwrap();
+    let cached = fetcher::fetch_html_cached(Some(&server.url), None, &cache, max_age).unwrap();
+
+    assert!(cached.contains("38,36"));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn test_cli_max_age_uses_cache_dir() {
+    let server = StandInServer::start(revalidating_stand_in);
+    let dir = tempfile::tempdir().unwrap();
+
+    for _ in 0..2 {
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_datapass"))
+            .args(["--remaining", "--max-age", "5m", "--url", &server.url])
+            .env("XDG_CACHE_HOME", dir.path())
+            .env("XDG_STATE_HOME", dir.path())
+            .assert()
+            .success()
+            .stdout("38.36\n");
+    }
+
+    assert_eq!(server.requests().len(), 1);
+
+    // --no-cache always downloads the full page
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_datapass"))
+        .args(["--remaining", "--no-cache", "--url", &server.url])
+        .env("XDG_CACHE_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .assert()
+        .success();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].header("If-None-Match"), None);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{datapass, usage_page, StandInServer, StubResponse};
+
+const UNLIMITED_PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+    <head><title>Data usage - MagentaMobil Prepaid Max</title></head>
+    <body>
+        <section class="data-pass-instance" id="test-pass">
+            <div class="volume"><strong>unlimited</strong></div>
+        </section>
+    </body>
+    </html>
+"#;
+
+/// Run `datapass check` against a page and return the exit code and output
+fn check(page: String, args: &[&str]) -> (i32, String) {
+    let server = StandInServer::start(move |_| StubResponse::html(&page));
+    let home = tempfile::tempdir().unwrap();
+
+    let output = datapass(home.path())
+        .args(["--no-cache", "--url", &server.url, "check"])
+        .args(args)
+        .output()
+        .unwrap();
+
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn test_check_states() {
+    let (code, output) = check(usage_page("38,36", "51", None), &[]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        output,
+        "DATAPASS OK - MagentaMobil Prepaid XL: 38.36 GB of 51.00 GB left (24.78% used) \
+         | remaining=38.36GB;;;0;51 used=12.64GB;;;0;51 used_percent=24.78%;80;95;0;100\n"
+    );
+
+    let (code, output) = check(
+        usage_page("2,5", "51", None),
+        &["--warning", "80%", "--critical", "99%"],
+    );
+    assert_eq!(code, 1);
+    assert!(output.starts_with("DATAPASS WARNING - "));
+    assert!(output.contains("; warning above 80% used |"));
+
+    let (code, output) = check(
+        usage_page("2,5", "51", None),
+        &["--warning", "10GB", "--critical", "5GB"],
+    );
+    assert_eq!(code, 2);
+    assert!(output.contains("| remaining=2.5GB;10:;5:;0;51 "));
+
+    let (code, output) = check(usage_page("0", "51", None), &["--critical", "100%"]);
+    assert_eq!(code, 2);
+    assert!(output.contains("; throttled |"));
+}
+
+#[test]
+fn test_check_unlimited_plan_is_ok() {
+    let (code, output) = check(UNLIMITED_PAGE.to_string(), &["--critical", "1%"]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        output,
+        "DATAPASS OK - MagentaMobil Prepaid Max: unlimited data\n"
+    );
+}
+
+#[test]
+fn test_check_failures_are_unknown() {
+    let server = StandInServer::start(|_| StubResponse::status(500, "down"));
+    let home = tempfile::tempdir().unwrap();
+
+    let output = datapass(home.path())
+        .args(["--no-cache", "--url", &server.url, "check"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("DATAPASS UNKNOWN - "));
+    assert_eq!(stdout.lines().count(), 1);
+
+    // Usage errors too
+    datapass(home.path())
+        .args(["check", "--warning", "80"])
+        .assert()
+        .code(3);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::{RecordedRequest, StandInServer, StubResponse};
+use datapass::blocking::DatapassClient;
+use datapass::Locale;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const USAGE_PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+    <head><title>Data usage - MagentaMobil Prepaid XL</title></head>
+    <body>
+        <section class="data-pass-instance" id="pass-1">
+            <div class="data-pass-instance__headline"><h2>MagentaMobil Prepaid XL</h2></div>
+            <div class="remaining-volume-value">38,36</div>
+            <div class="start-volume">51</div>
+            <div class="info-row">Gültig bis: 21. Februar 2026</div>
+        </section>
+        <div class="last-update">
+            <div class="hover-anker"><label>Letzte Aktualisierung:</label></div>
+            18.10.2026 um 09:12
+        </div>
+        <div class="pass-offer-card">
+            <div class="pass-offer-card__headline"><h3>DayFlat unlimited</h3></div>
+            <div class="volume unlimited">unlimited</div>
+            <div class="price"><span class="price">6,<span class="fraction">95</span>&#160;&euro;</span></div>
+            <a href="/pass/select/GDPXDF13?crt=token" class="pass-offer-card__call-to-action-link">Auswählen</a>
+        </div>
+    </body>
+    </html>
+"#;
+
+fn stand_in(request: &RecordedRequest) -> StubResponse {
+    match request.path.as_str() {
+        "/" => StubResponse::html(USAGE_PAGE),
+        _ => StubResponse::status(404, "not found"),
+    }
+}
+
+#[test]
+fn test_builder_locale_selects_page_language() {
+    let server = StandInServer::start(stand_in);
+    let client = DatapassClient::builder()
+        .url(&server.url)
+        .locale(Locale::German)
+        .build_blocking()
+        .unwrap();
+
+    client.fetch().unwrap();
+
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].header("Cookie"),
+        Some("Apollo-Summation-Disabled=true; Apollo-Lang=de_DE_TMDE")
+    );
+    assert!(requests[0]
+        .header("Accept-Language")
+        .unwrap()
+        .starts_with("de"));
+}
+
+#[test]
+fn test_fetch_report() {
+    let server = StandInServer::start(stand_in);
+    let client = DatapassClient::builder()
+        .url(&server.url)
+        .build_blocking()
+        .unwrap();
+
+    let report = client.fetch_report().unwrap();
+
+    assert_eq!(report.usage.remaining_gb, 38.36);
+    assert_eq!(report.passes.len(), 1);
+    assert_eq!(
+        report.passes[0].valid_until,
+        Some("21. Februar 2026".to_string())
+    );
+    assert_eq!(report.offers.len(), 1);
+    assert_eq!(report.offers[0].id, Some("GDPXDF13".to_string()));
+    assert_eq!(report.metadata.url, server.url);
+    assert_eq!(report.metadata.attempts, 1);
+    assert_eq!(
+        report.metadata.last_update,
+        Some("18.10.2026 um 09:12".to_string())
+    );
+
+    // One fetch for the whole report
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn test_retries_server_errors() {
+    let calls = AtomicUsize::new(0);
+    let server = StandInServer::start(move |request| {
+        if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+            StubResponse::status(503, "maintenance")
+        } else {
+            stand_in(request)
+        }
+    });
+
+    let client = DatapassClient::builder()
+        .url(&server.url)
+        .retries(2)
+        .retry_delay(Duration::from_millis(10))
+        .build_blocking()
+        .unwrap();
+
+    let report = client.fetch_report().unwrap();
+    assert_eq!(report.metadata.attempts, 3);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn test_client_errors_are_not_retried() {
+    let server = StandInServer::start(|_| StubResponse::status(404, "not found"));
+    let client = DatapassClient::builder()
+        .url(&server.url)
+        .retries(3)
+        .retry_delay(Duration::from_millis(10))
+        .build_blocking()
+        .unwrap();
+
+    assert!(client.fetch().is_err());
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn test_invalid_proxy_is_rejected() {
+    let result = DatapassClient::builder()
+        .proxy("not a proxy url")
+        .build_blocking();
+    assert!(result.is_err());
+}
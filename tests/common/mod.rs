@@ -0,0 +1,207 @@
+//! Minimal HTTP stand-in server and shared fixtures for integration tests
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The `datapass` binary with all XDG directories in `home`, for processes
+/// left running in the background
+#[cfg(all(feature = "cli", feature = "tui", feature = "color"))]
+pub fn datapass_process(home: &Path) -> std::process::Command {
+    let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_datapass"));
+    cmd.env("XDG_CACHE_HOME", home.join("cache"))
+        .env("XDG_STATE_HOME", home.join("state"))
+        .env("XDG_DATA_HOME", home.join("data"))
+        .env("XDG_CONFIG_HOME", home.join("config"));
+    cmd
+}
+
+/// `datapass` with all XDG directories in `home`
+#[cfg(all(feature = "cli", feature = "tui", feature = "color"))]
+pub fn datapass(home: &Path) -> assert_cmd::Command {
+    assert_cmd::Command::from_std(datapass_process(home))
+}
+
+/// Write the config file `datapass` reads with its XDG directories in `home`
+pub fn write_config(home: &Path, config: &str) {
+    let dir = home.join("config").join("datapass");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("config.toml"), config).unwrap();
+}
+
+/// A datapass page with one pass: `remaining` of `total` GB as the page
+/// shows them (e.g. "38,36" and "51"), optionally with a validity date
+pub fn usage_page(remaining: &str, total: &str, valid_until: Option<&str>) -> String {
+    let valid_until = valid_until
+        .map(|date| format!("<div class=\"info-row\">Valid until: {}</div>", date))
+        .unwrap_or_default();
+
+    format!(
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <head><title>Data usage - MagentaMobil Prepaid XL</title></head>
+        <body>
+            <section class="data-pass-instance" id="test-pass">
+                <div class="remaining-volume-value">{}</div>
+                <div class="start-volume">{}</div>
+                {}
+            </section>
+        </body>
+        </html>
+        "#,
+        remaining, total, valid_until
+    )
+}
+
+/// A request received by the stand-in server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path including the query string
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Case-insensitive header lookup
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A canned response
+#[derive(Debug, Clone)]
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubResponse {
+    pub fn html(body: &str) -> Self {
+        Self {
+            status: 200,
+            headers: vec![(
+                "Content-Type".to_string(),
+                "text/html; charset=utf-8".to_string(),
+            )],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn status(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    pub fn redirect(location: &str) -> Self {
+        Self::status(302, "").with_header("Location", location)
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// HTTP server on a random local port that answers every request with the
+/// handler's response and records what it received
+pub struct StandInServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StandInServer {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> StubResponse + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stand-in server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Some(request) = read_request(&stream) {
+                    let response = handler(&request);
+                    recorded.lock().unwrap().push(request);
+                    write_response(stream, &response);
+                }
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// All requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<RecordedRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+fn write_response(mut stream: TcpStream, response: &StubResponse) {
+    let mut head = format!(
+        "HTTP/1.1 {} Stand-In\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+    let _ = stream.flush();
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::{usage_page, write_config, StandInServer, StubResponse};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// `datapass` with all XDG directories in a temporary directory, in UTC
+fn datapass(home: &Path) -> assert_cmd::Command {
+    let mut cmd = common::datapass(home);
+    cmd.env("TZ", "UTC");
+    cmd
+}
+
+fn json(cmd: &mut assert_cmd::Command) -> serde_json::Value {
+    let output = cmd.output().unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_fetches_record_cycle_events() {
+    // Consumption, then a 5 GB booking, then a renewal
+    let pages = [
+        usage_page("10,00", "51", Some("01.11.2026")),
+        usage_page("14,00", "56", Some("01.11.2026")),
+        usage_page("51,00", "51", Some("01.12.2026")),
+    ];
+    let fetches = AtomicUsize::new(0);
+    let server = StandInServer::start(move |_| {
+        let index = fetches.fetch_add(1, Ordering::SeqCst).min(2);
+        StubResponse::html(&pages[index])
+    });
+
+    let home = tempfile::tempdir().unwrap();
+    write_config(home.path(), "[history]\n");
+
+    for _ in 0..3 {
+        datapass(home.path())
+            .args(["--no-cache", "--url", &server.url])
+            .assert()
+            .success();
+    }
+
+    let entries = json(datapass(home.path()).args(["history", "list", "--format", "json"]));
+    assert!(entries[0].get("events").is_none());
+    assert_eq!(entries[1]["events"][0]["type"], "PassBooked");
+    assert_eq!(entries[1]["events"][0]["added_gb"], 5.0);
+    assert_eq!(entries[2]["events"][0]["type"], "CycleStarted");
+    assert_eq!(entries[2]["events"][0]["valid_until"], "01.12.2026");
+
+    let cycles = json(datapass(home.path()).args(["cycles", "--format", "json"]));
+    assert_eq!(cycles.as_array().unwrap().len(), 2);
+    assert_eq!(cycles[0]["used_gb"], 42.0);
+    assert_eq!(cycles[0]["booked_gb"], 5.0);
+    assert_eq!(cycles[0]["bookings"], 1);
+    assert_eq!(cycles[1]["number"], 2);
+    assert_eq!(cycles[1]["used_gb"], 0.0);
+}
+
+/// Seed a history with a renewal at 01:00 on October 2
+fn seed_history(home: &Path) {
+    let reading = |timestamp: &str, remaining_gb: f64, valid_until: &str| {
+        serde_json::json!({
+            "timestamp": timestamp,
+            "remaining_gb": remaining_gb,
+            "total_gb": 51.0,
+            "used_gb": 51.0 - remaining_gb,
+            "percentage": (51.0 - remaining_gb) / 51.0 * 100.0,
+            "plan_name": "MagentaMobil Prepaid XL",
+            "valid_until": valid_until,
+            "is_unlimited": false,
+        })
+        .to_string()
+    };
+    let readings = [
+        reading("2026-10-01T12:00:00Z", 3.0, "2. October 2026"),
+        reading("2026-10-01T23:00:00Z", 1.0, "2. October 2026"),
+        reading("2026-10-02T01:00:00Z", 50.0, "1. November 2026"),
+        reading("2026-10-03T00:00:00Z", 46.0, "1. November 2026"),
+    ];
+
+    let dir = home.join("data").join("datapass");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("history.jsonl"), readings.join("\n") + "\n").unwrap();
+}
+
+#[test]
+fn test_report_per_cycle() {
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path());
+
+    let days =
+        json(datapass(home.path()).args(["report", "--cycle", "current", "--format", "json"]));
+    let used: f64 = days
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|day| day["used_gb"].as_f64().unwrap())
+        .sum();
+    // 1 GB since the renewal, then 4 GB
+    assert!((used - 5.0).abs() < 1e-6, "{}", used);
+
+    let days = json(datapass(home.path()).args(["report", "--cycle", "1", "--format", "json"]));
+    assert_eq!(days.as_array().unwrap().len(), 1);
+    assert!((days[0]["used_gb"].as_f64().unwrap() - 2.0).abs() < 1e-6);
+
+    datapass(home.path())
+        .args(["report", "--cycle", "3"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("No cycle '3'"));
+}
+
+#[test]
+fn test_human_cycles() {
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path());
+
+    datapass(home.path())
+        .arg("cycles")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("2. October 2026"))
+        .stdout(predicates::str::contains("1. November 2026"));
+}
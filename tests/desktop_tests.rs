@@ -0,0 +1,184 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{datapass, usage_page, write_config, StandInServer, StubResponse};
+use std::collections::HashMap;
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use zbus::blocking::connection::Builder;
+use zbus::interface;
+use zbus::zvariant::OwnedValue;
+
+/// A notification received by the stand-in bus
+#[derive(Debug, Clone)]
+struct Received {
+    app_name: String,
+    summary: String,
+    body: String,
+    urgency: Option<u8>,
+}
+
+/// `org.freedesktop.DBus`, just enough for a client to say hello
+struct FakeBus;
+
+#[interface(name = "org.freedesktop.DBus")]
+impl FakeBus {
+    fn hello(&self) -> String {
+        ":1.1".to_string()
+    }
+}
+
+/// `org.freedesktop.Notifications`, recording what it is sent
+struct FakeNotifications {
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl FakeNotifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: String,
+        _replaces_id: u32,
+        _app_icon: String,
+        summary: String,
+        body: String,
+        _actions: Vec<String>,
+        hints: HashMap<String, OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        let urgency = hints
+            .get("urgency")
+            .and_then(|value| u8::try_from(value).ok());
+        let mut received = self.received.lock().unwrap();
+        received.push(Received {
+            app_name,
+            summary,
+            body,
+            urgency,
+        });
+        received.len() as u32
+    }
+}
+
+/// Session bus stand-in on a Unix socket, answering every connection as the
+/// bus and the notification server at once
+struct StandInBus {
+    address: String,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+impl StandInBus {
+    fn start(dir: &Path) -> Self {
+        let path = dir.join("bus");
+        let listener = UnixListener::bind(&path).unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let shared = received.clone();
+        std::thread::spawn(move || {
+            let mut connections = Vec::new();
+            for stream in listener.incoming() {
+                let connection = Builder::async_io_unix_stream(stream.unwrap())
+                    .server(zbus::Guid::generate())
+                    .unwrap()
+                    .p2p()
+                    .serve_at("/org/freedesktop/DBus", FakeBus)
+                    .unwrap()
+                    .serve_at(
+                        "/org/freedesktop/Notifications",
+                        FakeNotifications {
+                            received: shared.clone(),
+                        },
+                    )
+                    .unwrap()
+                    .build()
+                    .unwrap();
+                connections.push(connection);
+            }
+        });
+
+        Self {
+            address: format!("unix:path={}", path.display()),
+            received,
+        }
+    }
+
+    fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+#[test]
+fn test_alerts_become_desktop_notifications() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    let bus = StandInBus::start(home.path());
+    write_config(
+        home.path(),
+        "[alerts]\nusage_percent = [50]\nremaining_gb = [5]\ndays_left = []\n\n[notify.desktop]\n",
+    );
+
+    let output = datapass(home.path())
+        .env("DBUS_SESSION_BUS_ADDRESS", &bus.address)
+        .args(["--verbose", "--no-cache", "--url", &server.url])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {}", stderr);
+
+    let received = bus.received();
+    assert_eq!(received.len(), 2, "{:?}\nstderr: {}", received, stderr);
+    assert!(received.iter().all(|n| n.app_name == "datapass"));
+
+    // Passing 50% is a warning (normal urgency), running low is critical
+    assert_eq!(received[0].urgency, Some(1));
+    assert_eq!(received[0].summary, "Mobile data: warning");
+    assert_eq!(received[1].urgency, Some(2));
+    assert_eq!(received[1].summary, "Mobile data: critical");
+    assert!(
+        received[1].body.starts_with(
+            "2.50 GB remaining (alert below 5 GB)\nMagentaMobil Prepaid XL: 2.50 GB of 51.00 GB remaining"
+        ),
+        "{}",
+        received[1].body
+    );
+}
+
+#[test]
+fn test_missing_bus_is_not_an_error() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        "[alerts]\nremaining_gb = [5]\n\n[notify.desktop]\n",
+    );
+
+    let output = datapass(home.path())
+        .env(
+            "DBUS_SESSION_BUS_ADDRESS",
+            format!("unix:path={}", home.path().join("no-bus").display()),
+        )
+        .args([
+            "--verbose",
+            "--no-cache",
+            "--url",
+            &server.url,
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Alert: "), "stderr: {}", stderr);
+    assert!(
+        stderr.contains("Desktop notifications disabled"),
+        "stderr: {}",
+        stderr
+    );
+    let data: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(data["remaining_gb"], 2.5);
+}
@@ -0,0 +1,299 @@
+mod common;
+
+use base64::Engine;
+use common::{datapass, usage_page, write_config, StandInServer, StubResponse};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+/// A message received by the SMTP sink
+#[derive(Debug, Clone, Default)]
+struct Mail {
+    /// `AUTH PLAIN` response, base64 encoded
+    auth: Option<String>,
+    from: String,
+    to: Vec<String>,
+    /// Headers and body as sent after `DATA`
+    data: String,
+}
+
+impl Mail {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.data
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.eq_ignore_ascii_case(name).then(|| value.trim())
+            })
+    }
+
+    /// The body, with the transfer encoding undone
+    fn body(&self) -> String {
+        let body = self
+            .data
+            .split_once("\r\n\r\n")
+            .map_or("", |(_, body)| body);
+        let bytes = match self.header("Content-Transfer-Encoding") {
+            Some("base64") => base64::engine::general_purpose::STANDARD
+                .decode(body.replace("\r\n", ""))
+                .unwrap(),
+            Some("quoted-printable") => quoted_printable(body),
+            _ => body.as_bytes().to_vec(),
+        };
+        String::from_utf8_lossy(&bytes).replace("\r\n", "\n")
+    }
+}
+
+fn quoted_printable(text: &str) -> Vec<u8> {
+    let text = text.replace("=\r\n", "");
+    let mut bytes = Vec::new();
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (byte, escaped) {
+            (b'=', Some(escaped)) => {
+                bytes.push(escaped);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    bytes
+}
+
+/// SMTP server on a random local port that accepts every message (with or
+/// without `AUTH PLAIN`) and records it
+struct SmtpSink {
+    port: u16,
+    mails: Arc<Mutex<Vec<Mail>>>,
+}
+
+impl SmtpSink {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mails = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&mails);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                serve(stream, &recorded);
+            }
+        });
+
+        Self { port, mails }
+    }
+
+    fn mails(&self) -> Vec<Mail> {
+        self.mails.lock().unwrap().clone()
+    }
+}
+
+fn serve(mut stream: TcpStream, mails: &Mutex<Vec<Mail>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut mail = Mail::default();
+    let _ = stream.write_all(b"220 sink ESMTP\r\n");
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let command = line.trim_end();
+        let verb = command
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_ascii_uppercase();
+
+        let reply: &[u8] = match verb.as_str() {
+            "EHLO" | "HELO" => b"250-sink\r\n250-AUTH PLAIN\r\n250 8BITMIME\r\n",
+            "AUTH" => {
+                mail.auth = command.split_whitespace().nth(2).map(str::to_string);
+                b"235 2.7.0 Authentication successful\r\n"
+            }
+            "MAIL" => {
+                mail.from = address(command);
+                b"250 OK\r\n"
+            }
+            "RCPT" => {
+                mail.to.push(address(command));
+                b"250 OK\r\n"
+            }
+            "DATA" => {
+                let _ = stream.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n");
+                let mut data = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == ".\r\n" {
+                        break;
+                    }
+                    data.push_str(&line);
+                }
+                mail.data = data;
+                mails.lock().unwrap().push(std::mem::take(&mut mail));
+                b"250 OK: queued\r\n"
+            }
+            "QUIT" => {
+                let _ = stream.write_all(b"221 Bye\r\n");
+                return;
+            }
+            _ => b"250 OK\r\n",
+        };
+        let _ = stream.write_all(reply);
+    }
+}
+
+/// The address in `MAIL FROM:<...>` or `RCPT TO:<...>`
+fn address(command: &str) -> String {
+    command
+        .split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(address, _)| address.to_string())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_alert_is_emailed_to_all_recipients() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let sink = SmtpSink::start();
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        &format!(
+            r#"
+[alerts]
+remaining_gb = [5]
+
+[notify.email]
+host = "127.0.0.1"
+port = {}
+tls = "none"
+username = "alerts"
+password = "s3cret"
+from = "datapass <datapass@example.com>"
+to = ["ops@example.com", "Alice <alice@example.com>"]
+"#,
+            sink.port
+        ),
+    );
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .success();
+
+    let mails = sink.mails();
+    assert_eq!(mails.len(), 1);
+    let mail = &mails[0];
+    assert_eq!(mail.auth.as_deref(), Some("AGFsZXJ0cwBzM2NyZXQ="));
+    assert_eq!(mail.from, "datapass@example.com");
+    assert_eq!(mail.to, ["ops@example.com", "alice@example.com"]);
+    assert_eq!(
+        mail.header("Subject"),
+        Some("[datapass] Critical: 2.50 GB remaining (alert below 5 GB)")
+    );
+
+    let body = mail.body();
+    assert!(
+        body.starts_with("2.50 GB remaining (alert below 5 GB)\n\nPlan: MagentaMobil Prepaid XL\n"),
+        "{}",
+        body
+    );
+    assert!(body.contains("Remaining: 2.50 GB (4.90%)"), "{}", body);
+    assert!(body.contains("Sent by datapass at "), "{}", body);
+}
+
+#[test]
+fn test_weekly_digest_is_sent_once() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let sink = SmtpSink::start();
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        &format!(
+            r#"
+[history]
+
+[notify.email]
+host = "127.0.0.1"
+port = {}
+tls = "none"
+from = "datapass@example.com"
+to = ["ops@example.com"]
+weekly_digest = true
+"#,
+            sink.port
+        ),
+    );
+
+    for _ in 0..2 {
+        datapass(home.path())
+            .args(["--no-cache", "--url", &server.url])
+            .assert()
+            .success();
+    }
+
+    let mails = sink.mails();
+    assert_eq!(mails.len(), 1);
+    let mail = &mails[0];
+    assert!(mail.auth.is_none());
+    assert_eq!(
+        mail.header("Subject"),
+        Some("[datapass] Weekly summary: 2.50 GB of 51.00 GB left")
+    );
+    let body = mail.body();
+    assert!(body.contains("Remaining: 2.50 GB"), "{}", body);
+    assert!(body.contains("Last 7 days\n\nPeriod"), "{}", body);
+    assert!(home
+        .path()
+        .join("state")
+        .join("datapass")
+        .join("email_digest.json")
+        .exists());
+}
+
+#[test]
+fn test_digest_state_per_config() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let sink = SmtpSink::start();
+    let home = tempfile::tempdir().unwrap();
+
+    // Two SIMs, each with its own config, on one host
+    for sim in ["sim1", "sim2"] {
+        write_config(
+            home.path(),
+            &format!(
+                r#"
+[notify.email]
+host = "127.0.0.1"
+port = {}
+tls = "none"
+from = "datapass@example.com"
+to = ["{}@example.com"]
+weekly_digest = true
+digest_state = "{}"
+"#,
+                sink.port,
+                sim,
+                home.path().join(format!("{}-digest.json", sim)).display()
+            ),
+        );
+
+        datapass(home.path())
+            .args(["--no-cache", "--url", &server.url])
+            .assert()
+            .success();
+    }
+
+    assert_eq!(sink.mails().len(), 2);
+    assert!(home.path().join("sim1-digest.json").exists());
+    assert!(home.path().join("sim2-digest.json").exists());
+}
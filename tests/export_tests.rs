@@ -0,0 +1,144 @@
+mod common;
+
+use datapass::export::{self, ExportRow};
+use datapass::history::HistoryStore;
+use std::path::Path;
+
+/// `datapass` with all XDG directories in a temporary directory, in UTC
+fn datapass(home: &Path) -> assert_cmd::Command {
+    let mut cmd = common::datapass(home);
+    cmd.env("TZ", "UTC");
+    cmd
+}
+
+fn history_path(home: &Path) -> std::path::PathBuf {
+    home.join("data").join("datapass").join("history.jsonl")
+}
+
+/// Three readings: one with two passes, a stale one and an unlimited one
+fn seed_history(home: &Path) {
+    let readings = [
+        serde_json::json!({
+            "timestamp": "2026-10-01T08:00:00Z",
+            "remaining_gb": 38.36,
+            "total_gb": 51.0,
+            "used_gb": 12.64,
+            "percentage": 24.784313725490197,
+            "plan_name": "MagentaMobil Prepaid XL, \"EU\"",
+            "valid_until": "12. November 2026",
+            "is_unlimited": false,
+            "pass_name": "Prepaid XL",
+            "zone": "Germany, Country group 1",
+            "passes": [
+                { "id": "a", "name": "Prepaid XL", "is_unlimited": false, "is_exhausted": false },
+                { "id": "b", "name": "Datenbonus", "is_unlimited": false, "is_exhausted": false },
+            ],
+        }),
+        serde_json::json!({
+            "timestamp": "2026-10-02T08:00:00Z",
+            "remaining_gb": 36.0,
+            "total_gb": 51.0,
+            "used_gb": 15.0,
+            "percentage": 29.411764705882355,
+            "plan_name": "MagentaMobil Prepaid XL",
+            "valid_until": "12. November 2026",
+            "is_unlimited": false,
+            "stale": { "fetched_at": "2026-10-02T07:00:00Z", "age_seconds": 3600 },
+        }),
+        serde_json::json!({
+            "timestamp": "2026-10-03T08:00:00Z",
+            "remaining_gb": 0.0,
+            "total_gb": 0.0,
+            "used_gb": 0.0,
+            "percentage": 0.0,
+            "is_unlimited": true,
+        }),
+    ]
+    .map(|reading| reading.to_string());
+
+    let path = history_path(home);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, readings.join("\n") + "\n").unwrap();
+}
+
+fn export(home: &Path, args: &[&str]) -> String {
+    let output = datapass(home).arg("export").args(args).output().unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The seeded readings as export rows
+fn expected_rows(home: &Path) -> Vec<ExportRow> {
+    HistoryStore::new(history_path(home))
+        .read()
+        .unwrap()
+        .iter()
+        .map(ExportRow::from)
+        .collect()
+}
+
+#[test]
+fn test_csv_export_round_trip() {
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path());
+
+    let csv = export(home.path(), &[]);
+    assert_eq!(
+        csv.lines().next().unwrap(),
+        "timestamp,remaining_gb,total_gb,used_gb,percentage,plan_name,valid_until,is_unlimited,pass_name,zone,pass_names,stale"
+    );
+    assert_eq!(csv, export(home.path(), &["--format", "csv"]));
+
+    let rows = export::read_csv(&csv).unwrap();
+    assert_eq!(rows, expected_rows(home.path()));
+    assert_eq!(rows[0].pass_name.as_deref(), Some("Prepaid XL"));
+    assert_eq!(rows[0].zone.as_deref(), Some("Germany, Country group 1"));
+    assert_eq!(
+        rows[0].pass_names.as_deref(),
+        Some("Prepaid XL; Datenbonus")
+    );
+    assert_eq!(rows[1].pass_names, None);
+    assert!(!rows[0].stale);
+    assert!(rows[1].stale);
+    assert!(rows[2].is_unlimited);
+}
+
+#[test]
+fn test_jsonl_export_round_trip() {
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path());
+
+    let jsonl = export(home.path(), &["--format", "jsonl"]);
+    assert_eq!(jsonl.lines().count(), 3);
+    assert_eq!(jsonl, export(home.path(), &["--format", "ndjson"]));
+    assert_eq!(
+        export::read_jsonl(&jsonl).unwrap(),
+        expected_rows(home.path())
+    );
+
+    let json: Vec<ExportRow> =
+        serde_json::from_str(&export(home.path(), &["--format", "json"])).unwrap();
+    assert_eq!(json, expected_rows(home.path()));
+}
+
+#[test]
+fn test_export_range() {
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path());
+
+    let rows = export::read_csv(&export(
+        home.path(),
+        &["--since", "2026-10-02", "--until", "2026-10-03"],
+    ))
+    .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].timestamp.to_rfc3339(), "2026-10-02T08:00:00+00:00");
+
+    // An empty history exports just the header
+    let empty = tempfile::tempdir().unwrap();
+    assert_eq!(export(empty.path(), &[]).lines().count(), 1);
+}
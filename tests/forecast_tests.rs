@@ -0,0 +1,96 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{datapass, usage_page, write_config, StandInServer, StubResponse};
+use std::path::Path;
+
+/// Enable the history and seed it with readings `hours_ago` hours old
+fn seed_history(home: &Path, config: &str, readings: &[(i64, f64)]) {
+    write_config(home, config);
+
+    let data_dir = home.join("data").join("datapass");
+    std::fs::create_dir_all(&data_dir).unwrap();
+    let lines: String = readings
+        .iter()
+        .map(|(hours_ago, remaining_gb)| {
+            format!(
+                "{}\n",
+                serde_json::json!({
+                    "timestamp": Utc::now() - Duration::hours(*hours_ago),
+                    "remaining_gb": remaining_gb,
+                    "total_gb": 51.0,
+                    "used_gb": 51.0 - remaining_gb,
+                    "percentage": (51.0 - remaining_gb) / 51.0 * 100.0,
+                    "plan_name": "MagentaMobil Prepaid XL",
+                    "valid_until": null,
+                    "is_unlimited": false,
+                })
+            )
+        })
+        .collect();
+    std::fs::write(data_dir.join("history.jsonl"), lines).unwrap();
+}
+
+fn fetch_json(home: &Path, url: &str) -> serde_json::Value {
+    let output = datapass(home)
+        .args(["--no-cache", "--format", "json", "--url", url])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_json_forecast() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    // About 2 GB per day before the current reading of 38.36 GB
+    seed_history(home.path(), "[history]\n", &[(48, 42.36), (24, 40.36)]);
+
+    let json = fetch_json(home.path(), &server.url);
+    let forecast = &json["forecast"];
+
+    assert_eq!(forecast["method"], "linear");
+    assert_eq!(forecast["samples"], 3);
+    let rate = forecast["burn_rate_gb_per_day"].as_f64().unwrap();
+    assert!((rate - 2.0).abs() < 0.01, "rate: {}", rate);
+    assert!(forecast["exhausts_at"].is_string());
+    assert!(forecast["lasts_until_valid_until"].is_null());
+}
+
+#[test]
+fn test_forecast_window_and_method_are_configurable() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    seed_history(
+        home.path(),
+        "[history]\n[forecast]\nmethod = \"ewma\"\nwindow_hours = 12\n",
+        &[(48, 44.36), (6, 38.86)],
+    );
+
+    // The reading from two days ago is outside the window
+    let json = fetch_json(home.path(), &server.url);
+    assert_eq!(json["forecast"]["method"], "ewma");
+    assert_eq!(json["forecast"]["samples"], 2);
+    let rate = json["forecast"]["burn_rate_gb_per_day"].as_f64().unwrap();
+    assert!((rate - 2.0).abs() < 0.01, "rate: {}", rate);
+}
+
+#[test]
+fn test_human_forecast_line() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path(), "[history]\n", &[(24, 40.36)]);
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Forecast:  2.00 GB/day, runs out",
+        ));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{datapass, usage_page, write_config, StandInServer, StubResponse};
+use std::path::Path;
+
+/// Write a config file that enables the history
+fn enable_history(home: &Path, settings: &str) {
+    write_config(home, &format!("[history]\n{}", settings));
+}
+
+fn list_json(home: &Path, args: &[&str]) -> Vec<serde_json::Value> {
+    let output = datapass(home)
+        .args(["history", "list", "--format", "json"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_fetches_are_recorded_when_enabled() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+
+    // Recording is off without a [history] section
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .success();
+    assert!(list_json(home.path(), &[]).is_empty());
+
+    enable_history(home.path(), "");
+    for _ in 0..2 {
+        datapass(home.path())
+            .args(["--no-cache", "--url", &server.url])
+            .assert()
+            .success();
+    }
+
+    let entries = list_json(home.path(), &[]);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["remaining_gb"], 38.36);
+    assert_eq!(entries[0]["plan_name"], "MagentaMobil Prepaid XL");
+    assert!(entries[0]["timestamp"].is_string());
+    assert_eq!(entries[0]["passes"][0]["id"], "test-pass");
+
+    assert_eq!(list_json(home.path(), &["--limit", "1"]).len(), 1);
+    assert!(list_json(home.path(), &["--since", "2099-01-01"]).is_empty());
+}
+
+#[test]
+fn test_file_readings_are_not_recorded() {
+    let home = tempfile::tempdir().unwrap();
+    enable_history(home.path(), "");
+
+    datapass(home.path())
+        .args(["--file", "-"])
+        .write_stdin(usage_page("38,36", "51", None))
+        .assert()
+        .success();
+
+    assert!(list_json(home.path(), &[]).is_empty());
+}
+
+#[test]
+fn test_prune() {
+    let home = tempfile::tempdir().unwrap();
+    let store = home.path().join("history.jsonl");
+    enable_history(
+        home.path(),
+        &format!(
+            "path = {:?}\nretention_days = 30\n",
+            store.display().to_string()
+        ),
+    );
+
+    let old = r#"{"timestamp":"2020-01-01T00:00:00Z","remaining_gb":50.0,"total_gb":51.0,"used_gb":1.0,"percentage":1.96,"plan_name":null,"valid_until":null,"is_unlimited":false}"#;
+    let recent = old.replace(
+        "2020-01-01",
+        &chrono::Utc::now().format("%Y-%m-%d").to_string(),
+    );
+    std::fs::write(&store, format!("{}\n{}\n", old, recent)).unwrap();
+
+    datapass(home.path())
+        .args(["history", "prune"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("Removed 1 readings"));
+
+    assert_eq!(list_json(home.path(), &[]).len(), 1);
+
+    datapass(home.path())
+        .args(["history", "prune", "--older-than", "0s"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("Removed 1 readings"));
+
+    assert!(list_json(home.path(), &[]).is_empty());
+}
@@ -0,0 +1,65 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{datapass, usage_page, write_config, StandInServer, StubResponse};
+use std::path::Path;
+
+/// Config with hooks that write their stdin and environment next to `home`
+fn write_hooks_config(home: &Path, extra: &str) {
+    let out = home.display();
+    let config = format!(
+        r#"{extra}
+[hooks]
+on_fetch = "cat > '{out}/fetch.json'; echo \"$DATAPASS_EVENT $DATAPASS_REMAINING_GB $DATAPASS_PLAN_NAME\" > '{out}/fetch.env'"
+on_alert = "echo \"$DATAPASS_ALERT_RULE: $DATAPASS_ALERT_MESSAGE\" >> '{out}/alerts'"
+on_error = "cat > '{out}/error.json'"
+"#
+    );
+    write_config(home, &config);
+}
+
+#[test]
+fn test_fetch_and_alert_hooks() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    write_hooks_config(home.path(), "[alerts]\nusage_percent = [20]\n");
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(home.path().join("fetch.json")).unwrap())
+            .unwrap();
+    assert_eq!(json["remaining_gb"], 38.36);
+    assert_eq!(json["total_gb"], 51.0);
+    assert_eq!(
+        std::fs::read_to_string(home.path().join("fetch.env")).unwrap(),
+        "fetch 38.36 MagentaMobil Prepaid XL\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(home.path().join("alerts")).unwrap(),
+        "usage_percent:20: 24.8% of the volume used (alert at 20%)\n"
+    );
+    assert!(!home.path().join("error.json").exists());
+}
+
+#[test]
+fn test_error_hook() {
+    let server = StandInServer::start(|_| StubResponse::status(500, "down"));
+    let home = tempfile::tempdir().unwrap();
+    write_hooks_config(home.path(), "");
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .failure();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(home.path().join("error.json")).unwrap())
+            .unwrap();
+    assert!(json["error"].as_str().unwrap().contains("500"), "{}", json);
+    assert!(!home.path().join("fetch.json").exists());
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::time::Duration;
+
+/// `datapass` with all XDG directories in a temporary directory, in UTC
+fn datapass(home: &Path) -> assert_cmd::Command {
+    let mut cmd = common::datapass(home);
+    cmd.env("TZ", "UTC");
+    cmd
+}
+
+fn run(cmd: &mut assert_cmd::Command) -> String {
+    let output = cmd.output().unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn history(home: &Path) -> Vec<serde_json::Value> {
+    serde_json::from_str(&run(
+        datapass(home).args(["history", "list", "--format", "json"])
+    ))
+    .unwrap()
+}
+
+/// A `datapass --format json` output, saved by a script after a run; returns
+/// the file's modification time
+fn save_output(dir: &Path, name: &str, remaining_gb: f64) -> DateTime<Utc> {
+    let output = serde_json::json!({
+        "remaining_gb": remaining_gb,
+        "total_gb": 51.0,
+        "used_gb": 51.0 - remaining_gb,
+        "percentage": (51.0 - remaining_gb) / 51.0 * 100.0,
+        "plan_name": "MagentaMobil Prepaid XL",
+        "valid_until": "1. November 2026",
+        "is_unlimited": false,
+    });
+    let path = dir.join(name);
+    std::fs::write(&path, serde_json::to_string_pretty(&output).unwrap()).unwrap();
+    std::fs::metadata(&path).unwrap().modified().unwrap().into()
+}
+
+#[test]
+fn test_import_outputs_and_hand_log() {
+    let home = tempfile::tempdir().unwrap();
+    let outputs = tempfile::tempdir().unwrap();
+
+    let first_run = save_output(outputs.path(), "run-1.json", 40.0);
+    std::thread::sleep(Duration::from_millis(50));
+    let second_run = save_output(outputs.path(), "run-2.json", 35.0);
+
+    let log = outputs.path().join("by-hand.csv");
+    std::fs::write(
+        &log,
+        "timestamp,total_gb,remaining_gb\n2026-10-01T00:00:00Z,51,45\n2026-10-02T00:00:00Z,51,44\n",
+    )
+    .unwrap();
+
+    // A copy of the first hand-logged reading in a subdirectory
+    std::fs::create_dir(outputs.path().join("old")).unwrap();
+    std::fs::write(
+        outputs.path().join("old").join("copy.csv"),
+        "timestamp,total_gb,remaining_gb\n2026-10-01T00:00:00Z,51,45\n",
+    )
+    .unwrap();
+
+    let summary = run(datapass(home.path()).args(["import"]).arg(outputs.path()));
+    assert!(
+        summary.starts_with("Imported 4 of 5 readings"),
+        "{}",
+        summary
+    );
+    assert!(summary.contains("(1 already recorded)"));
+
+    let entries = history(home.path());
+    let timestamps: Vec<DateTime<Utc>> = entries
+        .iter()
+        .map(|entry| entry["timestamp"].as_str().unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(timestamps[0].to_rfc3339(), "2026-10-01T00:00:00+00:00");
+    assert_eq!(timestamps[1].to_rfc3339(), "2026-10-02T00:00:00+00:00");
+    // Outputs without a timestamp are dated by the file
+    assert_eq!(timestamps[2..], [first_run, second_run]);
+    assert_eq!(entries[3]["remaining_gb"], 35.0);
+
+    // Importing again adds nothing
+    let summary = run(datapass(home.path()).arg("import").arg(&log));
+    assert!(
+        summary.starts_with("Imported 0 of 2 readings"),
+        "{}",
+        summary
+    );
+    assert_eq!(history(home.path()).len(), 4);
+}
+
+#[test]
+fn test_export_import_round_trip() {
+    let home = tempfile::tempdir().unwrap();
+    let outputs = tempfile::tempdir().unwrap();
+    let csv = "timestamp,total_gb,remaining_gb,pass_name,zone\n\
+               2026-10-01T00:00:00Z,51,45,,\n\
+               2026-10-02T00:00:00Z,51,50,,\n\
+               2026-10-03T00:00:00Z,56,53,Prepaid XL,\"Germany, Country group 1\"\n";
+    std::fs::write(outputs.path().join("log.csv"), csv).unwrap();
+    run(datapass(home.path()).arg("import").arg(outputs.path()));
+
+    // The renewal and the booking are detected in the imported history
+    let entries = history(home.path());
+    assert_eq!(entries[1]["events"][0]["type"], "CycleStarted");
+    assert_eq!(entries[2]["events"][0]["type"], "PassBooked");
+    assert_eq!(entries[2]["pass_name"], "Prepaid XL");
+    assert_eq!(entries[2]["zone"], "Germany, Country group 1");
+
+    for format in ["csv", "jsonl"] {
+        let exported = outputs.path().join(format!("export.{}", format));
+        let text = run(datapass(home.path()).args(["export", "--format", format]));
+        std::fs::write(&exported, &text).unwrap();
+
+        let copy = tempfile::tempdir().unwrap();
+        run(datapass(copy.path()).arg("import").arg(&exported));
+        assert_eq!(
+            run(datapass(copy.path()).args(["export", "--format", format])),
+            text
+        );
+    }
+}
+
+#[test]
+fn test_invalid_file_imports_nothing() {
+    let home = tempfile::tempdir().unwrap();
+    let outputs = tempfile::tempdir().unwrap();
+    std::fs::write(
+        outputs.path().join("a.csv"),
+        "timestamp,total_gb,remaining_gb\n2026-10-01T00:00:00Z,51,45\n",
+    )
+    .unwrap();
+    std::fs::write(outputs.path().join("b.json"), "{ \"total_gb\": 51 }").unwrap();
+
+    datapass(home.path())
+        .arg("import")
+        .arg(outputs.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("b.json"));
+    assert!(history(home.path()).is_empty());
+}
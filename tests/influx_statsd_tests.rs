@@ -0,0 +1,113 @@
+mod common;
+
+use common::{datapass, datapass_process, write_config, StandInServer, StubResponse};
+use std::net::UdpSocket;
+use std::process::{Child, Stdio};
+use std::time::Duration;
+
+const USAGE_PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+    <head><title>Data usage - MagentaMobil Prepaid XL</title></head>
+    <body>
+        <section class="data-pass-instance" id="test-pass">
+            <div class="data-pass-instance__headline"><h2>MagentaMobil Prepaid XL</h2></div>
+            <div class="remaining-volume-value">2,5</div>
+            <div class="start-volume">51</div>
+            <div class="info-row">Valid in <a href="/zoneInfo">Germany, Country group 1</a></div>
+        </section>
+    </body>
+    </html>
+"#;
+
+/// Kills the daemon when the test ends, passed or not
+struct Running(Child);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Run `datapass daemon` against `url`, sending StatsD to a local socket,
+/// and return the first datagram it sends
+fn first_datagram(url: &str) -> String {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        &format!(
+            "[statsd]\nport = {}\nprefix = \"sim1.datapass\"\n",
+            socket.local_addr().unwrap().port()
+        ),
+    );
+
+    let _daemon = Running(
+        datapass_process(home.path())
+            .args(["--no-cache", "--url", url, "daemon", "--interval", "60"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    let mut buffer = [0; 1500];
+    let length = socket.recv(&mut buffer).expect("no datagram received");
+    String::from_utf8(buffer[..length].to_vec()).unwrap()
+}
+
+#[test]
+fn test_daemon_sends_gauges() {
+    let server = StandInServer::start(|_| StubResponse::html(USAGE_PAGE));
+
+    let datagram = first_datagram(&server.url);
+    let lines: Vec<&str> = datagram.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "sim1.datapass.remaining_bytes:2500000000|g",
+            "sim1.datapass.used_bytes:48500000000|g",
+            "sim1.datapass.total_bytes:51000000000|g",
+            "sim1.datapass.used_ratio:0.9509803921568627|g",
+            "sim1.datapass.unlimited:0|g",
+        ]
+    );
+}
+
+#[test]
+fn test_daemon_counts_fetch_errors() {
+    let server = StandInServer::start(|_| StubResponse::status(500, "down"));
+
+    assert_eq!(
+        first_datagram(&server.url),
+        "sim1.datapass.fetch_errors:1|c"
+    );
+}
+
+#[test]
+fn test_influx_format() {
+    let server = StandInServer::start(|_| StubResponse::html(USAGE_PAGE));
+    let home = tempfile::tempdir().unwrap();
+
+    let output = datapass(home.path())
+        .args(["--no-cache", "--url", &server.url, "--format", "influx"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let line = String::from_utf8(output.stdout).unwrap();
+    let rest = line
+        .strip_prefix(
+            "datapass,plan=MagentaMobil\\ Prepaid\\ XL,pass=MagentaMobil\\ Prepaid\\ XL,\
+             zone=Germany\\,\\ Country\\ group\\ 1 ",
+        )
+        .unwrap_or_else(|| panic!("unexpected measurement or tags: {}", line));
+    let (fields, timestamp) = rest.trim_end().rsplit_once(' ').unwrap();
+    assert!(fields.starts_with("remaining_bytes=2500000000i,used_bytes=48500000000i,"));
+    assert!(fields.ends_with(",unlimited=false,stale=false"));
+    assert_eq!(timestamp.len(), 19);
+}
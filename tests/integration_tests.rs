@@ -212,3 +212,53 @@ fn test_parse_validity_date_german() {
     let data = result.unwrap();
     assert_eq!(data.valid_until, Some("21. Februar 2026".to_string()));
 }
+
+#[test]
+fn test_parse_tariff_transparency() {
+    use datapass::parse_tariff_transparency;
+
+    let html = r#"
+        <!DOCTYPE html>
+        <html>
+        <head>
+            <title>Tariff transparency - MagentaMobil Prepaid L</title>
+        </head>
+        <body>
+            <ul>
+                <li>Maximum download speed: 300 Mbit/s</li>
+                <li>Estimated maximum download speed: 150 Mbit/s</li>
+                <li>Maximum upload speed: 50 Mbit/s</li>
+                <li>Estimated maximum upload speed: 25 Mbit/s</li>
+                <li>Price: 19.95 € per 4 weeks</li>
+            </ul>
+        </body>
+        </html>
+    "#;
+
+    let result = parse_tariff_transparency(html);
+    assert!(result.is_ok(), "Failed to parse HTML: {:?}", result.err());
+
+    let tariff = result.unwrap();
+    assert_eq!(tariff.plan_name, Some("MagentaMobil Prepaid L".to_string()));
+    assert_eq!(tariff.max_download_mbps, Some(300.0));
+    assert_eq!(tariff.estimated_download_mbps, Some(150.0));
+    assert_eq!(tariff.max_upload_mbps, Some(50.0));
+    assert_eq!(tariff.estimated_upload_mbps, Some(25.0));
+    assert_eq!(tariff.price, Some("19.95 € per 4 weeks".to_string()));
+}
+
+#[test]
+#[cfg(feature = "http")]
+fn test_tariff_transparency_url() {
+    use datapass::fetcher::tariff_transparency_url;
+
+    assert_eq!(
+        tariff_transparency_url(None).unwrap(),
+        "https://datapass.de/tariffTransparency/domestic"
+    );
+    assert_eq!(
+        tariff_transparency_url(Some("https://pass.telekom.de/home")).unwrap(),
+        "https://pass.telekom.de/tariffTransparency/domestic"
+    );
+    assert!(tariff_transparency_url(Some("not a url")).is_err());
+}
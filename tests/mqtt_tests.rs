@@ -0,0 +1,261 @@
+mod common;
+
+use common::{datapass_process, usage_page, write_config, StandInServer, StubResponse};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Child, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// What a client sent in CONNECT
+#[derive(Debug, Clone, Default)]
+struct Connect {
+    client_id: String,
+    will_topic: Option<String>,
+    will_message: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+/// A PUBLISH received by the broker
+#[derive(Debug, Clone)]
+struct Publish {
+    topic: String,
+    payload: String,
+    retain: bool,
+}
+
+#[derive(Debug, Default)]
+struct Received {
+    connects: Vec<Connect>,
+    publishes: Vec<Publish>,
+}
+
+/// MQTT 3.1.1 broker on a random local port that accepts every client and
+/// records what it publishes (without passing anything on)
+struct StandInBroker {
+    port: u16,
+    received: Arc<Mutex<Received>>,
+}
+
+impl StandInBroker {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(Received::default()));
+
+        let recorded = Arc::clone(&received);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let recorded = Arc::clone(&recorded);
+                std::thread::spawn(move || serve(stream, &recorded));
+            }
+        });
+
+        Self { port, received }
+    }
+
+    /// The last message published to `topic`, waiting up to ten seconds
+    fn wait_for(&self, topic: &str) -> Option<Publish> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if let Some(publish) = self.last(topic) {
+                return Some(publish);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        None
+    }
+
+    fn last(&self, topic: &str) -> Option<Publish> {
+        let received = self.received.lock().unwrap();
+        received
+            .publishes
+            .iter()
+            .rev()
+            .find(|publish| publish.topic == topic)
+            .cloned()
+    }
+}
+
+fn serve(mut stream: TcpStream, received: &Mutex<Received>) {
+    while let Some((header, body)) = read_packet(&mut stream) {
+        match header >> 4 {
+            // CONNECT
+            1 => {
+                received.lock().unwrap().connects.push(parse_connect(&body));
+                let _ = stream.write_all(&[0x20, 2, 0, 0]);
+            }
+            // PUBLISH
+            3 => {
+                let qos = (header >> 1) & 3;
+                let mut rest = &body[..];
+                let topic = take_string(&mut rest);
+                if qos > 0 {
+                    let id = [rest[0], rest[1]];
+                    rest = &rest[2..];
+                    let ack = if qos == 1 { 0x40 } else { 0x50 };
+                    let _ = stream.write_all(&[ack, 2, id[0], id[1]]);
+                }
+                received.lock().unwrap().publishes.push(Publish {
+                    topic,
+                    payload: String::from_utf8_lossy(rest).to_string(),
+                    retain: header & 1 == 1,
+                });
+            }
+            // PUBREL
+            6 => {
+                let _ = stream.write_all(&[0x70, 2, body[0], body[1]]);
+            }
+            // PINGREQ
+            12 => {
+                let _ = stream.write_all(&[0xD0, 0]);
+            }
+            // DISCONNECT
+            14 => return,
+            _ => {}
+        }
+    }
+}
+
+fn read_packet(stream: &mut TcpStream) -> Option<(u8, Vec<u8>)> {
+    let mut byte = [0u8];
+    stream.read_exact(&mut byte).ok()?;
+    let header = byte[0];
+
+    let mut length = 0usize;
+    let mut shift = 0;
+    loop {
+        stream.read_exact(&mut byte).ok()?;
+        length |= ((byte[0] & 0x7F) as usize) << shift;
+        shift += 7;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).ok()?;
+    Some((header, body))
+}
+
+fn take_string(rest: &mut &[u8]) -> String {
+    let length = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+    let text = String::from_utf8_lossy(&rest[2..2 + length]).to_string();
+    *rest = &rest[2 + length..];
+    text
+}
+
+fn parse_connect(body: &[u8]) -> Connect {
+    let mut rest = body;
+    take_string(&mut rest); // protocol name
+    let flags = rest[1];
+    rest = &rest[4..]; // level, flags, keep alive
+
+    let mut connect = Connect {
+        client_id: take_string(&mut rest),
+        ..Connect::default()
+    };
+    if flags & 0x04 != 0 {
+        connect.will_topic = Some(take_string(&mut rest));
+        connect.will_message = Some(take_string(&mut rest));
+    }
+    if flags & 0x80 != 0 {
+        connect.username = Some(take_string(&mut rest));
+    }
+    if flags & 0x40 != 0 {
+        connect.password = Some(take_string(&mut rest));
+    }
+    connect
+}
+
+/// `datapass` with all XDG directories in a temporary directory, left running
+fn spawn_datapass(home: &Path, args: &[&str]) -> Child {
+    datapass_process(home)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap()
+}
+
+/// Kills the daemon when the test ends, passed or not
+struct Running(Child);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn test_daemon_publishes_readings_and_discovery() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let broker = StandInBroker::start();
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        &format!(
+            r#"
+[mqtt]
+host = "127.0.0.1"
+port = {}
+username = "datapass"
+password = "s3cret"
+topic_prefix = "home/sim1"
+node_id = "sim1"
+"#,
+            broker.port
+        ),
+    );
+
+    let _daemon = Running(spawn_datapass(
+        home.path(),
+        &[
+            "--no-cache",
+            "--url",
+            &server.url,
+            "daemon",
+            "--interval",
+            "60",
+        ],
+    ));
+
+    let remaining = broker
+        .wait_for("home/sim1/remaining_gb")
+        .expect("no reading published");
+    assert_eq!(remaining.payload, "2.50");
+    assert!(remaining.retain);
+    assert_eq!(
+        broker.wait_for("home/sim1/plan").unwrap().payload,
+        "MagentaMobil Prepaid XL"
+    );
+    assert_eq!(
+        broker.wait_for("home/sim1/status").unwrap().payload,
+        "available"
+    );
+
+    let online = broker.wait_for("home/sim1/availability").unwrap();
+    assert_eq!(online.payload, "online");
+    assert!(online.retain);
+
+    let discovery = broker
+        .wait_for("homeassistant/sensor/sim1/remaining_gb/config")
+        .expect("no discovery config published");
+    assert!(discovery.retain);
+    let discovery: serde_json::Value = serde_json::from_str(&discovery.payload).unwrap();
+    assert_eq!(discovery["state_topic"], "home/sim1/remaining_gb");
+    assert_eq!(discovery["unit_of_measurement"], "GB");
+
+    let connect = broker.received.lock().unwrap().connects[0].clone();
+    assert_eq!(connect.client_id, "datapass");
+    assert_eq!(connect.username.as_deref(), Some("datapass"));
+    assert_eq!(connect.password.as_deref(), Some("s3cret"));
+    assert_eq!(
+        connect.will_topic.as_deref(),
+        Some("home/sim1/availability")
+    );
+    assert_eq!(connect.will_message.as_deref(), Some("offline"));
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use std::path::Path;
+
+/// `datapass` with all XDG directories in a temporary directory, in UTC
+fn datapass(home: &Path) -> assert_cmd::Command {
+    let mut cmd = common::datapass(home);
+    cmd.env("TZ", "UTC");
+    cmd
+}
+
+fn reading(timestamp: &str, remaining_gb: f64, total_gb: f64, valid_until: &str) -> String {
+    serde_json::json!({
+        "timestamp": timestamp,
+        "remaining_gb": remaining_gb,
+        "total_gb": total_gb,
+        "used_gb": total_gb - remaining_gb,
+        "percentage": (total_gb - remaining_gb) / total_gb * 100.0,
+        "plan_name": "MagentaMobil Prepaid XL",
+        "valid_until": valid_until,
+        "is_unlimited": false,
+    })
+    .to_string()
+}
+
+/// A history across a renewal on October 2 and a 5 GB booking on October 3
+fn seed_history(home: &Path) {
+    let dir = home.join("data").join("datapass");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let readings = [
+        reading("2026-10-01T12:00:00Z", 3.0, 51.0, "2. October 2026"),
+        reading("2026-10-01T23:00:00Z", 1.0, 51.0, "2. October 2026"),
+        reading("2026-10-02T01:00:00Z", 50.0, 51.0, "1. November 2026"),
+        reading("2026-10-03T00:00:00Z", 46.0, 51.0, "1. November 2026"),
+        reading("2026-10-03T12:00:00Z", 49.0, 56.0, "1. November 2026"),
+    ];
+    std::fs::write(dir.join("history.jsonl"), readings.join("\n") + "\n").unwrap();
+}
+
+fn report_json(home: &Path, args: &[&str]) -> Vec<serde_json::Value> {
+    let output = datapass(home)
+        .args(["report", "--format", "json"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_daily_report_across_renewal_and_booking() {
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path());
+
+    let days = report_json(home.path(), &["--by", "day"]);
+    assert_eq!(days.len(), 3);
+
+    let used: Vec<f64> = days
+        .iter()
+        .map(|day| day["used_gb"].as_f64().unwrap())
+        .collect();
+    // Oct 1: 2 GB plus half of the 1 GB used around the renewal
+    assert!((used[0] - 2.5).abs() < 1e-6, "{:?}", used);
+    // Oct 2: the other half plus 4 GB
+    assert!((used[1] - 4.5).abs() < 1e-6, "{:?}", used);
+    // Oct 3: 2 GB used while the booking added 5 GB
+    assert!((used[2] - 2.0).abs() < 1e-6, "{:?}", used);
+    assert!(used.iter().all(|used| *used >= 0.0));
+
+    assert_eq!(days[1]["renewals"], 1);
+    assert_eq!(days[2]["bookings"], 1);
+}
+
+#[test]
+fn test_report_range_and_csv() {
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path());
+
+    let days = report_json(
+        home.path(),
+        &["--since", "2026-10-02", "--until", "2026-10-03"],
+    );
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0]["period"], "2026-10-02");
+
+    datapass(home.path())
+        .args(["report", "--by", "week", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(
+            "period,start,end,used_gb,readings,renewals,bookings\n\
+             2026-W40,2026-09-28T00:00:00+00:00,2026-10-05T00:00:00+00:00,9.000,5,1,1\n",
+        ));
+}
+
+#[test]
+fn test_human_report() {
+    let home = tempfile::tempdir().unwrap();
+    seed_history(home.path());
+
+    datapass(home.path())
+        .args(["report", "--by", "day"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("2026-10-02"))
+        .stdout(predicates::str::contains("renewed"))
+        .stdout(predicates::str::contains("Total"));
+}
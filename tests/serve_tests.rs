@@ -0,0 +1,89 @@
+mod common;
+
+use common::{datapass_process, usage_page, StandInServer, StubResponse};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::{Child, Stdio};
+
+/// Kills the exporter when the test ends, passed or not
+struct Running(Child);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Start `datapass serve` on a random local port (all XDG directories in a
+/// temporary directory) and return it with the address it announced
+fn serve(home: &Path, args: &[&str]) -> (Running, String) {
+    let mut child = datapass_process(home)
+        .args(["serve", "--metrics", "127.0.0.1:0"])
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let address = line
+        .trim()
+        .strip_prefix("Serving metrics on http://")
+        .and_then(|rest| rest.strip_suffix("/metrics"))
+        .unwrap_or_else(|| panic!("unexpected announcement: {}", line))
+        .to_string();
+
+    (Running(child), address)
+}
+
+/// GET a path and return the whole response, status line included
+fn get(address: &str, path: &str) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, address).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn test_serve_metrics_fetches_once_per_interval() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    let (_exporter, address) = serve(home.path(), &["--no-cache", "--url", &server.url]);
+
+    let first = get(&address, "/metrics");
+    assert!(first.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(first.contains("Content-Type: text/plain; version=0.0.4"));
+    assert!(first.contains("\ndatapass_remaining_bytes 2500000000\n"));
+    assert!(first.contains("\ndatapass_total_bytes 51000000000\n"));
+    assert!(first.contains("\ndatapass_pass_info{plan=\"MagentaMobil Prepaid XL\"} 1\n"));
+    assert!(first.contains("\ndatapass_fetch_errors_total 0\n"));
+    assert!(first.contains("\ndatapass_last_success_timestamp "));
+
+    get(&address, "/metrics");
+    assert_eq!(server.requests().len(), 1);
+
+    assert!(get(&address, "/other").starts_with("HTTP/1.1 404 Not Found\r\n"));
+}
+
+#[test]
+fn test_serve_counts_fetch_errors() {
+    let server = StandInServer::start(|_| StubResponse::status(500, "down"));
+    let home = tempfile::tempdir().unwrap();
+    let (_exporter, address) = serve(
+        home.path(),
+        &["--no-cache", "--url", &server.url, "--min-interval", "0s"],
+    );
+
+    get(&address, "/metrics");
+    let metrics = get(&address, "/metrics");
+    assert!(metrics.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(!metrics.contains("datapass_remaining_bytes"));
+    assert!(!metrics.contains("datapass_last_success_timestamp"));
+    assert!(metrics.contains("\ndatapass_fetch_errors_total 3\n"));
+}
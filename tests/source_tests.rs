@@ -0,0 +1,50 @@
+mod common;
+
+use common::usage_page;
+use datapass::{DatapassError, MemorySource, Result, Source};
+
+/// A user-defined source that counts how often it was asked
+struct CountingSource {
+    fetches: usize,
+}
+
+impl Source for CountingSource {
+    fn fetch(&mut self) -> Result<String> {
+        self.fetches += 1;
+        Ok(usage_page(&format!("{},00", 40 - self.fetches), "51", None))
+    }
+}
+
+#[test]
+fn test_custom_source() {
+    let mut source = CountingSource { fetches: 0 };
+
+    let first = datapass::get_data_usage_from(&mut source).unwrap();
+    let second = datapass::get_data_usage_from(&mut source).unwrap();
+
+    assert_eq!(first.remaining_gb, 39.0);
+    assert_eq!(second.remaining_gb, 38.0);
+}
+
+#[test]
+fn test_scripted_memory_source() {
+    let mut source = MemorySource::scripted([
+        Err(DatapassError::DataNotFound("no coverage".to_string())),
+        Ok(usage_page("38,36", "51", None)),
+    ]);
+
+    assert!(source.fetch_usage().is_err());
+    assert_eq!(source.fetch_usage().unwrap().remaining_gb, 38.36);
+    assert_eq!(source.fetch_usage().unwrap().total_gb, 51.0);
+}
+
+#[test]
+#[cfg(all(feature = "cli", feature = "tui", feature = "color"))]
+fn test_cli_reads_stdin() {
+    assert_cmd::Command::new(env!("CARGO_BIN_EXE_datapass"))
+        .args(["--file", "-", "--remaining"])
+        .write_stdin(usage_page("38,36", "51", None))
+        .assert()
+        .success()
+        .stdout("38.36\n");
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{datapass, usage_page, StandInServer, StubResponse};
+
+/// URL of a local port nobody listens on
+fn unreachable_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+#[test]
+fn test_allow_stale_falls_back_to_last_reading() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+
+    datapass(home.path())
+        .args(["--format", "json", "--url", &server.url])
+        .assert()
+        .success();
+
+    let offline = unreachable_url();
+
+    // Without --allow-stale a failed fetch is still an error
+    datapass(home.path())
+        .args(["--url", &offline])
+        .assert()
+        .failure();
+
+    let output = datapass(home.path())
+        .args(["--format", "json", "--allow-stale", "1h", "--url", &offline])
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["remaining_gb"], 38.36);
+    assert!(json["stale"]["fetched_at"].is_string());
+    assert!(json["stale"]["age_seconds"].as_u64().unwrap() < 3600);
+}
+
+#[test]
+fn test_fresh_reading_is_not_stale() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+
+    let output = datapass(home.path())
+        .args([
+            "--format",
+            "json",
+            "--allow-stale",
+            "1h",
+            "--url",
+            &server.url,
+        ])
+        .output()
+        .unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json.get("stale").is_none());
+}
+
+#[test]
+fn test_allow_stale_single_value_warns_on_stderr() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+
+    datapass(home.path())
+        .args(["--remaining", "--url", &server.url])
+        .assert()
+        .success();
+
+    datapass(home.path())
+        .args([
+            "--remaining",
+            "--allow-stale",
+            "1d",
+            "--url",
+            &unreachable_url(),
+        ])
+        .assert()
+        .success()
+        .stdout("38.36\n")
+        .stderr(predicates::str::contains("last reading"));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{datapass, usage_page, StandInServer, StubResponse};
+use std::collections::HashMap;
+
+/// Parse text in the Prometheus format 0.0.4 the way node_exporter's textfile
+/// collector does: every sample must belong to the family declared right
+/// before it. Returns the samples by name (labels included) with their type.
+fn parse_text_format(text: &str) -> HashMap<String, (String, f64)> {
+    let mut types = HashMap::new();
+    let mut family: Option<(String, String)> = None;
+    let mut samples = HashMap::new();
+
+    for line in text.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            let mut parts = comment.splitn(3, ' ');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("TYPE"), Some(name), Some(kind)) => {
+                    assert!(
+                        types.insert(name.to_string(), kind.to_string()).is_none(),
+                        "family {} declared twice",
+                        name
+                    );
+                    family = Some((name.to_string(), kind.to_string()));
+                }
+                (Some("HELP"), Some(_), Some(_)) => {}
+                _ => panic!("unexpected comment: {}", line),
+            }
+            continue;
+        }
+
+        let (series, value) = line.rsplit_once(' ').expect("sample without value");
+        let name = series.split('{').next().unwrap();
+        let (family_name, kind) = family
+            .as_ref()
+            .unwrap_or_else(|| panic!("sample {} without a family", name));
+        assert_eq!(name, family_name, "sample outside its family");
+        samples.insert(
+            series.to_string(),
+            (kind.clone(), value.parse::<f64>().expect("invalid value")),
+        );
+    }
+
+    samples
+}
+
+#[test]
+fn test_prometheus_format_writes_textfile() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+    let textfile = home.path().join("datapass.prom");
+    std::fs::write(&textfile, "old contents\n").unwrap();
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url, "--format", "prometheus"])
+        .arg("--output")
+        .arg(&textfile)
+        .assert()
+        .success()
+        .stdout("");
+
+    let samples = parse_text_format(&std::fs::read_to_string(&textfile).unwrap());
+    assert_eq!(
+        samples["datapass_remaining_bytes"],
+        ("gauge".to_string(), 38_360_000_000.0)
+    );
+    assert_eq!(
+        samples["datapass_pass_info{plan=\"MagentaMobil Prepaid XL\"}"],
+        ("gauge".to_string(), 1.0)
+    );
+    assert_eq!(
+        samples["datapass_fetch_errors_total"],
+        ("counter".to_string(), 0.0)
+    );
+
+    // Only the output file is left behind, no temporary files
+    let files: Vec<_> = std::fs::read_dir(home.path())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .collect();
+    assert_eq!(files.len(), 1);
+}
+
+#[test]
+fn test_stale_reading_counts_as_fetch_error() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("38,36", "51", None)));
+    let home = tempfile::tempdir().unwrap();
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .success();
+
+    // A local port nobody listens on
+    let offline = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let output = datapass(home.path())
+        .args(["--no-cache", "--allow-stale", "1h", "--url", &offline])
+        .args(["--format", "prometheus"])
+        .output()
+        .unwrap();
+
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("\ndatapass_remaining_bytes 38360000000\n"));
+    assert!(text.contains("\ndatapass_fetch_errors_total 1\n"));
+}
+
+#[test]
+fn test_failed_fetch_keeps_output_file() {
+    let server = StandInServer::start(|_| StubResponse::status(500, "down"));
+    let home = tempfile::tempdir().unwrap();
+    let textfile = home.path().join("datapass.prom");
+    std::fs::write(&textfile, "old contents\n").unwrap();
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url, "--format", "prometheus"])
+        .arg("--output")
+        .arg(&textfile)
+        .assert()
+        .failure();
+
+    assert_eq!(
+        std::fs::read_to_string(&textfile).unwrap(),
+        "old contents\n"
+    );
+}
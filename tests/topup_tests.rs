@@ -0,0 +1,221 @@
+mod common;
+
+use common::{RecordedRequest, StandInServer, StubResponse};
+use datapass::config::Config;
+use datapass::topup::{AuditLog, TopUp, TopUpAction};
+use datapass::DataUsage;
+
+const MAIN_PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+    <head><title>Data usage - MagentaMobil Prepaid L</title></head>
+    <body>
+        <div class="pass-offer-card">
+            <div class="pass-offer-card__headline"><h3>SpeedOn M</h3></div>
+            <div class="volume ">3<span class="unit">GB</span></div>
+            <div class="price"><span class="price">14,<span class="fraction">95</span>&#160;&euro;</span></div>
+            <a href="/pass/select/SPDONM?crt=abc" class="pass-offer-card__call-to-action-link">Select</a>
+        </div>
+    </body>
+    </html>
+"#;
+
+const SELECT_PAGE: &str = r#"
+    <html><body>
+        <form action="/pass/confirm" method="post">
+            <input type="hidden" name="jakarta.faces.ViewState" value="1:2" />
+            <button type="submit" name="buy">Buy now</button>
+        </form>
+    </body></html>
+"#;
+
+fn stand_in(request: &RecordedRequest) -> StubResponse {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => StubResponse::html(MAIN_PAGE),
+        ("GET", "/pass/select/SPDONM?crt=abc") => StubResponse::html(SELECT_PAGE),
+        ("POST", "/pass/confirm") => StubResponse::html(
+            r#"<html><body><div class="message-box">Your pass has been booked.</div></body></html>"#,
+        ),
+        _ => StubResponse::status(404, "not found"),
+    }
+}
+
+fn policy_config(audit_log: &std::path::Path, max_spend: f64) -> Config {
+    Config::from_toml(&format!(
+        r#"
+        [topup]
+        enabled = true
+        offer = "SpeedOn M"
+        below_mb = 500
+        min_days_left = 3
+        max_per_cycle = 2
+        max_spend_eur = {}
+        cooldown_minutes = 0
+        audit_log = "{}"
+        "#,
+        max_spend,
+        audit_log.display()
+    ))
+    .unwrap()
+}
+
+fn low_usage() -> DataUsage {
+    DataUsage::new(
+        0.3,
+        25.0,
+        Some("MagentaMobil Prepaid L".to_string()),
+        Some("31. December 2099".to_string()),
+    )
+}
+
+#[test]
+fn test_topup_books_and_audits() {
+    let server = StandInServer::start(stand_in);
+    let dir = tempfile::tempdir().unwrap();
+    let audit_path = dir.path().join("audit.jsonl");
+
+    let policy = policy_config(&audit_path, 30.0).topup.unwrap();
+    let mut topup = TopUp::new(policy, Some(&server.url), None);
+
+    // Plenty of volume left: nothing happens, but the decision is audited
+    let high = DataUsage::new(10.0, 25.0, None, Some("31. December 2099".to_string()));
+    assert_eq!(topup.check(&high).unwrap().action, TopUpAction::Skipped);
+    assert!(server.requests().is_empty());
+
+    let entry = topup.check(&low_usage()).unwrap();
+    assert_eq!(entry.action, TopUpAction::Booked);
+    assert_eq!(entry.price_eur, Some(14.95));
+
+    // A second booking still fits the budget (29.90 € of 30 €),
+    // a third exceeds the per-cycle limit
+    assert_eq!(
+        topup.check(&low_usage()).unwrap().action,
+        TopUpAction::Booked
+    );
+    assert_eq!(
+        topup.check(&low_usage()).unwrap().action,
+        TopUpAction::Skipped
+    );
+
+    let posts = server
+        .requests()
+        .iter()
+        .filter(|request| request.method == "POST")
+        .count();
+    assert_eq!(posts, 2);
+
+    let audit = AuditLog::new(audit_path).read().unwrap();
+    assert_eq!(audit.len(), 4);
+}
+
+#[test]
+fn test_topup_respects_budget() {
+    let server = StandInServer::start(stand_in);
+    let dir = tempfile::tempdir().unwrap();
+    let audit_path = dir.path().join("audit.jsonl");
+
+    let policy = policy_config(&audit_path, 10.0).topup.unwrap();
+    let mut topup = TopUp::new(policy, Some(&server.url), None);
+
+    let entry = topup.check(&low_usage()).unwrap();
+    assert_eq!(entry.action, TopUpAction::Skipped);
+    assert!(entry.reason.contains("budget"), "reason: {}", entry.reason);
+    assert!(server
+        .requests()
+        .iter()
+        .all(|request| request.method == "GET"));
+}
+
+#[test]
+fn test_topup_does_not_rebook_after_failed_submission() {
+    let server = StandInServer::start(|request| match request.method.as_str() {
+        "POST" => StubResponse::status(500, "internal server error"),
+        _ => stand_in(request),
+    });
+    let dir = tempfile::tempdir().unwrap();
+    let audit_path = dir.path().join("audit.jsonl");
+
+    let mut policy = policy_config(&audit_path, 30.0).topup.unwrap();
+    policy.cooldown_minutes = 60;
+    let mut topup = TopUp::new(policy, Some(&server.url), None);
+
+    // The order may have gone through before the server failed
+    let entry = topup.check(&low_usage()).unwrap();
+    assert_eq!(entry.action, TopUpAction::Unconfirmed);
+    assert!(entry.reason.contains("500"), "reason: {}", entry.reason);
+
+    let entry = topup.check(&low_usage()).unwrap();
+    assert_eq!(entry.action, TopUpAction::Skipped);
+    assert!(
+        entry.reason.contains("cooldown"),
+        "reason: {}",
+        entry.reason
+    );
+
+    let posts = server
+        .requests()
+        .iter()
+        .filter(|request| request.method == "POST")
+        .count();
+    assert_eq!(posts, 1);
+}
+
+#[test]
+fn test_topup_logs_repeated_skips_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let audit_path = dir.path().join("audit.jsonl");
+
+    let policy = policy_config(&audit_path, 30.0).topup.unwrap();
+    let mut topup = TopUp::new(policy, None, None);
+
+    for remaining in [10.0, 9.5, 9.0] {
+        let usage = DataUsage::new(remaining, 25.0, None, Some("31. December 2099".to_string()));
+        assert_eq!(topup.check(&usage).unwrap().action, TopUpAction::Skipped);
+    }
+    let unlimited = DataUsage::new_unlimited(None, Some("31. December 2099".to_string()));
+    topup.check(&unlimited).unwrap();
+
+    let audit = AuditLog::new(audit_path).read().unwrap();
+    assert_eq!(audit.len(), 2);
+    assert_eq!(audit[0].remaining_gb, 10.0);
+    assert_eq!(audit[1].reason, "unlimited plan");
+}
+
+#[test]
+fn test_topup_does_not_retry_rejected_order_during_cooldown() {
+    let server = StandInServer::start(|request| match request.method.as_str() {
+        "POST" => StubResponse::html(
+            r#"<html><body><div class="message-box">The booking was rejected.</div></body></html>"#,
+        ),
+        _ => stand_in(request),
+    });
+    let dir = tempfile::tempdir().unwrap();
+    let audit_path = dir.path().join("audit.jsonl");
+
+    let mut policy = policy_config(&audit_path, 30.0).topup.unwrap();
+    policy.cooldown_minutes = 60;
+    let mut topup = TopUp::new(policy, Some(&server.url), None);
+
+    assert_eq!(
+        topup.check(&low_usage()).unwrap().action,
+        TopUpAction::Rejected
+    );
+    for _ in 0..2 {
+        let entry = topup.check(&low_usage()).unwrap();
+        assert_eq!(entry.action, TopUpAction::Skipped);
+        assert!(
+            entry.reason.contains("cooldown"),
+            "reason: {}",
+            entry.reason
+        );
+    }
+
+    let posts = server
+        .requests()
+        .iter()
+        .filter(|request| request.method == "POST")
+        .count();
+    assert_eq!(posts, 1);
+    // The repeated skip is audited once
+    assert_eq!(AuditLog::new(audit_path).read().unwrap().len(), 2);
+}
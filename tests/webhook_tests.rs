@@ -0,0 +1,124 @@
+mod common;
+
+use common::{datapass, usage_page, write_config, StandInServer, StubResponse};
+use predicates::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[test]
+fn test_alert_is_posted_with_template_and_headers() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let webhook = StandInServer::start(|_| StubResponse::status(204, ""));
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        &format!(
+            r#"
+[alerts]
+remaining_gb = [5]
+
+[[notify.webhook]]
+url = "{}/hooks/ops"
+template = '{{"text": "{{{{alert_message}}}} on {{{{plan_name}}}}", "left": {{{{remaining_gb}}}}}}'
+headers = {{ Authorization = "Bearer secret" }}
+"#,
+            webhook.url
+        ),
+    );
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .success();
+
+    let requests = webhook.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/hooks/ops");
+    assert_eq!(request.header("Authorization"), Some("Bearer secret"));
+    assert_eq!(request.header("Content-Type"), Some("application/json"));
+
+    let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(
+        body["text"],
+        "2.50 GB remaining (alert below 5 GB) on MagentaMobil Prepaid XL"
+    );
+    assert_eq!(body["left"], 2.5);
+}
+
+#[test]
+fn test_failed_posts_are_retried() {
+    let server = StandInServer::start(|_| StubResponse::html(&usage_page("2,5", "51", None)));
+    let attempts = AtomicUsize::new(0);
+    let webhook = StandInServer::start(move |_| {
+        if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+            StubResponse::status(503, "busy")
+        } else {
+            StubResponse::status(200, "ok")
+        }
+    });
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        &format!(
+            "[alerts]\nremaining_gb = [5]\n\n[[notify.webhook]]\nurl = \"{}\"\nretry_delay_ms = 10\n",
+            webhook.url
+        ),
+    );
+
+    datapass(home.path())
+        .args(["--no-cache", "--url", &server.url])
+        .assert()
+        .success();
+
+    // Without a template the alert and the reading are sent as JSON
+    let requests = webhook.requests();
+    assert_eq!(requests.len(), 3);
+    let body: serde_json::Value = serde_json::from_str(&requests[2].body).unwrap();
+    assert_eq!(body["alert"]["rule"], "remaining_gb:5");
+    assert_eq!(body["alert"]["severity"], "critical");
+    assert_eq!(body["usage"]["remaining_gb"], 2.5);
+}
+
+#[test]
+fn test_notify_test_command() {
+    let good = StandInServer::start(|_| StubResponse::status(200, "ok"));
+    let bad = StandInServer::start(|_| StubResponse::status(400, "bad template"));
+    let home = tempfile::tempdir().unwrap();
+    write_config(
+        home.path(),
+        &format!(
+            "[[notify.webhook]]\nurl = \"{}\"\ntemplate = \"{{{{alert_message}}}}\"\n\n[[notify.webhook]]\nurl = \"{}\"\n",
+            good.url, bad.url
+        ),
+    );
+
+    datapass(home.path())
+        .args(["notify", "test"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("webhook: sent"))
+        .stderr(predicate::str::contains("HTTP 400 Bad Request"))
+        .stderr(predicate::str::contains("1 of 2 notifiers failed"));
+
+    let requests = good.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].body, "Test notification from datapass");
+    assert_eq!(
+        requests[0].header("Content-Type"),
+        Some("text/plain; charset=utf-8")
+    );
+    // Client errors are not retried
+    assert_eq!(bad.requests().len(), 1);
+}
+
+#[test]
+fn test_notify_test_without_notifiers() {
+    let home = tempfile::tempdir().unwrap();
+
+    datapass(home.path())
+        .args(["notify", "test"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No notifiers configured"));
+}